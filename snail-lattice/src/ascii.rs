@@ -0,0 +1,446 @@
+// Plain text representation of a maze, in the same style as the pacsnail board. Every cell and
+// every wall between two cells takes up one character, so an SxS maze is a grid of
+// (2S + 1)x(2S + 1) characters, one row per line:
+//
+//   #########
+//   #S .#   #
+//   # ### # #
+//   #*  #g G#
+//   #########
+//
+// Wall characters are either '#' or ' ', and the outer border must be closed. Cell characters
+// are one of:
+//
+//   ' '  empty          '.'  pellet         'g'  grass          'l'  slime
+//   'S'  start          '*'  powerup        'm'  mud            'r'  rubble
+//   'G'  goal           '#'  outside mask   'i'  ice
+//
+// A cell only holds a single character, so start and goal cells are plain terrain without a
+// pellet. Without an explicit 'S' or 'G' the maze uses the default top left start and bottom
+// right goal.
+
+use std::fmt;
+
+use crate::{
+    direction::Direction,
+    maze::{Maze, Terrain, CELLS_PER_IDX},
+    utils::Vec2,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pellet {
+    Pellet,
+    Powerup,
+    None,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    WrongSize { expected: usize, found: usize },
+    WrongWidth { line: usize, expected: usize, found: usize },
+    UnknownCharacter { line: usize, column: usize, character: char },
+    OpenBorder { line: usize, column: usize },
+    DuplicateMarker { line: usize, column: usize, character: char },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ParseError::WrongSize { expected, found } => {
+                write!(f, "expected {expected} lines, found {found}")
+            }
+            ParseError::WrongWidth {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line}: expected {expected} characters, found {found}"
+            ),
+            ParseError::UnknownCharacter {
+                line,
+                column,
+                character,
+            } => write!(f, "line {line}, column {column}: unexpected {character:?}"),
+            ParseError::OpenBorder { line, column } => {
+                write!(f, "line {line}, column {column}: the border must be a wall")
+            }
+            ParseError::DuplicateMarker {
+                line,
+                column,
+                character,
+            } => write!(f, "line {line}, column {column}: {character:?} appears twice"),
+        }
+    }
+}
+
+// A maze along with the pellets placed in it
+pub struct Board<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    pub maze: Maze<S>,
+    pub pellets: Vec<Pellet>,
+}
+
+impl<const S: usize> Board<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    pub fn from_maze(maze: Maze<S>) -> Board<S> {
+        Board {
+            maze,
+            pellets: vec![Pellet::None; S * S],
+        }
+    }
+
+    pub fn pellet_count(&self) -> usize {
+        self.pellets.iter().filter(|p| **p != Pellet::None).count()
+    }
+
+    pub fn parse(text: &str) -> Result<Board<S>, ParseError> {
+        let width = 2 * S + 1;
+        let lines: Vec<&[u8]> = text
+            .trim_matches(|c| c == '\n' || c == '\r')
+            .lines()
+            .map(|line| line.trim_end_matches('\r').as_bytes())
+            .collect();
+
+        if lines.len() != width {
+            return Err(ParseError::WrongSize {
+                expected: width,
+                found: lines.len(),
+            });
+        }
+
+        for (y, line) in lines.iter().enumerate() {
+            if line.len() != width {
+                return Err(ParseError::WrongWidth {
+                    line: y + 1,
+                    expected: width,
+                    found: line.len(),
+                });
+            }
+
+            for (x, c) in line.iter().enumerate() {
+                let border = x == 0 || y == 0 || x == width - 1 || y == width - 1;
+
+                // cells are handled separately below
+                if x % 2 == 1 && y % 2 == 1 {
+                    continue;
+                }
+
+                match *c {
+                    b'#' => {}
+                    b' ' if border => {
+                        return Err(ParseError::OpenBorder {
+                            line: y + 1,
+                            column: x + 1,
+                        })
+                    }
+                    b' ' => {}
+                    c => {
+                        return Err(ParseError::UnknownCharacter {
+                            line: y + 1,
+                            column: x + 1,
+                            character: c as char,
+                        })
+                    }
+                }
+            }
+        }
+
+        let mut board = Board::from_maze(Maze::new());
+        let mut mask = [true; S * S];
+        let mut start = None;
+        let mut goal = None;
+
+        for y in 0..S {
+            for x in 0..S {
+                let (line, column) = (2 * y + 1, 2 * x + 1);
+                let c = lines[line][column];
+                let mut cell = 0;
+
+                for direction in [
+                    Direction::Up,
+                    Direction::Down,
+                    Direction::Left,
+                    Direction::Right,
+                ] {
+                    let wall = match direction {
+                        Direction::Up => lines[line - 1][column],
+                        Direction::Down => lines[line + 1][column],
+                        Direction::Left => lines[line][column - 1],
+                        Direction::Right => lines[line][column + 1],
+                    };
+
+                    if wall == b'#' {
+                        cell |= direction.to_wall();
+                    }
+                }
+
+                board.maze.xor_cell(x, y, cell);
+
+                let marker = match c {
+                    b'S' => &mut start,
+                    b'G' => &mut goal,
+                    _ => {
+                        let (pellet, terrain) = match c {
+                            b' ' => (Pellet::None, Terrain::Plain),
+                            b'.' => (Pellet::Pellet, Terrain::Plain),
+                            b'*' => (Pellet::Powerup, Terrain::Plain),
+                            b'g' => (Pellet::None, Terrain::Grass),
+                            b'm' => (Pellet::None, Terrain::Mud),
+                            b'i' => (Pellet::None, Terrain::Ice),
+                            b'l' => (Pellet::None, Terrain::Slime),
+                            b'r' => (Pellet::None, Terrain::Rubble),
+                            b'#' => {
+                                mask[y * S + x] = false;
+                                continue;
+                            }
+                            c => {
+                                return Err(ParseError::UnknownCharacter {
+                                    line: line + 1,
+                                    column: column + 1,
+                                    character: c as char,
+                                })
+                            }
+                        };
+
+                        board.pellets[y * S + x] = pellet;
+                        board.maze.set_terrain(x, y, terrain);
+                        continue;
+                    }
+                };
+
+                if marker.replace(Vec2 { x, y }).is_some() {
+                    return Err(ParseError::DuplicateMarker {
+                        line: line + 1,
+                        column: column + 1,
+                        character: c as char,
+                    });
+                }
+            }
+        }
+
+        board.maze.set_mask(&mask);
+
+        if let Some(start) = start {
+            board.maze.start_pos = start;
+        }
+
+        if let Some(goal) = goal {
+            board.maze.end_pos = goal;
+        }
+
+        Ok(board)
+    }
+
+    fn cell_char(&self, x: usize, y: usize) -> char {
+        if !self.maze.is_valid(x, y) {
+            return '#';
+        }
+
+        let pos = Vec2 { x, y };
+        let c = match (self.pellets[y * S + x], self.maze.get_terrain(x, y)) {
+            (Pellet::Pellet, _) => '.',
+            (Pellet::Powerup, _) => '*',
+            (Pellet::None, Terrain::Plain) => ' ',
+            (Pellet::None, Terrain::Grass) => 'g',
+            (Pellet::None, Terrain::Mud) => 'm',
+            (Pellet::None, Terrain::Ice) => 'i',
+            (Pellet::None, Terrain::Slime) => 'l',
+            (Pellet::None, Terrain::Rubble) => 'r',
+        };
+
+        // a marker in its default position can be left out, which keeps whatever else is in the
+        // cell (e.g. the pellet in the corner of the pacsnail board)
+        if pos == self.maze.start_pos && (c == ' ' || pos != Vec2 { x: 0, y: 0 }) {
+            'S'
+        } else if pos == self.maze.end_pos && (c == ' ' || pos != Vec2 { x: S - 1, y: S - 1 }) {
+            'G'
+        } else {
+            c
+        }
+    }
+
+    // whether the wall character at (x, y) in the text grid is a wall. x and y must not both be
+    // even, since those are corners.
+    fn is_wall(&self, x: usize, y: usize) -> bool {
+        let width = 2 * S + 1;
+
+        if x == 0 || y == 0 || x == width - 1 || y == width - 1 {
+            true
+        } else if y % 2 == 1 {
+            // wall between two horizontally adjacent cells
+            self.maze
+                .get_cell((x - 1) / 2, y / 2)
+                .has_wall(Direction::Right)
+        } else {
+            self.maze
+                .get_cell(x / 2, (y - 1) / 2)
+                .has_wall(Direction::Down)
+        }
+    }
+}
+
+impl<const S: usize> fmt::Display for Board<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = 2 * S + 1;
+
+        for y in 0..width {
+            for x in 0..width {
+                let c = match (x % 2, y % 2) {
+                    (1, 1) => self.cell_char(x / 2, y / 2),
+
+                    // corners are only drawn when they touch a wall
+                    (0, 0) => {
+                        let touches_wall = (x > 0 && self.is_wall(x - 1, y))
+                            || (x < width - 1 && self.is_wall(x + 1, y))
+                            || (y > 0 && self.is_wall(x, y - 1))
+                            || (y < width - 1 && self.is_wall(x, y + 1));
+
+                        if touches_wall {
+                            '#'
+                        } else {
+                            ' '
+                        }
+                    }
+
+                    _ if self.is_wall(x, y) => '#',
+                    _ => ' ',
+                };
+
+                write!(f, "{c}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Board, ParseError, Pellet};
+    use crate::{
+        lattice::TilableMaze,
+        lfsr::LFSR,
+        maze::{AutoMaze, Maze, Terrain, SNAIL_MOVEMENT_TIME},
+        solvers::{HoldLeft, SolveStatus, Tremaux},
+        utils::Vec2,
+    };
+
+    const BOARD: &str = concat!(
+        "###########\n",
+        "#S . .#   #\n",
+        "# ### # # #\n",
+        "#*  #g  #m#\n",
+        "### # ### #\n",
+        "#i   .#   #\n",
+        "# ##### # #\n",
+        "#l  #   #r#\n",
+        "#   # ### #\n",
+        "#   #    G#\n",
+        "###########\n",
+    );
+
+    #[test]
+    fn parse_markers() {
+        let board = Board::<5>::parse(BOARD).unwrap();
+
+        assert_eq!(board.maze.start_pos, Vec2 { x: 0, y: 0 });
+        assert_eq!(board.maze.end_pos, Vec2 { x: 4, y: 4 });
+        assert_eq!(board.pellets[1], Pellet::Pellet);
+        assert_eq!(board.pellets[5], Pellet::Powerup);
+        assert_eq!(board.pellet_count(), 4);
+        assert_eq!(board.maze.get_terrain(2, 1), Terrain::Grass);
+        assert_eq!(board.maze.get_terrain(4, 1), Terrain::Mud);
+        assert_eq!(board.maze.get_terrain(0, 2), Terrain::Ice);
+        assert_eq!(board.maze.get_terrain(0, 3), Terrain::Slime);
+        assert_eq!(board.maze.get_terrain(4, 3), Terrain::Rubble);
+    }
+
+    #[test]
+    fn round_trip_text() {
+        let board = Board::<5>::parse(BOARD).unwrap();
+        assert_eq!(board.to_string(), BOARD);
+    }
+
+    #[test]
+    fn round_trip_generated() {
+        let mut rng = LFSR::new(0xBEEF);
+
+        for _ in 0..20 {
+            let mut maze = Maze::<12>::new();
+            maze.generate(&mut rng);
+            maze.generate_terrain(&mut rng);
+
+            let text = Board::from_maze(maze).to_string();
+            let parsed = Board::<12>::parse(&text).unwrap();
+
+            assert_eq!(parsed.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn solvers_run_on_boards() {
+        let mut rng = LFSR::new(0xBEEF);
+
+        let mut hold_left = AutoMaze::<5, HoldLeft<5>>::new();
+        hold_left.load(Board::parse(BOARD).unwrap().maze, &mut rng);
+
+        let mut tremaux = AutoMaze::<5, Tremaux<5>>::new();
+        tremaux.load(Board::parse(BOARD).unwrap().maze, &mut rng);
+
+        let mut solved = (false, false);
+        for _ in 0..200 {
+            if let SolveStatus::Solved(_) = hold_left.tick(SNAIL_MOVEMENT_TIME, &mut rng) {
+                solved.0 = true;
+            }
+            if let SolveStatus::Solved(_) = tremaux.tick(SNAIL_MOVEMENT_TIME, &mut rng) {
+                solved.1 = true;
+            }
+        }
+
+        assert_eq!(solved, (true, true));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Board::<2>::parse("#####\n# # #\n#####\n").err(),
+            Some(ParseError::WrongSize {
+                expected: 5,
+                found: 3
+            })
+        );
+
+        assert_eq!(
+            Board::<2>::parse("#####\n#S  #\n# #  \n#  G#\n#####\n").err(),
+            Some(ParseError::OpenBorder { line: 3, column: 5 })
+        );
+
+        assert_eq!(
+            Board::<2>::parse("#####\n#S  #\n# # #\n#  x#\n#####\n").err(),
+            Some(ParseError::UnknownCharacter {
+                line: 4,
+                column: 4,
+                character: 'x'
+            })
+        );
+
+        assert_eq!(
+            Board::<2>::parse("#####\n#S  #\n# # #\n#  S#\n#####\n").err(),
+            Some(ParseError::DuplicateMarker {
+                line: 4,
+                column: 4,
+                character: 'S'
+            })
+        );
+    }
+}
@@ -7,6 +7,13 @@ pub enum Direction {
 }
 
 impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
     pub fn from_number(num: usize) -> Direction {
         match num {
             0 => Direction::Up,
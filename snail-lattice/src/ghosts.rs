@@ -0,0 +1,365 @@
+// The ghosts from PacSnail, which can hunt a snail through any maze. Like in pacman, a ghost
+// never turns around unless it's at a dead end, and at every junction it takes whichever turn
+// leads closest to its target. What the target is depends on the ghost's personality and on the
+// status all of the ghosts share.
+
+use crate::{
+    direction::Direction,
+    image::Image,
+    maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    pathfinding::FieldCache,
+    rng::Rng,
+    snail::{Snail, INVERTED_PALETTE},
+    utils::{lerpi, Vec2, Vec2f},
+};
+
+pub const GHOST_MOVEMENT_TIME: f32 = SNAIL_MOVEMENT_TIME * 1.6;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GhostStatus {
+    Chase,
+    Scatter,
+    Frightened,
+}
+
+// Each entry represents 2 seconds
+const SCATTER_SCHEDULE: &[GhostStatus] = &[
+    GhostStatus::Scatter,
+    GhostStatus::Scatter,
+    GhostStatus::Scatter,
+    GhostStatus::Scatter,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Scatter,
+    GhostStatus::Scatter,
+    GhostStatus::Scatter,
+    GhostStatus::Scatter,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Scatter,
+    GhostStatus::Scatter,
+    GhostStatus::Scatter,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Chase,
+    GhostStatus::Scatter,
+    GhostStatus::Scatter,
+    GhostStatus::Scatter,
+    GhostStatus::Chase,
+];
+
+impl GhostStatus {
+    // the ghosts take a few breaks from chasing early on, then chase for good
+    pub fn scheduled(time: f32) -> GhostStatus {
+        SCATTER_SCHEDULE[((time / 2000.0).floor() as usize).min(SCATTER_SCHEDULE.len() - 1)]
+    }
+}
+
+pub const BLINKY_PALETTE: [[u8; 3]; 6] = [
+    [0xff, 0x00, 0x00], // orange
+    [0xff, 0x55, 0x00], // purple
+    [0xff, 0x00, 0x00], // orange
+    [0xff, 0xff, 0xff], // snail "eyes"
+    [0x00, 0x00, 0x00],
+    [0x00, 0x0A, 0x00],
+];
+
+pub const PINKY_PALETTE: [[u8; 3]; 6] = [
+    [0xff, 0x00, 0xff], // orange
+    [0xff, 0x55, 0xff], // purple
+    [0xff, 0x00, 0xff], // orange
+    [0xff, 0xff, 0xff], // snail "eyes"
+    [0x00, 0x00, 0x00],
+    [0x00, 0x0A, 0x00],
+];
+
+pub const CLYDE_PALETTE: [[u8; 3]; 6] = [
+    [0xaa, 0xaa, 0x00], // orange
+    [0xff, 0xaa, 0x00], // purple
+    [0xaa, 0xaa, 0x00], // orange
+    [0xff, 0xff, 0xff], // snail "eyes"
+    [0x00, 0x00, 0x00],
+    [0x00, 0x0A, 0x00],
+];
+
+pub const INKY_PALETTE: [[u8; 3]; 6] = [
+    [0x55, 0xaa, 0xff], // light blue
+    [0x55, 0xff, 0xff], // lighter lbue
+    [0x55, 0xaa, 0xff], // light blue
+    [0xff, 0xff, 0xff], // snail "eyes"
+    [0x00, 0x00, 0x00],
+    [0x00, 0x0A, 0x00],
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Personality {
+    // Scatters to top right
+    // Targets the snail directly during chase
+    Blinky,
+
+    // Scatters to top left
+    // Targets 1 tile in front of the snail during chase
+    Pinky,
+
+    // Scatters to bottom left
+    // Targets the snail during chase, unless it's close by, in which case it goes back to its
+    // scatter point instead
+    Clyde,
+
+    // Scatters to bottom right
+    // should target an extended vector from blinky's line of sight to the snail, but that would
+    // need every ghost to know where the others are, so we instead target one space behind the
+    // snail.
+    Inky,
+}
+
+impl Personality {
+    pub const ALL: [Personality; 4] = [
+        Personality::Blinky,
+        Personality::Pinky,
+        Personality::Clyde,
+        Personality::Inky,
+    ];
+
+    // the corner of a maze of the given size the ghost scatters to, one cell in from the edges
+    pub fn corner(self, size: usize) -> Vec2 {
+        let far = size.saturating_sub(2);
+
+        match self {
+            Personality::Blinky => Vec2 { x: far, y: 1 },
+            Personality::Pinky => Vec2 { x: 1, y: 1 },
+            Personality::Clyde => Vec2 { x: 1, y: far },
+            Personality::Inky => Vec2 { x: far, y: far },
+        }
+    }
+
+    pub fn palette(self) -> [[u8; 3]; 6] {
+        match self {
+            Personality::Blinky => BLINKY_PALETTE,
+            Personality::Pinky => PINKY_PALETTE,
+            Personality::Clyde => CLYDE_PALETTE,
+            Personality::Inky => INKY_PALETTE,
+        }
+    }
+}
+
+pub struct Ghost<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    pub snail: Snail<S>,
+    personality: Personality,
+
+    // where the ghost starts out, and heads for while scattering
+    home: Vec2,
+}
+
+impl<const S: usize> Ghost<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    pub fn new(personality: Personality, home: Vec2) -> Ghost<S> {
+        let mut ghost = Ghost {
+            snail: Snail::new(),
+            personality,
+            home,
+        };
+        ghost.reset();
+
+        ghost
+    }
+
+    pub fn reset(&mut self) {
+        self.snail.place(self.home);
+    }
+
+    // position in pixels, fact of the way from the last cell to the current one
+    pub fn pos(&self, fact: f32) -> Vec2f {
+        let snail = &self.snail;
+
+        Vec2f {
+            x: lerpi(snail.prev_pos.x as i32 * 10, snail.pos.x as i32 * 10, fact) as f32,
+            y: lerpi(snail.prev_pos.y as i32 * 10, snail.pos.y as i32 * 10, fact) as f32,
+        }
+    }
+
+    // whether the ghost is on the same cell as the snail, or they just walked past each other
+    pub fn catches(&self, snail: &Snail<S>) -> bool {
+        self.snail.pos == snail.pos
+            || (self.snail.pos == snail.prev_pos && self.snail.prev_pos == snail.pos)
+    }
+
+    // the cell the ghost heads for while chasing the snail
+    pub fn chase_target(&self, snail_pos: Vec2, snail_direction: Direction) -> Vec2 {
+        let ahead = |direction: Direction| {
+            let mut target_position = snail_pos;
+
+            match direction {
+                Direction::Up => target_position.y = target_position.y.saturating_sub(1),
+                Direction::Down => target_position.y = (target_position.y + 1).min(S - 1),
+                Direction::Left => target_position.x = target_position.x.saturating_sub(1),
+                Direction::Right => target_position.x = (target_position.x + 1).min(S - 1),
+            }
+
+            target_position
+        };
+
+        match self.personality {
+            Personality::Blinky => snail_pos,
+            Personality::Pinky => ahead(snail_direction),
+            Personality::Clyde => {
+                if snail_pos.manhattan_dist(self.snail.pos) < 5 {
+                    self.home
+                } else {
+                    snail_pos
+                }
+            }
+            Personality::Inky => ahead(snail_direction.flip()),
+        }
+    }
+
+    // fields has to be cleared whenever the walls of the maze change
+    pub fn step(
+        &mut self,
+        status: GhostStatus,
+        rng: &mut dyn Rng,
+        maze: &Maze<S>,
+        fields: &mut FieldCache<Direction>,
+        snail_pos: Vec2,
+        snail_direction: Direction,
+    ) {
+        let target = match status {
+            GhostStatus::Chase => Some(self.chase_target(snail_pos, snail_direction)),
+            GhostStatus::Scatter => Some(self.home),
+            GhostStatus::Frightened => None,
+        };
+
+        let snail = &mut self.snail;
+        let pos = snail.pos;
+        let behind_dir = snail.direction.flip();
+
+        let mut options = maze.get_cell(pos.x, pos.y).valid_directions();
+        // nowhere to go, e.g. on a cell outside of the maze's mask
+        if options.is_empty() {
+            snail.place(pos);
+            return;
+        }
+
+        if options.len() > 1 {
+            options.retain(|dir| *dir != behind_dir);
+        }
+
+        let closest_to = |fields: &mut FieldCache<Direction>, target: Vec2| {
+            let field = fields.get(maze, target);
+
+            options
+                .iter()
+                .copied()
+                .map(|dir| (dir, field.cost(maze, maze.next_pos(pos, dir))))
+                .filter(|(_, cost)| *cost != usize::MAX)
+                .min_by_key(|(_, cost)| *cost)
+                .map(|(dir, _)| dir)
+        };
+
+        // frightened ghosts wander around at random, and ghosts whose target can't be reached go
+        // for the snail instead
+        let direction = target.and_then(|target| {
+            closest_to(fields, target).or_else(|| closest_to(fields, snail_pos))
+        });
+
+        snail.direction = match direction {
+            Some(dir) => dir,
+            None => options[rng.next() as usize % options.len()],
+        };
+
+        snail.move_forward(maze);
+    }
+
+    pub fn draw(
+        &self,
+        status: GhostStatus,
+        animation_cycle: bool,
+        progress: f32,
+        image: &mut Image,
+        bx: usize,
+        by: usize,
+    ) {
+        let palette = match status {
+            GhostStatus::Chase | GhostStatus::Scatter => self.personality.palette(),
+            GhostStatus::Frightened => INVERTED_PALETTE,
+        };
+
+        self.snail
+            .draw(palette, animation_cycle, progress, image, bx, by);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ghost, GhostStatus, Personality};
+    use crate::{
+        direction::Direction, maze::Maze, pathfinding::FieldCache, rng::Xoshiro256, snail::Snail,
+        utils::Vec2,
+    };
+
+    #[test]
+    fn hunts_down_a_snail() {
+        let mut rng = Xoshiro256::new(0xBEEF);
+        let mut fields = FieldCache::new();
+
+        for _ in 0..20 {
+            let mut maze = Maze::<12>::new();
+            maze.generate(&mut rng);
+            fields.clear();
+
+            let mut snail = Snail::new();
+            snail.place(maze.start_pos);
+
+            let mut ghost = Ghost::new(Personality::Blinky, Personality::Blinky.corner(12));
+            assert_eq!(ghost.snail.pos, Vec2 { x: 10, y: 1 });
+
+            let steps = (0..1000).position(|_| {
+                ghost.step(
+                    GhostStatus::Chase,
+                    &mut rng,
+                    &maze,
+                    &mut fields,
+                    snail.pos,
+                    Direction::Right,
+                );
+                ghost.catches(&snail)
+            });
+            assert!(steps.is_some());
+        }
+    }
+
+    #[test]
+    fn scatters_before_chasing() {
+        assert_eq!(GhostStatus::scheduled(0.0), GhostStatus::Scatter);
+        assert_eq!(GhostStatus::scheduled(10000.0), GhostStatus::Chase);
+        assert_eq!(GhostStatus::scheduled(1e9), GhostStatus::Chase);
+    }
+}
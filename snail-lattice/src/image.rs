@@ -3,6 +3,12 @@ use crate::{
     utils::{Vec2i},
 };
 
+pub const SNAIL_IMAGE_SIZE: usize = 8;
+
+// facing down, 255 is transparent
+pub const SNAIL_IMAGE: &[u8; SNAIL_IMAGE_SIZE * SNAIL_IMAGE_SIZE] =
+    include_bytes!("../../assets/snail1_8x8.bin");
+
 pub struct Image<'a> {
     pub buffer: &'a mut [u8],
     pub width: usize,
@@ -87,73 +93,9 @@ impl<'a> Image<'a> {
     }
 
     fn draw_char(&mut self, c: char, x: usize, y: usize) {
-        let character_buffer = match c {
-            'a' => include_bytes!("../../assets/bitmap_font/a.bin"),
-            'b' => include_bytes!("../../assets/bitmap_font/b.bin"),
-            'c' => include_bytes!("../../assets/bitmap_font/c.bin"),
-            'd' => include_bytes!("../../assets/bitmap_font/d.bin"),
-            'e' => include_bytes!("../../assets/bitmap_font/e.bin"),
-            'f' => include_bytes!("../../assets/bitmap_font/f.bin"),
-            'g' => include_bytes!("../../assets/bitmap_font/g.bin"),
-            'h' => include_bytes!("../../assets/bitmap_font/h.bin"),
-            'i' => include_bytes!("../../assets/bitmap_font/i.bin"),
-            'j' => include_bytes!("../../assets/bitmap_font/j.bin"),
-            'k' => include_bytes!("../../assets/bitmap_font/k.bin"),
-            'l' => include_bytes!("../../assets/bitmap_font/l.bin"),
-            'm' => include_bytes!("../../assets/bitmap_font/m.bin"),
-            'n' => include_bytes!("../../assets/bitmap_font/n.bin"),
-            'o' => include_bytes!("../../assets/bitmap_font/o.bin"),
-            'p' => include_bytes!("../../assets/bitmap_font/p.bin"),
-            'q' => include_bytes!("../../assets/bitmap_font/q.bin"),
-            'r' => include_bytes!("../../assets/bitmap_font/r.bin"),
-            's' => include_bytes!("../../assets/bitmap_font/s.bin"),
-            't' => include_bytes!("../../assets/bitmap_font/t.bin"),
-            'u' => include_bytes!("../../assets/bitmap_font/u.bin"),
-            'v' => include_bytes!("../../assets/bitmap_font/v.bin"),
-            'w' => include_bytes!("../../assets/bitmap_font/w.bin"),
-            'x' => include_bytes!("../../assets/bitmap_font/x.bin"),
-            'y' => include_bytes!("../../assets/bitmap_font/y.bin"),
-            'z' => include_bytes!("../../assets/bitmap_font/z.bin"),
-            '0' => include_bytes!("../../assets/bitmap_font/0.bin"),
-            '1' => include_bytes!("../../assets/bitmap_font/1.bin"),
-            '2' => include_bytes!("../../assets/bitmap_font/2.bin"),
-            '3' => include_bytes!("../../assets/bitmap_font/3.bin"),
-            '4' => include_bytes!("../../assets/bitmap_font/4.bin"),
-            '5' => include_bytes!("../../assets/bitmap_font/5.bin"),
-            '6' => include_bytes!("../../assets/bitmap_font/6.bin"),
-            '7' => include_bytes!("../../assets/bitmap_font/7.bin"),
-            '8' => include_bytes!("../../assets/bitmap_font/8.bin"),
-            '9' => include_bytes!("../../assets/bitmap_font/9.bin"),
-            ':' => include_bytes!("../../assets/bitmap_font/:.bin"),
-
-            _ => unreachable!(),
-        };
-
         let px = 4 * (y * self.width + x);
-        for byte in character_buffer {
-            let x = byte >> 6;
-            let y = (byte >> 4) & 0b11;
-
-            if x == 3 && y == 3 {
-                break;
-            }
-
-            self.draw_pixel(
-                px + 4 * (y as usize * self.width + x as usize),
-                [0xFF, 0xFF, 0xFF],
-            );
-
-            let x = (byte >> 2) & 0b11;
-            let y = byte & 0b11;
-
-            if x == 3 && y == 3 {
-                break;
-            }
-
-            self.draw_pixel(
-                px + 4 * (y as usize * self.width + x as usize),
-                [0xFF, 0xFF, 0xFF],
-            );
+        for (x, y) in glyph(c) {
+            self.draw_pixel(px + 4 * (y * self.width + x), [0xFF, 0xFF, 0xFF]);
         }
     }
 
@@ -279,13 +221,11 @@ impl<'a> Image<'a> {
         dy: usize,
     ) {
         let snail_image = if animation_cycle {
-            include_bytes!("../../assets/snail1_8x8.bin")
+            SNAIL_IMAGE
         } else {
             include_bytes!("../../assets/snail2_8x8.bin")
         };
 
-        const SNAIL_IMAGE_SIZE: usize = 8;
-
         // draw goal
         for y in 0..SNAIL_IMAGE_SIZE {
             for x in 0..SNAIL_IMAGE_SIZE {
@@ -316,3 +256,65 @@ impl<'a> Image<'a> {
         }
     }
 }
+
+// pixels of a character in the bitmap font, relative to its top left corner. characters fit in a
+// 3x4 box. each byte stores two pixels as 2 bit x and y coordinates, and (3, 3) marks the end.
+pub fn glyph(c: char) -> Vec<(usize, usize)> {
+    let character_buffer: &[u8] = match c {
+        'a' => include_bytes!("../../assets/bitmap_font/a.bin"),
+        'b' => include_bytes!("../../assets/bitmap_font/b.bin"),
+        'c' => include_bytes!("../../assets/bitmap_font/c.bin"),
+        'd' => include_bytes!("../../assets/bitmap_font/d.bin"),
+        'e' => include_bytes!("../../assets/bitmap_font/e.bin"),
+        'f' => include_bytes!("../../assets/bitmap_font/f.bin"),
+        'g' => include_bytes!("../../assets/bitmap_font/g.bin"),
+        'h' => include_bytes!("../../assets/bitmap_font/h.bin"),
+        'i' => include_bytes!("../../assets/bitmap_font/i.bin"),
+        'j' => include_bytes!("../../assets/bitmap_font/j.bin"),
+        'k' => include_bytes!("../../assets/bitmap_font/k.bin"),
+        'l' => include_bytes!("../../assets/bitmap_font/l.bin"),
+        'm' => include_bytes!("../../assets/bitmap_font/m.bin"),
+        'n' => include_bytes!("../../assets/bitmap_font/n.bin"),
+        'o' => include_bytes!("../../assets/bitmap_font/o.bin"),
+        'p' => include_bytes!("../../assets/bitmap_font/p.bin"),
+        'q' => include_bytes!("../../assets/bitmap_font/q.bin"),
+        'r' => include_bytes!("../../assets/bitmap_font/r.bin"),
+        's' => include_bytes!("../../assets/bitmap_font/s.bin"),
+        't' => include_bytes!("../../assets/bitmap_font/t.bin"),
+        'u' => include_bytes!("../../assets/bitmap_font/u.bin"),
+        'v' => include_bytes!("../../assets/bitmap_font/v.bin"),
+        'w' => include_bytes!("../../assets/bitmap_font/w.bin"),
+        'x' => include_bytes!("../../assets/bitmap_font/x.bin"),
+        'y' => include_bytes!("../../assets/bitmap_font/y.bin"),
+        'z' => include_bytes!("../../assets/bitmap_font/z.bin"),
+        '0' => include_bytes!("../../assets/bitmap_font/0.bin"),
+        '1' => include_bytes!("../../assets/bitmap_font/1.bin"),
+        '2' => include_bytes!("../../assets/bitmap_font/2.bin"),
+        '3' => include_bytes!("../../assets/bitmap_font/3.bin"),
+        '4' => include_bytes!("../../assets/bitmap_font/4.bin"),
+        '5' => include_bytes!("../../assets/bitmap_font/5.bin"),
+        '6' => include_bytes!("../../assets/bitmap_font/6.bin"),
+        '7' => include_bytes!("../../assets/bitmap_font/7.bin"),
+        '8' => include_bytes!("../../assets/bitmap_font/8.bin"),
+        '9' => include_bytes!("../../assets/bitmap_font/9.bin"),
+        ':' => include_bytes!("../../assets/bitmap_font/:.bin"),
+
+        _ => unreachable!(),
+    };
+
+    let mut pixels = vec![];
+    for byte in character_buffer {
+        for nibble in [byte >> 4, byte & 0b1111] {
+            let x = (nibble >> 2) as usize;
+            let y = (nibble & 0b11) as usize;
+
+            if x == 3 && y == 3 {
+                return pixels;
+            }
+
+            pixels.push((x, y));
+        }
+    }
+
+    pixels
+}
@@ -1,16 +1,24 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryInto,
+};
 
 use wasm_bindgen::prelude::*;
 
 use crate::{
     image::Image,
-    lfsr::LFSR,
+    layered::Tower,
     maze::AutoMaze,
+    race::Race,
+    rng::{Rng, RngKind},
+    share::{CodeError, ShareCode},
     solvers::{
-        Automaton, Clones, Demolitionist, Flying, HoldLeft, Inverted, Learning, RandomTeleport,
-        RandomWalk, Rpg, SolveStatus, Telepathic, TimeTravel, Tremaux,
+        AStar, AntColony, Automaton, Bidirectional, Boids, Clones, DeadEndFilling, Demolitionist,
+        Flying, HoldLeft, Inverted, Learning, Pledge, QLearning, RandomTeleport, RandomWalk, Rpg,
+        Scripted, SolveStatus, Telepathic, TimeTravel, Tremaux,
     },
     utils::set_panic_hook,
+    vm::{Program, VmError},
 };
 
 #[derive(Clone, Copy)]
@@ -23,15 +31,48 @@ pub enum MazeType {
     Clone,
 }
 
+// each maze in a lattice is generated from a seed of its own, so it can be shared on its own
+pub const MAZE_RNG: RngKind = RngKind::Xoshiro;
+
 pub trait TilableMaze {
     const SIZE: usize;
 
+    // whether the tile's maze can be built again from a share code, see generate_from. tiles
+    // holding several mazes which each move on at their own pace can't be
+    const SHAREABLE: bool = false;
+
     fn new() -> Self;
-    fn tick(&mut self, dt: f32, lfsr: &mut LFSR) -> SolveStatus;
+    fn tick(&mut self, dt: f32, rng: &mut dyn Rng) -> SolveStatus;
     fn set_upgrades(&mut self, upgrades: u32);
-    fn draw_foreground(&mut self, lfsr: &mut LFSR, image: &mut Image, bx: usize, by: usize);
+    fn set_modifiers(&mut self, modifiers: u32);
+    fn draw_foreground(&mut self, rng: &mut dyn Rng, image: &mut Image, bx: usize, by: usize);
     fn draw_background(&mut self, image: &mut Image, bx: usize, by: usize);
-    fn generate(&mut self, lfsr: &mut LFSR);
+    fn generate(&mut self, rng: &mut dyn Rng);
+
+    // generates the maze a MAZE_RNG seed stands for. rng is still used for everything which
+    // doesn't change the maze itself, like the snails. only called on SHAREABLE tiles
+    fn generate_from(&mut self, _seed: u32, rng: &mut dyn Rng) {
+        self.generate(rng);
+    }
+
+    // the seed the current maze was generated from, for SHAREABLE tiles
+    fn seed(&self) -> u32 {
+        0
+    }
+
+    fn snapshot(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    fn restore(&mut self, _data: &[u8]) {}
+
+    fn set_program(&mut self, _program: &Program) {}
+
+    fn program_error(&self) -> Option<VmError> {
+        None
+    }
+
+    fn set_mode(&mut self, _mode: u32) {}
 }
 
 pub struct SnailLattice<LatticeElement>
@@ -40,8 +81,17 @@ where
 {
     width: usize,
     mazes: Vec<LatticeElement>,
-    lfsr: LFSR,
+    seed: u32,
+    rng_kind: RngKind,
+    rng: Box<dyn Rng>,
     upgrades: u32,
+    modifiers: u32,
+    // the player's program, given to new mazes as well, see Scripted
+    program: Option<Program>,
+    // see Solver::set_mode, given to new mazes as well
+    mode: u32,
+    // the seed from a share code, which the next new maze is generated from
+    shared_seed: Option<u32>,
 
     // stores the number of mazes solved by a given maze since the last query
     solve_count: Vec<u32>,
@@ -55,27 +105,65 @@ where
 }
 
 impl<LatticeElement: TilableMaze> SnailLattice<LatticeElement> {
-    pub fn new(width: usize, seed: u16) -> SnailLattice<LatticeElement> {
+    pub fn new(width: usize, seed: u32) -> SnailLattice<LatticeElement> {
         #[cfg(feature = "console_error_panic_hook")]
         set_panic_hook();
 
         let mut lattice = SnailLattice::<LatticeElement> {
             width,
             upgrades: 0,
+            modifiers: 0,
+            program: None,
+            mode: 0,
+            shared_seed: None,
             mazes: Vec::new(),
-            lfsr: LFSR::new(seed),
+            seed,
+            rng_kind: RngKind::Lfsr,
+            rng: RngKind::Lfsr.build(seed),
             solve_count: Vec::new(),
             bg_buffers: BTreeMap::new(),
             render_marked: BTreeSet::new(),
         };
 
         for maze in lattice.mazes.iter_mut() {
-            maze.generate(&mut lattice.lfsr);
+            maze.generate(&mut *lattice.rng);
         }
 
         lattice
     }
 
+    // a lattice whose first maze is the one the code was made from
+    pub fn from_code(
+        width: usize,
+        code: &ShareCode,
+        algorithm: u8,
+    ) -> Result<SnailLattice<LatticeElement>, CodeError> {
+        code.expect(algorithm, LatticeElement::SIZE)?;
+
+        if !LatticeElement::SHAREABLE {
+            return Err(CodeError::NotShareable);
+        }
+
+        if code.rng != MAZE_RNG {
+            return Err(CodeError::UnknownRng(code.rng.number() as u8));
+        }
+
+        let mut lattice = SnailLattice::new(width, code.seed);
+        lattice.shared_seed = Some(code.seed);
+
+        Ok(lattice)
+    }
+
+    // a code for the maze at index as it was when it was generated
+    pub fn share_code(&self, algorithm: u8, index: usize) -> Result<ShareCode, CodeError> {
+        if !LatticeElement::SHAREABLE {
+            return Err(CodeError::NotShareable);
+        }
+
+        let maze = self.mazes.get(index).ok_or(CodeError::NoMaze { index })?;
+        ShareCode::new(algorithm, LatticeElement::SIZE, MAZE_RNG, maze.seed())
+    }
+
     pub fn count(&self) -> usize {
         self.mazes.len()
     }
@@ -166,7 +254,7 @@ impl<LatticeElement: TilableMaze> SnailLattice<LatticeElement> {
         };
 
         for maze in self.mazes.iter_mut().skip(index).take(count) {
-            maze.draw_foreground(&mut self.lfsr, &mut image, cx, cy);
+            maze.draw_foreground(&mut *self.rng, &mut image, cx, cy);
 
             cx += maze_size;
             if cx >= dimensions[0] {
@@ -183,6 +271,41 @@ impl<LatticeElement: TilableMaze> SnailLattice<LatticeElement> {
         }
     }
 
+    // takes effect the next time each maze is generated
+    pub fn set_modifiers(&mut self, modifiers: u32) {
+        self.modifiers = modifiers;
+        for maze in &mut self.mazes {
+            maze.set_modifiers(self.modifiers);
+        }
+    }
+
+    pub fn set_program(&mut self, program: Program) {
+        for maze in &mut self.mazes {
+            maze.set_program(&program);
+        }
+
+        self.program = Some(program);
+    }
+
+    pub fn set_mode(&mut self, mode: u32) {
+        self.mode = mode;
+        for maze in &mut self.mazes {
+            maze.set_mode(self.mode);
+        }
+    }
+
+    // the first error any of the mazes' programs stopped with
+    pub fn program_error(&self) -> Option<VmError> {
+        self.mazes.iter().find_map(|maze| maze.program_error())
+    }
+
+    // swaps out the random number generator for a fresh one of a different kind, starting from
+    // the lattice's original seed
+    pub fn set_rng(&mut self, kind: RngKind) {
+        self.rng_kind = kind;
+        self.rng = kind.build(self.seed);
+    }
+
     pub fn set_width(&mut self, width: usize) {
         self.width = width;
 
@@ -211,13 +334,13 @@ impl<LatticeElement: TilableMaze> SnailLattice<LatticeElement> {
         let mut total = 0;
 
         for (i, maze) in self.mazes.iter_mut().enumerate() {
-            match maze.tick(dt, &mut self.lfsr) {
+            match maze.tick(dt, &mut *self.rng) {
                 SolveStatus::Solved(count) => {
                     total += count;
                     self.solve_count[i] += count as u32;
                     self.render_marked.insert(i);
                 }
-                SolveStatus::Rerender => {
+                SolveStatus::Rerender | SolveStatus::KeyCollected => {
                     self.render_marked.insert(i);
                 }
                 SolveStatus::None => {}
@@ -227,6 +350,48 @@ impl<LatticeElement: TilableMaze> SnailLattice<LatticeElement> {
         total
     }
 
+    // the state of every maze worth keeping between page loads, each prefixed by its length as a
+    // little endian u32. mazes themselves aren't included, since they're cheap to generate again
+    pub fn snapshot(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+
+        for maze in &self.mazes {
+            let snapshot = maze.snapshot();
+
+            data.extend_from_slice(&(snapshot.len() as u32).to_le_bytes());
+            data.extend_from_slice(&snapshot);
+        }
+
+        data
+    }
+
+    // restores the mazes which currently exist from a snapshot, in order. anything left over in
+    // the snapshot is ignored
+    pub fn restore(&mut self, mut data: &[u8]) -> Result<(), String> {
+        for maze in &mut self.mazes {
+            if data.is_empty() {
+                break;
+            }
+
+            if data.len() < 4 {
+                return Err("the snapshot is truncated".to_string());
+            }
+
+            let (len, rest) = data.split_at(4);
+            let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+
+            if rest.len() < len {
+                return Err("the snapshot is truncated".to_string());
+            }
+
+            let (snapshot, rest) = rest.split_at(len);
+            maze.restore(snapshot);
+            data = rest;
+        }
+
+        Ok(())
+    }
+
     pub fn alter(&mut self, difference: i32) {
         if difference < 0 {
             for _ in 0..difference.abs() {
@@ -242,10 +407,19 @@ impl<LatticeElement: TilableMaze> SnailLattice<LatticeElement> {
             for _ in 0..difference {
                 let mut new_maze = LatticeElement::new();
                 new_maze.set_upgrades(self.upgrades);
-                new_maze.generate(&mut self.lfsr);
+                new_maze.set_modifiers(self.modifiers);
+                if let Some(program) = &self.program {
+                    new_maze.set_program(program);
+                }
+                new_maze.set_mode(self.mode);
+
+                match self.shared_seed.take() {
+                    Some(seed) => new_maze.generate_from(seed, &mut *self.rng),
+                    None => new_maze.generate(&mut *self.rng),
+                }
 
                 // offset time slightly
-                new_maze.tick(time_offset, &mut self.lfsr);
+                new_maze.tick(time_offset, &mut *self.rng);
 
                 self.render_marked.insert(self.mazes.len());
                 self.mazes.push(new_maze);
@@ -299,18 +473,30 @@ impl TilableMaze for MetaMaze {
         self.clone.set_upgrades((upgrades >> 24) & 0b111);
     }
 
-    fn tick(&mut self, dt: f32, lfsr: &mut LFSR) -> SolveStatus {
+    fn set_modifiers(&mut self, modifiers: u32) {
+        self.random_walk.set_modifiers(modifiers);
+        self.random_teleport.set_modifiers(modifiers);
+        self.learning.set_modifiers(modifiers);
+        self.hold_left.set_modifiers(modifiers);
+        self.inverted.set_modifiers(modifiers);
+        self.tremaux.set_modifiers(modifiers);
+        self.rpg.set_modifiers(modifiers);
+        self.time_travel.set_modifiers(modifiers);
+        self.clone.set_modifiers(modifiers);
+    }
+
+    fn tick(&mut self, dt: f32, rng: &mut dyn Rng) -> SolveStatus {
         let mut total = 0;
 
-        total += self.random_walk.tick(dt, lfsr).get_count();
-        total += self.random_teleport.tick(dt, lfsr).get_count();
-        total += self.learning.tick(dt, lfsr).get_count();
-        total += self.hold_left.tick(dt, lfsr).get_count();
-        total += self.inverted.tick(dt, lfsr).get_count();
-        total += self.tremaux.tick(dt, lfsr).get_count();
-        total += self.time_travel.tick(dt, lfsr).get_count();
-        total += self.clone.tick(dt, lfsr).get_count();
-        total += self.rpg.tick(dt, lfsr).get_count();
+        total += self.random_walk.tick(dt, rng).get_count();
+        total += self.random_teleport.tick(dt, rng).get_count();
+        total += self.learning.tick(dt, rng).get_count();
+        total += self.hold_left.tick(dt, rng).get_count();
+        total += self.inverted.tick(dt, rng).get_count();
+        total += self.tremaux.tick(dt, rng).get_count();
+        total += self.time_travel.tick(dt, rng).get_count();
+        total += self.clone.tick(dt, rng).get_count();
+        total += self.rpg.tick(dt, rng).get_count();
 
         if total > 0 {
             SolveStatus::Solved(total)
@@ -319,18 +505,18 @@ impl TilableMaze for MetaMaze {
         }
     }
 
-    fn draw_foreground(&mut self, lfsr: &mut LFSR, image: &mut Image, bx: usize, by: usize) {
-        self.random_walk.draw_foreground(lfsr, image, bx, by);
+    fn draw_foreground(&mut self, rng: &mut dyn Rng, image: &mut Image, bx: usize, by: usize) {
+        self.random_walk.draw_foreground(rng, image, bx, by);
         self.random_teleport
-            .draw_foreground(lfsr, image, bx + 70, by);
-        self.learning.draw_foreground(lfsr, image, bx + 140, by);
-        self.hold_left.draw_foreground(lfsr, image, bx, by + 70);
-        self.inverted.draw_foreground(lfsr, image, bx + 70, by + 70);
-        self.tremaux.draw_foreground(lfsr, image, bx + 140, by + 70);
-        self.rpg.draw_foreground(lfsr, image, bx, by + 140);
+            .draw_foreground(rng, image, bx + 70, by);
+        self.learning.draw_foreground(rng, image, bx + 140, by);
+        self.hold_left.draw_foreground(rng, image, bx, by + 70);
+        self.inverted.draw_foreground(rng, image, bx + 70, by + 70);
+        self.tremaux.draw_foreground(rng, image, bx + 140, by + 70);
+        self.rpg.draw_foreground(rng, image, bx, by + 140);
         self.time_travel
-            .draw_foreground(lfsr, image, bx + 70, by + 140);
-        self.clone.draw_foreground(lfsr, image, bx + 140, by + 140);
+            .draw_foreground(rng, image, bx + 70, by + 140);
+        self.clone.draw_foreground(rng, image, bx + 140, by + 140);
     }
 
     fn draw_background(&mut self, image: &mut Image, bx: usize, by: usize) {
@@ -345,31 +531,47 @@ impl TilableMaze for MetaMaze {
         self.clone.draw_background(image, bx + 140, by + 140);
     }
 
-    fn generate(&mut self, lfsr: &mut LFSR) {
-        self.random_walk.generate(lfsr);
-        self.random_teleport.generate(lfsr);
-        self.learning.generate(lfsr);
-        self.hold_left.generate(lfsr);
-        self.inverted.generate(lfsr);
-        self.tremaux.generate(lfsr);
-        self.rpg.generate(lfsr);
-        self.time_travel.generate(lfsr);
-        self.clone.generate(lfsr);
+    fn generate(&mut self, rng: &mut dyn Rng) {
+        self.random_walk.generate(rng);
+        self.random_teleport.generate(rng);
+        self.learning.generate(rng);
+        self.hold_left.generate(rng);
+        self.inverted.generate(rng);
+        self.tremaux.generate(rng);
+        self.rpg.generate(rng);
+        self.time_travel.generate(rng);
+        self.clone.generate(rng);
     }
 }
 
 macro_rules! lattice_impl {
-    ($name:tt, $tile:ty) => {
+    ($name:tt, $tile:ty, $algorithm:expr) => {
         #[wasm_bindgen]
         pub struct $name(SnailLattice<$tile>);
 
         #[wasm_bindgen]
         impl $name {
             #[wasm_bindgen(constructor)]
-            pub fn new(width: usize, seed: u16) -> Self {
+            pub fn new(width: usize, seed: u32) -> Self {
                 Self(SnailLattice::new(width, seed))
             }
 
+            #[wasm_bindgen]
+            pub fn from_code(width: usize, code: &str) -> Result<$name, String> {
+                ShareCode::parse(code)
+                    .and_then(|code| SnailLattice::from_code(width, &code, $algorithm))
+                    .map(Self)
+                    .map_err(|err| err.to_string())
+            }
+
+            #[wasm_bindgen]
+            pub fn share_code(&self, index: usize) -> Result<String, String> {
+                self.0
+                    .share_code($algorithm, index)
+                    .map(|code| code.to_string())
+                    .map_err(|err| err.to_string())
+            }
+
             #[wasm_bindgen]
             pub fn get_dimensions(&self, count: usize) -> Vec<usize> {
                 self.0.get_dimensions(count)
@@ -385,6 +587,16 @@ macro_rules! lattice_impl {
                 self.0.set_upgrades(upgrades);
             }
 
+            #[wasm_bindgen]
+            pub fn set_modifiers(&mut self, modifiers: u32) {
+                self.0.set_modifiers(modifiers);
+            }
+
+            #[wasm_bindgen]
+            pub fn set_mode(&mut self, mode: u32) {
+                self.0.set_mode(mode);
+            }
+
             #[wasm_bindgen]
             pub fn render(&mut self, buffer: &mut [u8], index: usize, count: usize) {
                 self.0.render(buffer, index, count);
@@ -400,6 +612,16 @@ macro_rules! lattice_impl {
                 self.0.alter(difference);
             }
 
+            #[wasm_bindgen]
+            pub fn snapshot(&self) -> Vec<u8> {
+                self.0.snapshot()
+            }
+
+            #[wasm_bindgen]
+            pub fn restore(&mut self, data: &[u8]) -> Result<(), String> {
+                self.0.restore(data)
+            }
+
             #[wasm_bindgen]
             pub fn count(&self) -> usize {
                 self.0.mazes.len()
@@ -409,21 +631,156 @@ macro_rules! lattice_impl {
             pub fn set_width(&mut self, width: usize) {
                 self.0.set_width(width);
             }
+
+            // 0 for the original lfsr, 1 for xoshiro
+            #[wasm_bindgen]
+            pub fn set_rng(&mut self, kind: u32) {
+                if let Some(kind) = RngKind::from_number(kind) {
+                    self.0.set_rng(kind);
+                }
+            }
         }
     };
 }
 
-lattice_impl!(RandomWalkLattice, AutoMaze<5, RandomWalk<5>>);
-lattice_impl!(RandomTeleportLattice, AutoMaze<7, RandomTeleport<7>>);
-lattice_impl!(LearningLattice, AutoMaze<9, Learning<9>>);
-lattice_impl!(HoldLeftLattice, AutoMaze<9, HoldLeft<9>>);
-lattice_impl!(InvertedLattice, AutoMaze<9, Inverted<9>>);
-lattice_impl!(TremauxLattice, AutoMaze<11, Tremaux<11>>);
-lattice_impl!(RpgLattice, AutoMaze<11, Rpg<11>>);
-lattice_impl!(TimeTravelLattice, AutoMaze<13, TimeTravel<13>>);
-lattice_impl!(CloneLattice, AutoMaze<20, Clones<20>>);
-lattice_impl!(MetaLattice, MetaMaze);
-lattice_impl!(DemolitionistLattice, AutoMaze<15, Demolitionist<15>>);
-lattice_impl!(FlyingLattice, AutoMaze<15, Flying<15>>);
-lattice_impl!(TelepathicLattice, AutoMaze<11, Telepathic<11>>);
-lattice_impl!(AutomatonLattice, AutoMaze<20, Automaton<20>>);
+lattice_impl!(RandomWalkLattice, AutoMaze<5, RandomWalk<5>>, 16);
+lattice_impl!(RandomTeleportLattice, AutoMaze<7, RandomTeleport<7>>, 17);
+lattice_impl!(LearningLattice, AutoMaze<9, Learning<9>>, 18);
+lattice_impl!(HoldLeftLattice, AutoMaze<9, HoldLeft<9>>, 19);
+lattice_impl!(InvertedLattice, AutoMaze<9, Inverted<9>>, 20);
+lattice_impl!(TremauxLattice, AutoMaze<11, Tremaux<11>>, 21);
+lattice_impl!(RpgLattice, AutoMaze<11, Rpg<11>>, 22);
+lattice_impl!(TimeTravelLattice, AutoMaze<13, TimeTravel<13>>, 23);
+lattice_impl!(CloneLattice, AutoMaze<20, Clones<20>>, 24);
+lattice_impl!(MetaLattice, MetaMaze, 25);
+lattice_impl!(DemolitionistLattice, AutoMaze<15, Demolitionist<15>>, 26);
+lattice_impl!(FlyingLattice, AutoMaze<15, Flying<15>>, 27);
+lattice_impl!(TelepathicLattice, AutoMaze<11, Telepathic<11>>, 28);
+lattice_impl!(AutomatonLattice, AutoMaze<20, Automaton<20>>, 29);
+lattice_impl!(TowerLattice, Tower<11, 3>, 30);
+lattice_impl!(PledgeLattice, AutoMaze<11, Pledge<11>>, 31);
+lattice_impl!(DeadEndFillingLattice, AutoMaze<11, DeadEndFilling<11>>, 32);
+lattice_impl!(AStarLattice, AutoMaze<11, AStar<11>>, 33);
+lattice_impl!(BidirectionalLattice, AutoMaze<13, Bidirectional<13>>, 34);
+lattice_impl!(AntColonyLattice, AutoMaze<11, AntColony<11>>, 35);
+// 36 was the genetic algorithm, which is now a mode of LearningLattice. the ids are part of share
+// codes, so they're never reused
+lattice_impl!(QLearningLattice, AutoMaze<11, QLearning<11>>, 37);
+lattice_impl!(BoidsLattice, AutoMaze<11, Boids<11>>, 38);
+lattice_impl!(ScriptedLattice, AutoMaze<11, Scripted<11>>, 39);
+lattice_impl!(RaceLattice, Race<11>, 40);
+
+#[wasm_bindgen]
+impl ScriptedLattice {
+    // gives every snail the program, or says what's wrong with it if it doesn't compile
+    #[wasm_bindgen]
+    pub fn compile(&mut self, source: &str) -> Result<(), String> {
+        let program = Program::compile(source).map_err(|err| err.to_string())?;
+        self.0.set_program(program);
+
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn program_error(&self) -> Option<String> {
+        self.0.program_error().map(|err| err.to_string())
+    }
+}
+
+#[wasm_bindgen]
+impl QLearningLattice {
+    // how many moves the snail in a maze took to solve the last one, or 0 if it hasn't solved
+    // one yet. this goes down as the snail learns
+    #[wasm_bindgen]
+    pub fn last_steps(&self, index: usize) -> usize {
+        self.0
+            .mazes
+            .get(index)
+            .map(|maze| maze.solver().last_steps())
+            .unwrap_or(0)
+    }
+}
+
+// racers are in the order hold left, tremaux, random walk, clones, see Race::new
+#[wasm_bindgen]
+impl RaceLattice {
+    // the fraction of every race so far each racer has won
+    #[wasm_bindgen]
+    pub fn win_rates(&self) -> Vec<f32> {
+        let races: usize = self.0.mazes.iter().map(Race::races).sum();
+        let mut wins = [0; 4];
+
+        for maze in &self.0.mazes {
+            for (total, wins) in wins.iter_mut().zip(maze.wins()) {
+                *total += wins;
+            }
+        }
+
+        wins.iter()
+            .map(|wins| *wins as f32 / races.max(1) as f32)
+            .collect()
+    }
+
+    // how long each racer took in the last race in a maze, or -1 if it didn't finish. empty if
+    // no race there has finished yet
+    #[wasm_bindgen]
+    pub fn last_times(&self, index: usize) -> Vec<f32> {
+        match self.0.mazes.get(index).and_then(Race::last_result) {
+            Some(result) => result
+                .times
+                .iter()
+                .map(|time| time.unwrap_or(-1.0))
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MetaMaze, SnailLattice};
+    use crate::{
+        maze::{AutoMaze, PORTAL_MODIFIER, SNAIL_MOVEMENT_TIME, TERRAIN_MODIFIER},
+        share::{CodeError, ShareCode},
+        solvers::Tremaux,
+    };
+
+    #[test]
+    fn share_codes_rebuild_the_maze() {
+        let mut lattice = SnailLattice::<AutoMaze<11, Tremaux<11>>>::new(2, 0xFEAD);
+        lattice.set_modifiers(TERRAIN_MODIFIER | PORTAL_MODIFIER);
+        lattice.alter(3);
+        lattice.tick(1000.0 * SNAIL_MOVEMENT_TIME);
+
+        // every maze has been solved, so none of them are the ones the lattice started with
+        let solves = lattice.get_solve_count();
+        assert!(solves.chunks(2).all(|solves| solves[1] > 0));
+
+        let code = lattice.share_code(21, 2).unwrap();
+        let code = ShareCode::parse(&code.to_string()).unwrap();
+
+        let mut shared =
+            SnailLattice::<AutoMaze<11, Tremaux<11>>>::from_code(1, &code, 21).unwrap();
+        shared.set_modifiers(TERRAIN_MODIFIER | PORTAL_MODIFIER);
+        shared.alter(1);
+
+        let (a, b) = (&lattice.mazes[2].maze, &shared.mazes[0].maze);
+        assert_eq!((a.start_pos, a.end_pos), (b.start_pos, b.end_pos));
+
+        for y in 0..11 {
+            for x in 0..11 {
+                assert_eq!(a.get_cell(x, y).0, b.get_cell(x, y).0);
+                assert_eq!(a.get_terrain(x, y), b.get_terrain(x, y));
+            }
+        }
+
+        assert_eq!(
+            lattice.share_code(21, 3).err(),
+            Some(CodeError::NoMaze { index: 3 })
+        );
+        assert_eq!(
+            SnailLattice::<MetaMaze>::new(1, 0).share_code(25, 0).err(),
+            Some(CodeError::NotShareable)
+        );
+    }
+}
@@ -0,0 +1,475 @@
+use crate::{
+    direction::Direction,
+    image::Image,
+    lattice::{TilableMaze, MAZE_RNG},
+    maze::{Maze, MazeCell, ANIMATION_TIME, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    pathfinding::{a_star, bfs, DistanceField, Graph},
+    rng::Rng,
+    snail::{Snail, DEFAULT_PALETTE},
+    solvers::SolveStatus,
+    utils::Vec2,
+};
+
+// on top of the 4 planar directions, a snail standing on a staircase can climb up or down a floor
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LayeredMove {
+    Planar(Direction),
+    Ascend,
+    Descend,
+}
+
+impl LayeredMove {
+    pub fn flip(self) -> LayeredMove {
+        match self {
+            LayeredMove::Planar(dir) => LayeredMove::Planar(dir.flip()),
+            LayeredMove::Ascend => LayeredMove::Descend,
+            LayeredMove::Descend => LayeredMove::Ascend,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct LayeredPos {
+    pub floor: usize,
+    pub pos: Vec2,
+}
+
+impl LayeredPos {
+    pub fn new(floor: usize, x: usize, y: usize) -> LayeredPos {
+        LayeredPos {
+            floor,
+            pos: Vec2 { x, y },
+        }
+    }
+}
+
+// A stack of F SxS mazes. Each floor is joined to the one above it by a single staircase, so the
+// whole tower is still a perfect maze.
+pub struct LayeredMaze<const S: usize, const F: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    pub floors: Vec<Maze<S>>,
+
+    // stairs[f] connects floor f to floor f + 1
+    pub stairs: Vec<Vec2>,
+    pub end_pos: LayeredPos,
+}
+
+impl<const S: usize, const F: usize> LayeredMaze<S, F>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    pub fn new() -> Self {
+        LayeredMaze {
+            floors: (0..F).map(|_| Maze::new()).collect(),
+            stairs: vec![Vec2 { x: 0, y: 0 }; F - 1],
+            end_pos: LayeredPos::new(F - 1, S - 1, S - 1),
+        }
+    }
+
+    fn index(at: LayeredPos) -> usize {
+        at.floor * S * S + at.pos.y * S + at.pos.x
+    }
+
+    pub fn generate(&mut self, rng: &mut dyn Rng) {
+        let mut distances = [0; S * S];
+        let mut arrival = Vec2 { x: 0, y: 0 };
+
+        for floor in 0..F {
+            self.floors[floor].generate(rng);
+
+            // every staircase (and the final goal) is placed on the cell farthest away from where
+            // the snail arrives on that floor
+            self.floors[floor].get_distances(arrival.x, arrival.y, &mut distances);
+
+            let farthest = (0..(S * S)).max_by_key(|&i| distances[i]).unwrap_or(0);
+            let farthest = Vec2 {
+                x: farthest % S,
+                y: farthest / S,
+            };
+
+            self.floors[floor].end_pos = farthest;
+
+            if floor < F - 1 {
+                self.stairs[floor] = farthest;
+            } else {
+                self.end_pos = LayeredPos {
+                    floor,
+                    pos: farthest,
+                };
+            }
+
+            arrival = farthest;
+        }
+    }
+
+    pub fn get_cell(&self, at: LayeredPos) -> MazeCell {
+        self.floors[at.floor].get_cell(at.pos.x, at.pos.y)
+    }
+
+    // every move that can be made from a given position, along with where it leads
+    pub fn neighbors(&self, at: LayeredPos) -> Vec<(LayeredMove, LayeredPos)> {
+        let cell = self.get_cell(at);
+        let mut neighbors = Vec::with_capacity(6);
+
+        for dir in cell.valid_directions() {
+            let mut next = at;
+
+            match dir {
+                Direction::Up => next.pos.y -= 1,
+                Direction::Down => next.pos.y += 1,
+                Direction::Left => next.pos.x -= 1,
+                Direction::Right => next.pos.x += 1,
+            }
+
+            neighbors.push((LayeredMove::Planar(dir), next));
+        }
+
+        if at.floor < F - 1 && self.stairs[at.floor] == at.pos {
+            neighbors.push((LayeredMove::Ascend, LayeredPos { floor: at.floor + 1, ..at }));
+        }
+
+        if at.floor > 0 && self.stairs[at.floor - 1] == at.pos {
+            neighbors.push((LayeredMove::Descend, LayeredPos { floor: at.floor - 1, ..at }));
+        }
+
+        neighbors
+    }
+
+    pub fn valid_moves(&self, at: LayeredPos) -> Vec<LayeredMove> {
+        self.neighbors(at).into_iter().map(|(mv, _)| mv).collect()
+    }
+
+    // indexed by floor * S * S + y * S + x
+    pub fn get_distances(&self, source: LayeredPos) -> Vec<usize> {
+        bfs(self, &[source])
+    }
+
+    // for each position, the move that brings it one step closer to source
+    pub fn get_directions(&self, source: LayeredPos) -> Vec<Option<LayeredMove>> {
+        let field = DistanceField::new(self, source);
+
+        (0..(F * S * S))
+            .map(|i| field.next_move(self, self.node(i)))
+            .collect()
+    }
+
+    pub fn get_solve_sequence(&self, start: LayeredPos, target: LayeredPos) -> Vec<LayeredMove> {
+        a_star(self, start, target).unwrap_or_default()
+    }
+}
+
+impl<const S: usize, const F: usize> Default for LayeredMaze<S, F>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// every move takes the same amount of time, whether it's along a floor or up the stairs
+impl<const S: usize, const F: usize> Graph for LayeredMaze<S, F>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    type Node = LayeredPos;
+    type Move = LayeredMove;
+
+    fn node_count(&self) -> usize {
+        F * S * S
+    }
+
+    fn index(&self, at: LayeredPos) -> usize {
+        Self::index(at)
+    }
+
+    fn node(&self, index: usize) -> LayeredPos {
+        LayeredPos::new(index / (S * S), index % S, (index % (S * S)) / S)
+    }
+
+    fn edges(&self, at: LayeredPos) -> Vec<(LayeredMove, LayeredPos, usize)> {
+        self.neighbors(at)
+            .into_iter()
+            .map(|(mv, next)| (mv, next, 1))
+            .collect()
+    }
+
+    fn reverse_edges(&self, at: LayeredPos) -> Vec<(LayeredMove, LayeredPos, usize)> {
+        self.neighbors(at)
+            .into_iter()
+            .map(|(mv, previous)| (mv.flip(), previous, 1))
+            .collect()
+    }
+}
+
+// draws a little 3 step staircase in the cell at (x, y), facing up or down
+fn draw_stairs(image: &mut Image, color: [u8; 3], up: bool, x: usize, y: usize) {
+    for step in 0..3 {
+        let row = if up { 2 + 2 * step } else { 6 - 2 * step };
+
+        for col in (2 + 2 * step)..8 {
+            image.draw_pixel_xy(color, x + col, y + row);
+        }
+    }
+}
+
+/// Tower Snail Upgrades:
+/// - Handrail:          Tower Snail no longer slows down when climbing stairs.
+/// - Spiral Staircases: Tower Snail moves 25% faster.
+
+pub struct Tower<const S: usize, const F: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    maze: LayeredMaze<S, F>,
+    snail: Snail<S>,
+    floor: usize,
+    solve_sequence: Vec<LayeredMove>,
+    upgrades: u32,
+
+    // stores time since start, in milliseconds
+    clock: f32,
+
+    // time since last movement
+    movement_timer: f32,
+
+    // what the current tower was generated from, see TilableMaze::generate_from
+    seed: u32,
+}
+
+impl<const S: usize, const F: usize> Tower<S, F>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn movement_time(&self) -> f32 {
+        let mut movement_time = SNAIL_MOVEMENT_TIME;
+
+        // Spiral Staircases
+        if (self.upgrades & 0b10) != 0 {
+            movement_time *= 0.75;
+        }
+
+        // climbing takes twice as long without a handrail
+        if (self.upgrades & 0b1) == 0
+            && matches!(
+                self.solve_sequence.last(),
+                Some(LayeredMove::Ascend) | Some(LayeredMove::Descend)
+            )
+        {
+            movement_time *= 2.0;
+        }
+
+        movement_time
+    }
+
+    fn step(&mut self) -> SolveStatus {
+        let mut status = SolveStatus::None;
+
+        match self.solve_sequence.pop() {
+            Some(LayeredMove::Planar(dir)) => {
+                self.snail.direction = dir;
+                self.snail.move_forward(&self.maze.floors[self.floor]);
+            }
+            Some(LayeredMove::Ascend) => {
+                self.floor += 1;
+                self.snail.prev_pos = self.snail.pos;
+                status = SolveStatus::Rerender;
+            }
+            Some(LayeredMove::Descend) => {
+                self.floor -= 1;
+                self.snail.prev_pos = self.snail.pos;
+                status = SolveStatus::Rerender;
+            }
+            None => {}
+        }
+
+        if self.floor == self.maze.end_pos.floor && self.snail.pos == self.maze.end_pos.pos {
+            SolveStatus::Solved(F)
+        } else {
+            status
+        }
+    }
+}
+
+impl<const S: usize, const F: usize> TilableMaze for Tower<S, F>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    const SIZE: usize = S;
+    const SHAREABLE: bool = true;
+
+    fn new() -> Self {
+        Tower {
+            maze: LayeredMaze::new(),
+            snail: Snail::new(),
+            floor: 0,
+            solve_sequence: vec![],
+            upgrades: 0,
+            clock: 0.0,
+            movement_timer: 0.0,
+            seed: 0,
+        }
+    }
+
+    fn tick(&mut self, mut dt: f32, rng: &mut dyn Rng) -> SolveStatus {
+        self.clock += dt;
+        let mut total = 0;
+        let mut rerender = false;
+
+        dt += self.movement_timer;
+
+        while dt > self.movement_time() {
+            let movement_time = self.movement_time();
+            dt -= movement_time;
+
+            match self.step() {
+                SolveStatus::Solved(count) => {
+                    total += count;
+                    self.generate(rng);
+                }
+                SolveStatus::Rerender | SolveStatus::KeyCollected => rerender = true,
+                SolveStatus::None => {}
+            }
+        }
+
+        self.movement_timer = dt;
+
+        match (total, rerender) {
+            (0, true) => SolveStatus::Rerender,
+            (0, false) => SolveStatus::None,
+            (num, _) => SolveStatus::Solved(num),
+        }
+    }
+
+    fn set_upgrades(&mut self, upgrades: u32) {
+        self.upgrades = upgrades;
+    }
+
+    fn set_modifiers(&mut self, _modifiers: u32) {}
+
+    fn draw_foreground(&mut self, _rng: &mut dyn Rng, image: &mut Image, bx: usize, by: usize) {
+        let animation_cycle = (self.clock / ANIMATION_TIME).round() as usize % 2 == 0;
+
+        if self.floor < F - 1 {
+            let stairs = self.maze.stairs[self.floor];
+            draw_stairs(
+                image,
+                DEFAULT_PALETTE[0],
+                true,
+                bx + stairs.x * 10,
+                by + stairs.y * 10,
+            );
+        }
+
+        if self.floor > 0 {
+            let stairs = self.maze.stairs[self.floor - 1];
+            draw_stairs(
+                image,
+                DEFAULT_PALETTE[2],
+                false,
+                bx + stairs.x * 10,
+                by + stairs.y * 10,
+            );
+        }
+
+        if self.floor == self.maze.end_pos.floor && animation_cycle {
+            let end_pos = self.maze.end_pos.pos;
+            image.draw_goal(DEFAULT_PALETTE[0], bx + end_pos.x * 10, by + end_pos.y * 10);
+        }
+
+        self.snail.draw(
+            DEFAULT_PALETTE,
+            animation_cycle,
+            self.movement_timer / self.movement_time(),
+            image,
+            bx,
+            by,
+        );
+
+        let mut floor_text = "floor:".to_string();
+        floor_text.push_str(&(self.floor + 1).to_string());
+
+        image.draw_text(&floor_text, bx + 2, by + 1 + S * 10 - 6);
+    }
+
+    // only the floor the snail is currently on is shown
+    fn draw_background(&mut self, image: &mut Image, bx: usize, by: usize) {
+        self.maze.floors[self.floor].draw_background(
+            DEFAULT_PALETTE[4],
+            DEFAULT_PALETTE[5],
+            image,
+            bx,
+            by,
+        );
+    }
+
+    fn generate(&mut self, rng: &mut dyn Rng) {
+        self.generate_from(rng.big() as u32, rng);
+    }
+
+    fn generate_from(&mut self, seed: u32, _rng: &mut dyn Rng) {
+        self.seed = seed;
+        self.maze.generate(&mut *MAZE_RNG.build(seed));
+        self.snail.reset();
+        self.floor = 0;
+
+        self.solve_sequence = self
+            .maze
+            .get_solve_sequence(LayeredPos::new(0, 0, 0), self.maze.end_pos);
+        self.solve_sequence.reverse();
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LayeredMaze, LayeredMove, LayeredPos};
+    use crate::rng::Xoshiro256;
+
+    // follows moves from start, checking that each one can actually be made, and returns where
+    // they lead
+    fn walk(maze: &LayeredMaze<7, 3>, start: LayeredPos, moves: &[LayeredMove]) -> LayeredPos {
+        moves.iter().fold(start, |at, mv| {
+            maze.neighbors(at)
+                .into_iter()
+                .find(|(next_move, _)| next_move == mv)
+                .map(|(_, next)| next)
+                .unwrap()
+        })
+    }
+
+    #[test]
+    fn solve_sequence_climbs_every_floor() {
+        let mut maze = LayeredMaze::<7, 3>::new();
+        maze.generate(&mut Xoshiro256::new(5));
+
+        let start = LayeredPos::new(0, 0, 0);
+        let up = maze.get_solve_sequence(start, maze.end_pos);
+        let down = maze.get_solve_sequence(maze.end_pos, start);
+
+        assert_eq!(walk(&maze, start, &up), maze.end_pos);
+        assert_eq!(walk(&maze, maze.end_pos, &down), start);
+        assert_eq!(
+            up.len(),
+            maze.get_distances(start)[LayeredMaze::<7, 3>::index(maze.end_pos)]
+        );
+
+        // the tower is a perfect maze, so the shortest way never goes back down a floor
+        assert_eq!(
+            up.iter().filter(|mv| **mv == LayeredMove::Ascend).count(),
+            2
+        );
+        assert!(!up.contains(&LayeredMove::Descend));
+        assert_eq!(
+            down.iter()
+                .filter(|mv| **mv == LayeredMove::Descend)
+                .count(),
+            2
+        );
+    }
+}
@@ -6,25 +6,87 @@
 
 extern crate test;
 
+pub mod ascii;
 mod direction;
+pub mod ghosts;
 mod image;
 pub mod lattice;
+pub mod layered;
 mod lfsr;
 mod manual;
+pub mod mask;
 pub mod maze;
+pub mod metrics;
+pub mod pathfinding;
+pub mod race;
+mod rng;
+pub mod share;
 mod snail;
 pub mod solvers;
 mod utils;
+pub mod vm;
 
 #[cfg(test)]
 mod tests {
     use crate::{
         lattice::{MetaLattice, RpgLattice, SnailLattice},
-        maze::{AutoMaze, SNAIL_MOVEMENT_TIME},
-        solvers::{Clones, Rpg},
+        maze::{AutoMaze, PREDATOR_MODIFIER, SNAIL_MOVEMENT_TIME},
+        solvers::{Clones, QLearning, Rpg, Scripted, Tremaux},
+        vm::{Program, VmError},
     };
     use test::Bencher;
 
+    #[test]
+    fn snapshot_round_trip() {
+        let mut lattice = SnailLattice::<AutoMaze<11, QLearning<11>>>::new(2, 0xFEAD);
+        lattice.alter(4);
+        lattice.tick(1000.0 * SNAIL_MOVEMENT_TIME);
+
+        let snapshot = lattice.snapshot();
+
+        let mut restored = SnailLattice::<AutoMaze<11, QLearning<11>>>::new(2, 0xBEEF);
+        restored.alter(4);
+        restored.restore(&snapshot).unwrap();
+        assert_eq!(restored.snapshot(), snapshot);
+
+        assert!(restored.restore(&snapshot[..snapshot.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn scripted_programs() {
+        let mut lattice = SnailLattice::<AutoMaze<11, Scripted<11>>>::new(2, 0xFEAD);
+        lattice.alter(2);
+
+        // the default program follows the left wall, so it gets there eventually
+        assert!(lattice.tick(1000.0 * SNAIL_MOVEMENT_TIME) > 0);
+        assert_eq!(lattice.program_error(), None);
+
+        lattice.set_program(Program::compile("spin:\nturn left\njump spin\nmove").unwrap());
+        lattice.alter(1);
+        lattice.tick(SNAIL_MOVEMENT_TIME);
+
+        assert_eq!(
+            lattice.program_error(),
+            Some(VmError::TooManyInstructions { line: 2 })
+        );
+        assert_eq!(lattice.tick(1000.0 * SNAIL_MOVEMENT_TIME), 0);
+    }
+
+    #[test]
+    fn predators_send_snails_back() {
+        let mut lattice = SnailLattice::<AutoMaze<11, Tremaux<11>>>::new(1, 0xFEAD);
+        lattice.alter(1);
+        let unhunted = lattice.tick(1000.0 * SNAIL_MOVEMENT_TIME);
+
+        let mut lattice = SnailLattice::<AutoMaze<11, Tremaux<11>>>::new(1, 0xFEAD);
+        lattice.set_modifiers(PREDATOR_MODIFIER);
+        lattice.alter(1);
+        let hunted = lattice.tick(1000.0 * SNAIL_MOVEMENT_TIME);
+
+        assert!(hunted > 0);
+        assert!(hunted < unhunted);
+    }
+
     #[bench]
     fn cloning_snail_tick(b: &mut Bencher) {
         let mut lattice = SnailLattice::<AutoMaze<100, Clones<100>>>::new(10, 0xFEAD);
@@ -39,7 +101,7 @@ mod tests {
     fn cloning_snail_render(b: &mut Bencher) {
         let mut lattice = SnailLattice::<AutoMaze<100, Clones<100>>>::new(10, 0xFEAD);
         lattice.alter(100);
-        lattice.tick(100000);
+        lattice.tick(100000.0);
 
         let dimensions = lattice.get_dimensions(100);
 
@@ -64,7 +126,7 @@ mod tests {
     fn rpg_snail_render(b: &mut Bencher) {
         let mut lattice = SnailLattice::<AutoMaze<100, Rpg<100>>>::new(10, 0xFEAD);
         lattice.alter(100);
-        lattice.tick(100000);
+        lattice.tick(100000.0);
 
         let dimensions = lattice.get_dimensions(100);
 
@@ -89,7 +151,7 @@ mod tests {
     fn meta_snail_render(b: &mut Bencher) {
         let mut lattice = MetaLattice::new(10, 0xFEAD);
         lattice.alter(100);
-        lattice.tick(100000);
+        lattice.tick(100000.0);
 
         let dimensions = lattice.get_dimensions(100);
 
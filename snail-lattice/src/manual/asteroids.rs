@@ -1,6 +1,6 @@
 use std::f32::consts::PI;
 
-use crate::{image::Image, lfsr::LFSR, snail::DEFAULT_PALETTE, utils::Vec2f};
+use crate::{image::Image, rng::Rng, snail::DEFAULT_PALETTE, utils::Vec2f};
 
 struct Asteroid {
     pos: Vec2f,
@@ -165,6 +165,9 @@ pub struct AsteroidsGame {
     bullets: Vec<Bullet>,
 
     shoot_cooldown: f32,
+
+    // goes up every time the game starts over
+    games_played: usize,
 }
 
 const SHOOT_COOLDOWN: f32 = 300.0;
@@ -177,24 +180,25 @@ impl AsteroidsGame {
             bullets: vec![],
 
             shoot_cooldown: 0.0,
+            games_played: 0,
         }
     }
 
-    fn generate_asteroids(&mut self, lfsr: &mut LFSR, count: usize) {
+    fn generate_asteroids(&mut self, rng: &mut dyn Rng, count: usize) {
         for _ in 0..count {
             // random f32
-            let size = (lfsr.big() % 24).max(6) as f32;
+            let size = (rng.big() % 24).max(6) as f32;
 
             // velocity
-            let velx = ((lfsr.big() % 20).max(1) as f32 - 10.0) / 100.0;
-            let vely = ((lfsr.big() % 20).max(1) as f32 - 10.0) / 100.0;
+            let velx = ((rng.big() % 20).max(1) as f32 - 10.0) / 100.0;
+            let vely = ((rng.big() % 20).max(1) as f32 - 10.0) / 100.0;
 
             let mut x = 120i32;
             let mut y = 120i32;
 
             while x.abs_diff(120) < 20 && y.abs_diff(120) < 20 {
-                x = (lfsr.big() % 240) as i32;
-                y = (lfsr.big() % 240) as i32;
+                x = (rng.big() % 240) as i32;
+                y = (rng.big() % 240) as i32;
             }
 
             self.asteroids.push(Asteroid::new(
@@ -214,15 +218,20 @@ impl AsteroidsGame {
 
         self.asteroids.clear();
         self.bullets.clear();
+        self.games_played += 1;
     }
 
     pub fn resolution(&self) -> Vec<u32> {
         vec![240, 240]
     }
 
-    pub fn tick(&mut self, lfsr: &mut LFSR, keys: Vec<u32>, dt: f32) -> i32 {
+    pub fn games_played(&self) -> usize {
+        self.games_played
+    }
+
+    pub fn tick(&mut self, rng: &mut dyn Rng, keys: Vec<u32>, dt: f32) -> i32 {
         if self.asteroids.is_empty() {
-            self.generate_asteroids(lfsr, 6);
+            self.generate_asteroids(rng, 6);
         }
 
         let mut keys_bits = 0;
@@ -0,0 +1,127 @@
+// Every day, everyone playing the daily challenge gets the same levels. The frontend passes in the
+// player's local date (like "2026-10-18"), which is hashed into the seed for the game's random
+// number generator, and keeps the best results in local storage between sessions.
+//
+// The maze and wolfenstein are played for time: an attempt ends when the goal is reached. The
+// other games are played for score, and an attempt ends when the game starts over. Either way
+// the game is then restarted from the seed so every attempt plays out on the same levels.
+
+pub struct DailyChallenge {
+    date: String,
+    seed: u32,
+
+    // time spent and fragments earned since the current attempt started
+    time: f32,
+    score: i64,
+
+    best_time: Option<f32>,
+    best_score: Option<i64>,
+}
+
+impl DailyChallenge {
+    pub fn new(date: &str) -> DailyChallenge {
+        DailyChallenge {
+            date: date.to_string(),
+            seed: date_seed(date),
+            time: 0.0,
+            score: 0,
+            best_time: None,
+            best_score: None,
+        }
+    }
+
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    pub fn best_time(&self) -> Option<f32> {
+        self.best_time
+    }
+
+    pub fn set_best_time(&mut self, best_time: Option<f32>) {
+        self.best_time = best_time;
+    }
+
+    pub fn best_score(&self) -> Option<i64> {
+        self.best_score
+    }
+
+    pub fn set_best_score(&mut self, best_score: Option<i64>) {
+        self.best_score = best_score;
+    }
+
+    // score is whatever the game returned for the frame, where negative numbers are bonuses
+    pub fn tick(&mut self, dt: f32, score: i64) {
+        self.time += dt;
+        self.score += score.abs();
+    }
+
+    pub fn restart(&mut self) {
+        self.time = 0.0;
+        self.score = 0;
+    }
+
+    // the goal was reached
+    pub fn finish_timed(&mut self) {
+        self.best_time = Some(match self.best_time {
+            Some(best) => best.min(self.time),
+            None => self.time,
+        });
+
+        self.restart();
+    }
+
+    // the game started over
+    pub fn finish_scored(&mut self) {
+        self.best_score = Some(match self.best_score {
+            Some(best) => best.max(self.score),
+            None => self.score,
+        });
+
+        self.restart();
+    }
+}
+
+// 32 bit fnv-1a, https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function
+pub fn date_seed(date: &str) -> u32 {
+    date.trim().bytes().fold(0x811C9DC5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{date_seed, DailyChallenge};
+
+    #[test]
+    fn seeds() {
+        assert_eq!(date_seed(""), 0x811C9DC5);
+        assert_eq!(date_seed("a"), 0xE40C292C);
+        assert_eq!(date_seed("2026-10-18"), date_seed(" 2026-10-18\n"));
+        assert_ne!(date_seed("2026-10-18"), date_seed("2026-10-19"));
+    }
+
+    #[test]
+    fn keeps_best_results() {
+        let mut daily = DailyChallenge::new("2026-10-18");
+
+        daily.tick(5000.0, 25);
+        daily.finish_timed();
+        daily.tick(3000.0, -25);
+        daily.finish_timed();
+        daily.tick(4000.0, 25);
+        daily.finish_timed();
+        assert_eq!(daily.best_time(), Some(3000.0));
+
+        daily.tick(16.0, 100);
+        daily.tick(16.0, -200);
+        daily.finish_scored();
+        daily.tick(16.0, 50);
+        daily.finish_scored();
+        assert_eq!(daily.best_score(), Some(300));
+    }
+}
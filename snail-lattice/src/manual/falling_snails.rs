@@ -1,4 +1,4 @@
-use crate::{direction::Direction, image::Image, lfsr::LFSR, snail::DEFAULT_PALETTE, utils::Vec2i};
+use crate::{direction::Direction, image::Image, rng::Rng, snail::DEFAULT_PALETTE, utils::Vec2i};
 
 // [2, 4, 1, 3]
 // based on https://codeincomplete.com/articles/javascript-tetris/
@@ -132,21 +132,25 @@ pub struct FallingSnailsGame {
     right_held: f32,
     left_held: f32,
     held_piece_dir: Direction,
+
+    // goes up every time the board fills up and the game starts over
+    games_played: usize,
 }
 
 impl FallingSnailsGame {
-    pub fn new(lfsr: &mut LFSR) -> FallingSnailsGame {
+    pub fn new(rng: &mut dyn Rng) -> FallingSnailsGame {
         FallingSnailsGame {
             grid: [0; _],
             held_piece_pos: Vec2i::new(4, 0),
             held_piece_dir: Direction::Right,
-            selected_piece: lfsr.big() % BLOCKS.len(),
+            selected_piece: rng.big() % BLOCKS.len(),
             can_rotate: true,
             right_held: -1000.0,
             left_held: -1000.0,
             last_shift_update: 0.0,
             fall_timer: 0.0,
             time: 0.0,
+            games_played: 0,
         }
     }
 
@@ -154,7 +158,11 @@ impl FallingSnailsGame {
         vec![200, 200]
     }
 
-    pub fn tick(&mut self, lfsr: &mut LFSR, keys: Vec<u32>, dt: f32) -> i64 {
+    pub fn games_played(&self) -> usize {
+        self.games_played
+    }
+
+    pub fn tick(&mut self, rng: &mut dyn Rng, keys: Vec<u32>, dt: f32) -> i64 {
         self.time += dt;
 
         let mut keys_bits = 0;
@@ -180,7 +188,7 @@ impl FallingSnailsGame {
             ) {
                 self.held_piece_pos.y += 1;
             } else {
-                self.reset_piece(lfsr);
+                self.reset_piece(rng);
             }
         }
 
@@ -373,7 +381,7 @@ impl FallingSnailsGame {
         }
     }
 
-    fn reset_piece(&mut self, lfsr: &mut LFSR) {
+    fn reset_piece(&mut self, rng: &mut dyn Rng) {
         for_each_block(
             self.selected_piece,
             self.held_piece_pos.x,
@@ -388,7 +396,7 @@ impl FallingSnailsGame {
 
         self.held_piece_pos = Vec2i::new(4, 0);
         self.held_piece_dir = Direction::Right;
-        self.selected_piece = lfsr.big() % BLOCKS.len();
+        self.selected_piece = rng.big() % BLOCKS.len();
 
         // if piece collides, we reset the whole board
 
@@ -398,6 +406,7 @@ impl FallingSnailsGame {
             self.held_piece_dir,
         ) {
             self.grid.fill(0);
+            self.games_played += 1;
         }
     }
 
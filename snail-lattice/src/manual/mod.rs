@@ -3,18 +3,23 @@ use wasm_bindgen::prelude::wasm_bindgen;
 use crate::{
     direction::Direction,
     image::Image,
-    lfsr::LFSR,
     maze::{Maze, ANIMATION_TIME, SNAIL_MOVEMENT_TIME},
+    rng::{Rng, RngKind},
+    share::{CodeError, ShareCode, MANUAL_MAZE_ALGORITHM, WOLFENSTEIN_ALGORITHM},
     snail::{Snail, DEFAULT_PALETTE, GRAYSCALE_PALETTE},
     utils::{set_panic_hook, Vec2},
 };
 
 use self::{
-    asteroids::AsteroidsGame, falling_snails::FallingSnailsGame, pacsnail::PacSnail,
-    wolfenstein::WolfensteinGame,
+    asteroids::AsteroidsGame,
+    daily::DailyChallenge,
+    falling_snails::FallingSnailsGame,
+    pacsnail::PacSnail,
+    wolfenstein::{WolfensteinGame, GOAL_SCORE},
 };
 
 mod asteroids;
+mod daily;
 mod falling_snails;
 mod pacsnail;
 mod wolfenstein;
@@ -22,8 +27,10 @@ mod wolfenstein;
 // am i really going to implemennt 3 full parody games inside my snail maze incremental game?
 // yes, yes i am
 enum ManualGame {
-    SnailMaze(ManualMaze),
-    PacSnail(PacSnail),
+    // the games with a whole maze or board in them are boxed, since they're much bigger than the
+    // others
+    SnailMaze(Box<ManualMaze>),
+    PacSnail(Box<PacSnail>),
     Asteroids(AsteroidsGame),
     Wolfenstein(WolfensteinGame),
     FallingSnails(FallingSnailsGame),
@@ -32,20 +39,29 @@ enum ManualGame {
 #[wasm_bindgen]
 pub struct Game {
     game: ManualGame,
-    lfsr: LFSR,
+    game_type: u32,
+    seed: u32,
+    // the original lfsr unless set_rng says otherwise, so existing seeds keep their mazes
+    rng_kind: RngKind,
+    rng: Box<dyn Rng>,
+    daily: Option<DailyChallenge>,
 }
 
 #[wasm_bindgen]
 impl Game {
     #[wasm_bindgen(constructor)]
-    pub fn new(seed: u16) -> Self {
+    pub fn new(seed: u32) -> Self {
         set_panic_hook();
 
-        let mut lfsr = LFSR::new(seed);
+        let mut rng = RngKind::Lfsr.build(seed);
 
         Self {
-            game: ManualGame::SnailMaze(ManualMaze::new(&mut lfsr)),
-            lfsr,
+            game: ManualGame::SnailMaze(Box::new(ManualMaze::new(&mut *rng))),
+            game_type: 0,
+            seed,
+            rng_kind: RngKind::Lfsr,
+            rng,
+            daily: None,
         }
     }
 
@@ -63,52 +79,207 @@ impl Game {
 
     #[wasm_bindgen]
     pub fn set_game(&mut self, game_type: u32) {
+        self.game_type = game_type;
+
+        // every attempt at the daily challenge starts from the same seed
+        if let Some(daily) = &mut self.daily {
+            self.rng = self.rng_kind.build(daily.seed());
+            daily.restart();
+        }
+
         match game_type {
-            0 => self.game = ManualGame::SnailMaze(ManualMaze::new(&mut self.lfsr)),
-            1 => self.game = ManualGame::PacSnail(PacSnail::new()),
+            0 => self.game = ManualGame::SnailMaze(Box::new(ManualMaze::new(&mut *self.rng))),
+            1 => self.game = ManualGame::PacSnail(Box::new(PacSnail::new())),
             2 => self.game = ManualGame::Asteroids(AsteroidsGame::new()),
-            3 => self.game = ManualGame::Wolfenstein(WolfensteinGame::new(&mut self.lfsr)),
-            4 => self.game = ManualGame::FallingSnails(FallingSnailsGame::new(&mut self.lfsr)),
+            3 => self.game = ManualGame::Wolfenstein(WolfensteinGame::new(&mut *self.rng)),
+            4 => self.game = ManualGame::FallingSnails(FallingSnailsGame::new(&mut *self.rng)),
             _ => unreachable!(),
         }
     }
 
+    // 0 for the original lfsr, 1 for xoshiro. starts over from the game's original seed, and
+    // takes effect from the next game
+    #[wasm_bindgen]
+    pub fn set_rng(&mut self, kind: u32) {
+        if let Some(kind) = RngKind::from_number(kind) {
+            self.rng_kind = kind;
+            self.rng = kind.build(self.seed);
+        }
+    }
+
+    // the game being played, as passed to set_game. loading a code can switch to another one
+    #[wasm_bindgen]
+    pub fn game_type(&self) -> u32 {
+        self.game_type
+    }
+
+    // the code for the level currently being played, if the game has levels which can be shared
+    #[wasm_bindgen]
+    pub fn share_code(&self) -> Option<String> {
+        match &self.game {
+            ManualGame::SnailMaze(game) => Some(game.code.to_string()),
+            ManualGame::Wolfenstein(game) => Some(game.code().to_string()),
+            _ => None,
+        }
+    }
+
+    // switches to the game the code was made in, starting on the level it was made for
+    #[wasm_bindgen]
+    pub fn load_code(&mut self, code: &str) -> Result<(), String> {
+        self.load(code).map_err(|err| err.to_string())
+    }
+
+    // date is the player's local date, like "2026-10-18"
+    #[wasm_bindgen]
+    pub fn start_daily(&mut self, date: &str, game_type: u32) {
+        self.daily = Some(DailyChallenge::new(date));
+        self.set_game(game_type);
+    }
+
+    #[wasm_bindgen]
+    pub fn stop_daily(&mut self) {
+        self.daily = None;
+    }
+
+    #[wasm_bindgen]
+    pub fn daily_date(&self) -> Option<String> {
+        self.daily.as_ref().map(|daily| daily.date().to_string())
+    }
+
+    // the fastest the goal has been reached today, in milliseconds
+    #[wasm_bindgen]
+    pub fn daily_best_time(&self) -> Option<f32> {
+        self.daily.as_ref().and_then(|daily| daily.best_time())
+    }
+
+    #[wasm_bindgen]
+    pub fn set_daily_best_time(&mut self, best_time: Option<f32>) {
+        if let Some(daily) = &mut self.daily {
+            daily.set_best_time(best_time);
+        }
+    }
+
+    // the most fragments earned in a single game today
     #[wasm_bindgen]
-    pub fn render(&mut self, buffer: &mut [u8], keys: Vec<u32>, mut dt: f32) -> i64 {
+    pub fn daily_best_score(&self) -> Option<i64> {
+        self.daily.as_ref().and_then(|daily| daily.best_score())
+    }
+
+    #[wasm_bindgen]
+    pub fn set_daily_best_score(&mut self, best_score: Option<i64>) {
+        if let Some(daily) = &mut self.daily {
+            daily.set_best_score(best_score);
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn render(&mut self, buffer: &mut [u8], keys: Vec<u32>, dt: f32) -> i64 {
+        let games_played = self.games_played();
+        let ret = self.tick_game(buffer, keys, dt);
+        let restarted = self.games_played() != games_played;
+
+        if let Some(daily) = &mut self.daily {
+            daily.tick(dt, ret);
+
+            let finished = match self.game {
+                ManualGame::SnailMaze(_) if ret != 0 => {
+                    daily.finish_timed();
+                    true
+                }
+                ManualGame::Wolfenstein(_) if ret == GOAL_SCORE as i64 => {
+                    daily.finish_timed();
+                    true
+                }
+                ManualGame::PacSnail(_)
+                | ManualGame::Asteroids(_)
+                | ManualGame::FallingSnails(_)
+                    if restarted =>
+                {
+                    daily.finish_scored();
+                    true
+                }
+                _ => false,
+            };
+
+            if finished {
+                self.set_game(self.game_type);
+            }
+        }
+
+        ret
+    }
+}
+
+impl Game {
+    fn tick_game(&mut self, buffer: &mut [u8], keys: Vec<u32>, mut dt: f32) -> i64 {
         match &mut self.game {
             ManualGame::SnailMaze(game) => {
-                let ret = game.tick(&mut self.lfsr, keys, dt);
+                let ret = game.tick(&mut *self.rng, keys, dt);
                 game.render(buffer);
                 ret as i64
             }
             ManualGame::PacSnail(game) => {
                 let mut ret = 0;
                 while dt > 30.0 {
-                    ret += game.tick(&mut self.lfsr, &keys, 30.0);
+                    ret += game.tick(&mut *self.rng, &keys, 30.0);
                     dt -= 30.0;
                 }
-                ret += game.tick(&mut self.lfsr, &keys, dt);
+                ret += game.tick(&mut *self.rng, &keys, dt);
 
                 game.render(buffer);
                 ret as i64
             }
             ManualGame::Asteroids(game) => {
-                let ret = game.tick(&mut self.lfsr, keys, dt);
+                let ret = game.tick(&mut *self.rng, keys, dt);
                 game.render(buffer);
                 ret as i64
             }
             ManualGame::Wolfenstein(game) => {
-                let ret = game.tick(&mut self.lfsr, keys, dt);
+                let ret = game.tick(&mut *self.rng, keys, dt);
                 game.render(buffer);
                 ret as i64
             }
             ManualGame::FallingSnails(game) => {
-                let ret = game.tick(&mut self.lfsr, keys, dt);
+                let ret = game.tick(&mut *self.rng, keys, dt);
                 game.render(buffer);
                 ret
             }
         }
     }
+
+    // None for games which are played for time rather than score
+    fn games_played(&self) -> Option<usize> {
+        match &self.game {
+            ManualGame::PacSnail(game) => Some(game.games_played()),
+            ManualGame::Asteroids(game) => Some(game.games_played()),
+            ManualGame::FallingSnails(game) => Some(game.games_played()),
+            ManualGame::SnailMaze(_) | ManualGame::Wolfenstein(_) => None,
+        }
+    }
+
+    fn load(&mut self, code: &str) -> Result<(), CodeError> {
+        let code = ShareCode::parse(code)?;
+
+        match code.algorithm {
+            WOLFENSTEIN_ALGORITHM => {
+                let mut game = WolfensteinGame::new(&mut *self.rng);
+                game.load(code)?;
+                self.game = ManualGame::Wolfenstein(game);
+                self.game_type = WOLFENSTEIN_ALGORITHM as u32;
+            }
+            _ => {
+                let mut game = ManualMaze::new(&mut *self.rng);
+                game.load(code)?;
+                self.game = ManualGame::SnailMaze(Box::new(game));
+                self.game_type = MANUAL_MAZE_ALGORITHM as u32;
+            }
+        }
+
+        // a shared level isn't the same for everyone today
+        self.daily = None;
+
+        Ok(())
+    }
 }
 
 const MANUAL_MOVEMENT_TIME: f32 = SNAIL_MOVEMENT_TIME / 2.0;
@@ -116,6 +287,7 @@ const MANUAL_MOVEMENT_TIME: f32 = SNAIL_MOVEMENT_TIME / 2.0;
 struct ManualMaze {
     snail: Snail<7>,
     maze: Maze<7>,
+    code: ShareCode,
     end_pos: Vec2,
     bg_buffer: Vec<u8>,
 
@@ -125,28 +297,47 @@ struct ManualMaze {
 }
 
 impl ManualMaze {
-    fn new(lfsr: &mut LFSR) -> Self {
-        let mut bg_buffer = vec![0; 4 * 71 * 71];
-        let mut image = Image {
-            buffer: &mut bg_buffer,
-            width: 71,
-            height: 71,
-        };
-
-        let mut maze = Maze::new();
-        maze.generate(lfsr);
-
-        maze.draw_background(DEFAULT_PALETTE[4], DEFAULT_PALETTE[5], &mut image, 0, 0);
-
-        ManualMaze {
+    fn new(rng: &mut dyn Rng) -> Self {
+        let mut game = ManualMaze {
             snail: Snail::new(),
-            maze,
+            maze: Maze::new(),
+            code: ManualMaze::random_code(rng),
             end_pos: Vec2 { x: 6, y: 6 },
-            bg_buffer,
+            bg_buffer: vec![0; 4 * 71 * 71],
             movement_timer: MANUAL_MOVEMENT_TIME,
             solve_type: 25,
             time: 0.0,
+        };
+
+        game.load(game.code).unwrap();
+        game
+    }
+
+    // each level gets a seed of its own, so that it can be shared
+    fn random_code(rng: &mut dyn Rng) -> ShareCode {
+        ShareCode::new(MANUAL_MAZE_ALGORITHM, 7, RngKind::Xoshiro, rng.big() as u32).unwrap()
+    }
+
+    fn load(&mut self, code: ShareCode) -> Result<(), CodeError> {
+        self.maze = code.generate_maze(MANUAL_MAZE_ALGORITHM)?;
+        self.code = code;
+        self.snail.reset();
+
+        let mut image = Image {
+            buffer: &mut self.bg_buffer,
+            width: 71,
+            height: 71,
+        };
+
+        if self.solve_type > 0 {
+            self.maze
+                .draw_background(DEFAULT_PALETTE[4], DEFAULT_PALETTE[5], &mut image, 0, 0);
+        } else {
+            self.maze
+                .draw_background(GRAYSCALE_PALETTE[4], GRAYSCALE_PALETTE[5], &mut image, 0, 0);
         }
+
+        Ok(())
     }
 
     fn resolution(&self) -> Vec<u32> {
@@ -159,7 +350,7 @@ impl ManualMaze {
     // 2 => left
     // 4 => down
     // 8 => up
-    fn tick(&mut self, lfsr: &mut LFSR, keys: Vec<u32>, dt: f32) -> i32 {
+    fn tick(&mut self, rng: &mut dyn Rng, keys: Vec<u32>, dt: f32) -> i32 {
         self.time += dt;
 
         self.movement_timer += dt;
@@ -188,36 +379,15 @@ impl ManualMaze {
         }
 
         if self.snail.prev_pos == self.end_pos {
-            self.snail.reset();
-
-            self.maze.generate(lfsr);
-
-            let mut image = Image {
-                buffer: &mut self.bg_buffer,
-                width: 71,
-                height: 71,
-            };
-
             let solve_type = self.solve_type;
 
-            if lfsr.big() % 10 == 0 {
+            if rng.big() % 10 == 0 {
                 self.solve_type = -25;
             } else {
                 self.solve_type = 25;
             }
 
-            if self.solve_type > 0 {
-                self.maze
-                    .draw_background(DEFAULT_PALETTE[4], DEFAULT_PALETTE[5], &mut image, 0, 0);
-            } else {
-                self.maze.draw_background(
-                    GRAYSCALE_PALETTE[4],
-                    GRAYSCALE_PALETTE[5],
-                    &mut image,
-                    0,
-                    0,
-                );
-            }
+            self.load(ManualMaze::random_code(rng)).unwrap();
 
             solve_type
         } else {
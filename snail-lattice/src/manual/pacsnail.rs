@@ -1,499 +1,56 @@
 use crate::{
+    ascii::{Board, Pellet},
     direction::Direction,
+    ghosts::{Ghost, GhostStatus, Personality, GHOST_MOVEMENT_TIME},
     image::Image,
-    lfsr::LFSR,
     maze::{Maze, ANIMATION_TIME, SNAIL_MOVEMENT_TIME},
-    snail::{Snail, DEFAULT_PALETTE, INVERTED_PALETTE},
-    utils::{lerpi, Vec2, Vec2f},
+    pathfinding::FieldCache,
+    rng::Rng,
+    snail::DEFAULT_PALETTE,
+    utils::{Vec2, Vec2f},
 };
 
-const PACMAN_BOARD: &[u8] = concat!(
-    "#####################",
-    "#. . . . .#. . . . .#",
-    "# ### ### # ### ### #",
-    "#* . . . . . . . . *#",
-    "# ### # ##### # ### #",
-    "#. . .#. .#. .#. . .#",
-    "##### ### # ### #####",
-    "#   #.#. . . .#.#   #",
-    "#   # # ##### # #   #",
-    "#   #. .#   #. .#   #",
-    "#   # # ##### # #   #",
-    "#   #.#. . . .#.#   #",
-    "##### # ##### # #####",
-    "#. . . . .#. . . . .#",
-    "# ### ### # ### ### #",
-    "#* .#. .     . .#. *#",
-    "### # # ##### # # ###",
-    "#. . .#. .#. .#. . .#",
-    "# ####### # ####### #",
-    "#. . . . . . . . . .#",
-    "#####################",
-)
-.as_bytes();
-
-#[derive(Clone, Copy)]
-enum GhostStatus {
-    Chase,
-    Scatter,
-    Frightened,
-    // Locked,
-}
-
-trait Ghost {
-    fn draw(&self, status: GhostStatus, animation_cycle: bool, progress: f32, image: &mut Image);
-    fn get_snail(&mut self) -> &mut Snail<10>;
-    fn scatter_point(&self) -> Vec2;
-
-    fn pos(&mut self, fact: f32) -> Vec2f {
-        let snail = self.get_snail();
-
-        Vec2f {
-            x: lerpi(snail.prev_pos.x as i32 * 10, snail.pos.x as i32 * 10, fact) as f32,
-            y: lerpi(snail.prev_pos.y as i32 * 10, snail.pos.y as i32 * 10, fact) as f32,
-        }
-    }
-
-    fn chase(
-        &mut self,
-        lfsr: &mut LFSR,
-        maze: &Maze<10>,
-        player_pos: Vec2,
-        player_direction: Direction,
-    );
-
-    fn reset(&mut self) {
-        let point = self.scatter_point();
-        let snail = self.get_snail();
-        snail.pos = point;
-        snail.prev_pos = snail.pos;
-    }
-
-    fn step(
-        &mut self,
-        status: GhostStatus,
-        lfsr: &mut LFSR,
-        maze: &mut Maze<10>,
-        player_pos: Vec2,
-        player_direction: Direction,
-    ) {
-        let (pos, dir) = {
-            let snail = self.get_snail();
-            (snail.pos, snail.direction)
-        };
-
-        let behind_dir = dir.flip();
-
-        let mut set_wall = false;
-
-        if !maze.get_cell(pos.x, pos.y).has_wall(behind_dir) {
-            maze.set_wall(pos.x, pos.y, behind_dir);
-            set_wall = true;
-        }
-
-        let valid_directions = maze.get_cell(pos.x, pos.y).valid_directions();
-
-        if valid_directions.len() > 1 {
-            match status {
-                GhostStatus::Chase => self.chase(lfsr, maze, player_pos, player_direction),
-                GhostStatus::Scatter => self.scatter(lfsr, maze),
-                GhostStatus::Frightened => self.frightened(lfsr, maze),
-            }
-        } else {
-            let snail = self.get_snail();
-            snail.direction = valid_directions[0];
-
-            snail.move_forward(maze);
-        }
-
-        if set_wall {
-            maze.set_wall(pos.x, pos.y, behind_dir);
-        }
-    }
-
-    fn scatter(&mut self, lfsr: &mut LFSR, maze: &Maze<10>) {
-        let scatter_point = self.scatter_point();
-        let mut snail = self.get_snail();
-
-        // is it bad that we have to re-compute this for every single iteration? yes.
-        // does this realistically matter when we only have 4 ghosts and a 10x10 maze? hopefully not
-        let directions = maze.get_solve_sequence(snail.pos.x, snail.pos.y, scatter_point);
-
-        snail.direction = match directions.first() {
-            Some(dir) => *dir,
-            None => {
-                let directions = maze.get_cell(snail.pos.x, snail.pos.y).valid_directions();
-                directions[lfsr.next() as usize % directions.len()]
-            }
-        };
-
-        snail.move_forward(maze);
-    }
-
-    fn frightened(&mut self, lfsr: &mut LFSR, maze: &Maze<10>) {
-        let mut snail = self.get_snail();
-
-        let cell = maze.get_cell(snail.pos.x, snail.pos.y);
-        let valid_directions = cell.valid_directions();
-
-        if valid_directions.len() > 2 {
-            snail.direction = valid_directions[lfsr.next() as usize % valid_directions.len()];
-        } else if valid_directions.len() == 1 {
-            snail.direction = valid_directions[0];
-        } else {
-            if snail.direction.flip() == valid_directions[0] {
-                snail.direction = valid_directions[1];
-            } else {
-                snail.direction = valid_directions[0]
-            }
-        }
-
-        snail.move_forward(maze);
-    }
-}
-
-// Scatters to top right
-// Targets player directly during chase
-struct Blinky(Snail<10>);
-
-pub const BLINKY_PALETTE: [[u8; 3]; 6] = [
-    [0xff, 0x00, 0x00], // orange
-    [0xff, 0x55, 0x00], // purple
-    [0xff, 0x00, 0x00], // orange
-    [0xff, 0xff, 0xff], // snail "eyes"
-    [0x00, 0x00, 0x00],
-    [0x00, 0x0A, 0x00],
-];
-
-impl Ghost for Blinky {
-    fn draw(&self, status: GhostStatus, animation_cycle: bool, progress: f32, image: &mut Image) {
-        let palette = match status {
-            GhostStatus::Chase | GhostStatus::Scatter => BLINKY_PALETTE,
-            GhostStatus::Frightened => INVERTED_PALETTE,
-        };
-
-        self.0.draw(palette, animation_cycle, progress, image, 0, 0);
-    }
-
-    fn get_snail(&mut self) -> &mut Snail<10> {
-        &mut self.0
-    }
-
-    fn scatter_point(&self) -> Vec2 {
-        Vec2 { x: 8, y: 1 }
-    }
-
-    fn chase(
-        &mut self,
-        _lfsr: &mut LFSR,
-        maze: &Maze<10>,
-        player_pos: Vec2,
-        _player_direction: Direction,
-    ) {
-        let directions = maze.get_solve_sequence(self.0.pos.x, self.0.pos.y, player_pos);
-        if let Some(dir) = directions.first() {
-            self.0.direction = *dir;
-            assert!(self.0.move_forward(maze));
-        }
-    }
-}
-
-// Scatters to top right
-// Targets 1 tile in front of pacman during chase mode
-struct Pinky(Snail<10>);
-
-pub const PINKY_PALETTE: [[u8; 3]; 6] = [
-    [0xff, 0x00, 0xff], // orange
-    [0xff, 0x55, 0xff], // purple
-    [0xff, 0x00, 0xff], // orange
-    [0xff, 0xff, 0xff], // snail "eyes"
-    [0x00, 0x00, 0x00],
-    [0x00, 0x0A, 0x00],
-];
-
-impl Ghost for Pinky {
-    fn draw(&self, status: GhostStatus, animation_cycle: bool, progress: f32, image: &mut Image) {
-        let palette = match status {
-            GhostStatus::Chase | GhostStatus::Scatter => PINKY_PALETTE,
-            GhostStatus::Frightened => INVERTED_PALETTE,
-        };
-
-        self.0.draw(palette, animation_cycle, progress, image, 0, 0);
-    }
-
-    fn get_snail(&mut self) -> &mut Snail<10> {
-        &mut self.0
-    }
-
-    fn scatter_point(&self) -> Vec2 {
-        Vec2 { x: 1, y: 1 }
-    }
-
-    fn chase(
-        &mut self,
-        _lfsr: &mut LFSR,
-        maze: &Maze<10>,
-        player_pos: Vec2,
-        player_direction: Direction,
-    ) {
-        let mut target_position = player_pos;
-
-        match player_direction {
-            Direction::Up => target_position.y = target_position.y.saturating_sub(1),
-            Direction::Down => target_position.y = (target_position.y + 1).min(9),
-            Direction::Left => target_position.x = target_position.x.saturating_sub(1),
-            Direction::Right => target_position.x = (target_position.x + 1).min(9),
-        }
-
-        let directions = maze.get_solve_sequence(self.0.pos.x, self.0.pos.y, target_position);
-
-        self.0.direction = match directions.first() {
-            Some(dir) => *dir,
-            None => maze.get_solve_sequence(self.0.pos.x, self.0.pos.y, player_pos)[0],
-        };
-
-        assert!(self.0.move_forward(maze));
-    }
-}
-
-// Scatters to bottom left
-// Targets pacman during chase mode, if closer than 1 tile, instead targets his scatter point
-struct Clyde(Snail<10>);
-
-pub const CLYDE_PALETTE: [[u8; 3]; 6] = [
-    [0xaa, 0xaa, 0x00], // orange
-    [0xff, 0xaa, 0x00], // purple
-    [0xaa, 0xaa, 0x00], // orange
-    [0xff, 0xff, 0xff], // snail "eyes"
-    [0x00, 0x00, 0x00],
-    [0x00, 0x0A, 0x00],
-];
-
-impl Ghost for Clyde {
-    fn draw(&self, status: GhostStatus, animation_cycle: bool, progress: f32, image: &mut Image) {
-        let palette = match status {
-            GhostStatus::Chase | GhostStatus::Scatter => CLYDE_PALETTE,
-            GhostStatus::Frightened => INVERTED_PALETTE,
-        };
-
-        self.0.draw(palette, animation_cycle, progress, image, 0, 0);
-    }
-
-    fn get_snail(&mut self) -> &mut Snail<10> {
-        &mut self.0
-    }
-
-    fn scatter_point(&self) -> Vec2 {
-        Vec2 { x: 1, y: 8 }
-    }
-
-    fn chase(
-        &mut self,
-        lfsr: &mut LFSR,
-        maze: &Maze<10>,
-        player_pos: Vec2,
-        _player_direction: Direction,
-    ) {
-        let target_position = if player_pos.manhattan_dist(self.0.pos) < 5 {
-            self.scatter_point()
-        } else {
-            player_pos
-        };
-
-        let directions = maze.get_solve_sequence(self.0.pos.x, self.0.pos.y, target_position);
-
-        self.0.direction = match directions.first() {
-            Some(dir) => *dir,
-            None => {
-                let directions = maze.get_cell(self.0.pos.x, self.0.pos.y).valid_directions();
-                directions[lfsr.next() as usize % directions.len()]
-            }
-        };
-
-        self.0.move_forward(maze);
-    }
-}
-
-// Scatters to bottom left
-// should target an extended vector from blinky's line of sight to the snail, but that would be
-// annoying to implenment which how I have things structured right now, so we instead target one
-// space behind the snail.
-struct Inky(Snail<10>);
-
-pub const INKY_PALETTE: [[u8; 3]; 6] = [
-    [0x55, 0xaa, 0xff], // light blue
-    [0x55, 0xff, 0xff], // lighter lbue
-    [0x55, 0xaa, 0xff], // light blue
-    [0xff, 0xff, 0xff], // snail "eyes"
-    [0x00, 0x00, 0x00],
-    [0x00, 0x0A, 0x00],
-];
-
-impl Ghost for Inky {
-    fn draw(&self, status: GhostStatus, animation_cycle: bool, progress: f32, image: &mut Image) {
-        let palette = match status {
-            GhostStatus::Chase | GhostStatus::Scatter => INKY_PALETTE,
-            GhostStatus::Frightened => INVERTED_PALETTE,
-        };
-
-        self.0.draw(palette, animation_cycle, progress, image, 0, 0);
-    }
-
-    fn get_snail(&mut self) -> &mut Snail<10> {
-        &mut self.0
-    }
-
-    fn scatter_point(&self) -> Vec2 {
-        Vec2 { x: 8, y: 8 }
-    }
-
-    fn chase(
-        &mut self,
-        _lfsr: &mut LFSR,
-        maze: &Maze<10>,
-        player_pos: Vec2,
-        player_direction: Direction,
-    ) {
-        let mut target_position = player_pos;
-
-        match player_direction.flip() {
-            Direction::Up => target_position.y = target_position.y.saturating_sub(1),
-            Direction::Down => target_position.y = (target_position.y + 1).min(9),
-            Direction::Left => target_position.x = target_position.x.saturating_sub(1),
-            Direction::Right => target_position.x = (target_position.x + 1).min(9),
-        }
-
-        let directions = maze.get_solve_sequence(self.0.pos.x, self.0.pos.y, target_position);
-
-        self.0.direction = match directions.first() {
-            Some(dir) => *dir,
-            None => maze.get_solve_sequence(self.0.pos.x, self.0.pos.y, player_pos)[0],
-        };
-
-        assert!(self.0.move_forward(maze));
-    }
-}
+const PACMAN_BOARD: &str = concat!(
+    "#####################\n",
+    "#. . . . .#. . . . .#\n",
+    "# ### ### # ### ### #\n",
+    "#* . . . . . . . . *#\n",
+    "# ### # ##### # ### #\n",
+    "#. . .#. .#. .#. . .#\n",
+    "##### ### # ### #####\n",
+    "#   #.#. . . .#.#   #\n",
+    "#   # # ##### # #   #\n",
+    "#   #. .#   #. .#   #\n",
+    "#   # # ##### # #   #\n",
+    "#   #.#. . . .#.#   #\n",
+    "##### # ##### # #####\n",
+    "#. . . . .#. . . . .#\n",
+    "# ### ### # ### ### #\n",
+    "#* .#. .     . .#. *#\n",
+    "### # # ##### # # ###\n",
+    "#. . .#. .#. .#. . .#\n",
+    "# ####### # ####### #\n",
+    "#. . . . . . . . . .#\n",
+    "#####################\n",
+);
 
 fn pacman_maze() -> (Maze<10>, Vec<Pellet>, usize) {
-    let mut maze = Maze::new();
-    let mut pellets = vec![Pellet::None; 10 * 10];
-    let mut pellet_count = 0;
-    let width = 21;
-
-    for x in 0..10 {
-        for y in 0..10 {
-            let pos = (y * 2 + 1) * width + (x * 2 + 1);
-            let mut cell = 0;
-
-            if PACMAN_BOARD[pos] == b'.' {
-                pellets[y * 10 + x] = Pellet::Pellet;
-                pellet_count += 1;
-            } else if PACMAN_BOARD[pos] == b'*' {
-                pellets[y * 10 + x] = Pellet::Powerup;
-                pellet_count += 1;
-            }
-
-            // right
-            if PACMAN_BOARD[pos + 1] == b'#' {
-                cell |= 1;
-            }
-
-            // left
-            if PACMAN_BOARD[pos - 1] == b'#' {
-                cell |= 2;
-            }
+    let board = Board::parse(PACMAN_BOARD).unwrap();
+    let pellet_count = board.pellet_count();
 
-            // up
-            if PACMAN_BOARD[pos + width] == b'#' {
-                cell |= 4;
-            }
-
-            // down
-            if PACMAN_BOARD[pos - width] == b'#' {
-                cell |= 8;
-            }
-
-            maze.xor_cell(x, y, cell);
-        }
-    }
-
-    (maze, pellets, pellet_count)
+    (board.maze, board.pellets, pellet_count)
 }
 
-fn all_ghosts() -> Vec<Box<dyn Ghost>> {
-    let mut ghosts: Vec<Box<dyn Ghost>> = vec![
-        Box::new(Blinky(Snail::new())),
-        Box::new(Pinky(Snail::new())),
-        Box::new(Clyde(Snail::new())),
-        Box::new(Inky(Snail::new())),
-    ];
-
-    for ghost in &mut ghosts {
-        ghost.reset();
-    }
-
-    ghosts
+// the ghosts scatter to the corners of the board
+fn all_ghosts() -> Vec<Ghost<10>> {
+    Personality::ALL
+        .iter()
+        .map(|personality| Ghost::new(*personality, personality.corner(10)))
+        .collect()
 }
 
-const GHOST_MOVEMENT_TIME: f32 = SNAIL_MOVEMENT_TIME * 1.6;
 const POWERUP_TIME: f32 = SNAIL_MOVEMENT_TIME * 30.0;
 
-// Each entry represents 2 seconds
-const SCATTER_SCHEUDLE: &'static [GhostStatus] = &[
-    GhostStatus::Scatter,
-    GhostStatus::Scatter,
-    GhostStatus::Scatter,
-    GhostStatus::Scatter,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Scatter,
-    GhostStatus::Scatter,
-    GhostStatus::Scatter,
-    GhostStatus::Scatter,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Scatter,
-    GhostStatus::Scatter,
-    GhostStatus::Scatter,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Chase,
-    GhostStatus::Scatter,
-    GhostStatus::Scatter,
-    GhostStatus::Scatter,
-    GhostStatus::Chase,
-];
-
-#[derive(Clone, Copy, PartialEq)]
-enum Pellet {
-    Pellet,
-    Powerup,
-    None,
-}
-
 struct Player {
     pos: Vec2f,
     direction: Option<Direction>,
@@ -613,20 +170,27 @@ pub struct PacSnail {
     bg_buffer: Vec<u8>,
     pellets: Vec<Pellet>,
     player: Player,
-    ghosts: Vec<Box<dyn Ghost>>,
-    locked_ghosts: Vec<Box<dyn Ghost>>,
+    ghosts: Vec<Ghost<10>>,
+    locked_ghosts: Vec<Ghost<10>>,
     ghost_movement_timer: f32,
     powerup_timer: f32,
     powerup_streak: usize,
     maze: Maze<10>,
+
+    // the board never changes, so distance fields to every target can be kept until reset
+    fields: FieldCache<Direction>,
     pellet_count: usize,
     time: f32,
+
+    // goes up every time the game starts over
+    games_played: usize,
 }
 
 impl PacSnail {
     pub fn new() -> Self {
         let mut s = Self {
             maze: Maze::new(),
+            fields: FieldCache::new(),
             pellets: vec![],
             pellet_count: 0,
             player: Player::new(),
@@ -635,6 +199,7 @@ impl PacSnail {
             powerup_timer: 0.0,
             powerup_streak: 0,
             time: 0.0,
+            games_played: 0,
             ghosts: vec![],
             locked_ghosts: vec![],
         };
@@ -689,9 +254,11 @@ impl PacSnail {
         let (maze, pellets, pellet_count) = pacman_maze();
 
         self.maze = maze;
+        self.fields.clear();
         self.pellets = pellets;
         self.pellet_count = pellet_count;
         self.time = 0.0;
+        self.games_played += 1;
 
         self.ghosts = all_ghosts();
         self.locked_ghosts.clear();
@@ -717,7 +284,11 @@ impl PacSnail {
         vec![101, 101]
     }
 
-    pub fn tick(&mut self, lfsr: &mut LFSR, keys: &Vec<u32>, dt: f32) -> i32 {
+    pub fn games_played(&self) -> usize {
+        self.games_played
+    }
+
+    pub fn tick(&mut self, rng: &mut dyn Rng, keys: &Vec<u32>, dt: f32) -> i32 {
         if self.pellet_count == 0 {
             self.reset();
             return -100;
@@ -767,15 +338,15 @@ impl PacSnail {
             let current_status = if self.powerup_timer > 0.0 {
                 GhostStatus::Frightened
             } else {
-                SCATTER_SCHEUDLE
-                    [((self.time / 2000.0).floor() as usize).min(SCATTER_SCHEUDLE.len() - 1)]
+                GhostStatus::scheduled(self.time)
             };
 
             for ghost in &mut self.ghosts {
                 ghost.step(
                     current_status,
-                    lfsr,
-                    &mut self.maze,
+                    rng,
+                    &self.maze,
+                    &mut self.fields,
                     player_pos,
                     self.player.direction.unwrap_or(Direction::Right),
                 );
@@ -841,6 +412,8 @@ impl PacSnail {
                 animation_cycle,
                 self.ghost_movement_timer / GHOST_MOVEMENT_TIME,
                 &mut image,
+                0,
+                0,
             );
         }
 
@@ -1,11 +1,10 @@
-use std::{
-    collections::{HashSet, VecDeque},
-    f32::consts::PI,
-};
+use std::{collections::HashSet, f32::consts::PI};
 
 use crate::{
     image::Image,
-    lfsr::LFSR,
+    pathfinding::{bfs, Graph},
+    rng::{Rng, RngKind},
+    share::{CodeError, ShareCode, WOLFENSTEIN_ALGORITHM},
     snail::{DEFAULT_PALETTE, INVERTED_PALETTE},
     utils::{Vec2f, Vec2i},
 };
@@ -23,6 +22,9 @@ const SCREEN_W: usize = 240;
 const SCREEN_H: usize = 240;
 const LINE_HEIGHT: usize = 180;
 
+// returned by tick when the goal is reached
+pub const GOAL_SCORE: i32 = -10_000_000;
+
 fn generate_bg_buffer(width: usize, height: usize) -> Vec<u8> {
     let mut buffer = vec![0; width * height * 4];
 
@@ -55,7 +57,13 @@ fn generate_bg_buffer(width: usize, height: usize) -> Vec<u8> {
     buffer
 }
 
-fn generate_maze_random_walk(grid: &mut Vec<u8>, size: usize, lfsr: &mut LFSR, x: usize, y: usize) {
+fn generate_maze_random_walk(
+    grid: &mut Vec<u8>,
+    size: usize,
+    rng: &mut dyn Rng,
+    x: usize,
+    y: usize,
+) {
     let mut next = Some((x, y));
     let width = 2 * size + 1;
 
@@ -64,7 +72,7 @@ fn generate_maze_random_walk(grid: &mut Vec<u8>, size: usize, lfsr: &mut LFSR, x
         grid[(2 * y + 1) * width + (2 * x + 1)] = 0;
         next = None;
 
-        for direction in lfsr.random_order() {
+        for direction in rng.random_order() {
             //right
             if direction == 0 && x < size - 1 && grid[(2 * y + 1) * width + (2 * x + 3)] == 1 {
                 // unset right wall
@@ -98,51 +106,76 @@ fn generate_maze_random_walk(grid: &mut Vec<u8>, size: usize, lfsr: &mut LFSR, x
     }
 }
 
-fn find_fartherst_point(grid: &[u8], size: usize, x: usize, y: usize) -> (usize, usize) {
-    let width = 2 * size + 1;
-    let mut queue = VecDeque::new();
-    let mut visited = HashSet::new(); // BTreeSet might be faster but idc
-    let mut last_point = (x, y);
-
-    queue.push_back((x, y));
-
-    while let Some((x, y)) = queue.pop_front() {
-        last_point = (x, y);
-        visited.insert(last_point);
-
-        // push all valid directions to the queue
-        if x < size - 1
-            && grid[(2 * y + 1) * width + (2 * x + 2)] == 0
-            && !visited.contains(&(x + 1, y))
-        {
-            queue.push_back((x + 1, y));
+// a maze stored as a grid of wall and floor tiles, where cell (x, y) is the tile at
+// (2 * x + 1, 2 * y + 1) and the tiles between cells are the walls between them
+struct Grid<'a> {
+    grid: &'a [u8],
+    size: usize,
+}
+
+impl Graph for Grid<'_> {
+    type Node = (usize, usize);
+    type Move = ();
+
+    fn node_count(&self) -> usize {
+        self.size * self.size
+    }
+
+    fn index(&self, (x, y): (usize, usize)) -> usize {
+        y * self.size + x
+    }
+
+    fn node(&self, index: usize) -> (usize, usize) {
+        (index % self.size, index / self.size)
+    }
+
+    fn edges(&self, (x, y): (usize, usize)) -> Vec<((), (usize, usize), usize)> {
+        let width = 2 * self.size + 1;
+        let mut edges = Vec::with_capacity(4);
+
+        // right
+        if x < self.size - 1 && self.grid[(2 * y + 1) * width + (2 * x + 2)] == 0 {
+            edges.push(((), (x + 1, y), 1));
         }
-        //left
-        if x > 0 && grid[(2 * y + 1) * width + (2 * x)] == 0 && !visited.contains(&(x - 1, y)) {
-            queue.push_back((x - 1, y));
+        // left
+        if x > 0 && self.grid[(2 * y + 1) * width + (2 * x)] == 0 {
+            edges.push(((), (x - 1, y), 1));
         }
         // down
-        if y < size - 1
-            && grid[(2 * y + 2) * width + (2 * x + 1)] == 0
-            && !visited.contains(&(x, y + 1))
-        {
-            queue.push_back((x, y + 1));
+        if y < self.size - 1 && self.grid[(2 * y + 2) * width + (2 * x + 1)] == 0 {
+            edges.push(((), (x, y + 1), 1));
         }
         // up
-        if y > 0 && grid[(2 * y) * width + (2 * x + 1)] == 0 && !visited.contains(&(x, y - 1)) {
-            queue.push_back((x, y - 1));
+        if y > 0 && self.grid[(2 * y) * width + (2 * x + 1)] == 0 {
+            edges.push(((), (x, y - 1), 1));
         }
+
+        edges
+    }
+
+    fn reverse_edges(&self, node: (usize, usize)) -> Vec<((), (usize, usize), usize)> {
+        self.edges(node)
     }
+}
+
+fn find_fartherst_point(grid: &[u8], size: usize, x: usize, y: usize) -> (usize, usize) {
+    let grid = Grid { grid, size };
+    let distances = bfs(&grid, &[(x, y)]);
 
-    last_point
+    let farthest = (0..grid.node_count())
+        .filter(|&i| distances[i] != usize::MAX)
+        .max_by_key(|&i| distances[i])
+        .unwrap_or(grid.index((x, y)));
+
+    grid.node(farthest)
 }
 
 // assumes buffer is of size (2 * size + 1)^2
-fn generate_maze(grid: &mut Vec<u8>, lfsr: &mut LFSR, size: usize) {
+fn generate_maze(grid: &mut Vec<u8>, rng: &mut dyn Rng, size: usize) {
     // fill in entire grid with walls
     grid.fill(1);
 
-    generate_maze_random_walk(grid, size, lfsr, 0, 0);
+    generate_maze_random_walk(grid, size, rng, 0, 0);
 
     let width = 2 * size + 1;
 
@@ -150,7 +183,7 @@ fn generate_maze(grid: &mut Vec<u8>, lfsr: &mut LFSR, size: usize) {
         for y in 0..size {
             // if not visited
             if grid[(2 * y + 1) * width + (2 * x + 1)] == 1 {
-                for direction in lfsr.random_order() {
+                for direction in rng.random_order() {
                     //right
                     if direction == 0
                         && x < size - 1
@@ -158,7 +191,7 @@ fn generate_maze(grid: &mut Vec<u8>, lfsr: &mut LFSR, size: usize) {
                     {
                         // unset right wall
                         grid[(2 * y + 1) * width + (2 * x + 2)] = 0;
-                        generate_maze_random_walk(grid, size, lfsr, x, y);
+                        generate_maze_random_walk(grid, size, rng, x, y);
                         break;
                     }
                     //left
@@ -168,7 +201,7 @@ fn generate_maze(grid: &mut Vec<u8>, lfsr: &mut LFSR, size: usize) {
                     {
                         // unset left wall
                         grid[(2 * y + 1) * width + (2 * x)] = 0;
-                        generate_maze_random_walk(grid, size, lfsr, x, y);
+                        generate_maze_random_walk(grid, size, rng, x, y);
                         break;
                     }
                     // down
@@ -178,7 +211,7 @@ fn generate_maze(grid: &mut Vec<u8>, lfsr: &mut LFSR, size: usize) {
                     {
                         // unset bottom wall wall
                         grid[(2 * y + 2) * width + (2 * x + 1)] = 0;
-                        generate_maze_random_walk(grid, size, lfsr, x, y);
+                        generate_maze_random_walk(grid, size, rng, x, y);
                         break;
                     }
                     // up
@@ -188,7 +221,7 @@ fn generate_maze(grid: &mut Vec<u8>, lfsr: &mut LFSR, size: usize) {
                     {
                         // unset top wall
                         grid[(2 * y) * width + (2 * x + 1)] = 0;
-                        generate_maze_random_walk(grid, size, lfsr, x, y);
+                        generate_maze_random_walk(grid, size, rng, x, y);
                         break;
                     }
                 }
@@ -226,6 +259,7 @@ pub struct WolfensteinGame {
     size: usize,
     width: usize,
     grid: Vec<u8>,
+    code: ShareCode,
 
     enemies: Vec<Enemy>,
     zbuffer: Vec<f32>,
@@ -236,7 +270,7 @@ pub struct WolfensteinGame {
 }
 
 impl WolfensteinGame {
-    pub fn new(lfsr: &mut LFSR) -> WolfensteinGame {
+    pub fn new(rng: &mut dyn Rng) -> WolfensteinGame {
         let mut game = WolfensteinGame {
             player_pos: Ray {
                 pos: Vec2f::new(1.5, 1.5),
@@ -249,6 +283,13 @@ impl WolfensteinGame {
             grid: vec![0; (2 * DEFAULT_MAZE_SIZE + 1).pow(2)],
             width: 2 * DEFAULT_MAZE_SIZE + 1,
             size: DEFAULT_MAZE_SIZE,
+            code: ShareCode::new(
+                WOLFENSTEIN_ALGORITHM,
+                DEFAULT_MAZE_SIZE,
+                RngKind::Xoshiro,
+                0,
+            )
+            .unwrap(),
 
             bg_buffer: generate_bg_buffer(SCREEN_W, SCREEN_H),
             zbuffer: vec![0.0; SCREEN_W],
@@ -258,7 +299,7 @@ impl WolfensteinGame {
             time: 0.0,
         };
 
-        game.reset(lfsr);
+        game.reset(rng);
 
         game
     }
@@ -314,8 +355,26 @@ impl WolfensteinGame {
         }
     }
 
-    fn reset(&mut self, lfsr: &mut LFSR) {
-        generate_maze(&mut self.grid, lfsr, self.size);
+    pub fn code(&self) -> ShareCode {
+        self.code
+    }
+
+    // starts a new level with a seed of its own, so that it can be shared
+    fn reset(&mut self, rng: &mut dyn Rng) {
+        let seed = rng.big() as u32;
+        let code =
+            ShareCode::new(WOLFENSTEIN_ALGORITHM, self.size, RngKind::Xoshiro, seed).unwrap();
+
+        self.load(code).unwrap();
+    }
+
+    // builds the level the code was made for, with the enemies in the same places
+    pub fn load(&mut self, code: ShareCode) -> Result<(), CodeError> {
+        code.expect(WOLFENSTEIN_ALGORITHM, self.size)?;
+        self.code = code;
+
+        let rng = &mut *code.build_rng();
+        generate_maze(&mut self.grid, rng, self.size);
 
         self.player_pos.pos.x = 1.5;
         self.player_pos.pos.y = 1.5;
@@ -363,8 +422,8 @@ impl WolfensteinGame {
             let mut y = 0;
 
             while invalid_positions.contains(&(x, y)) {
-                x = lfsr.big() % self.size;
-                y = lfsr.big() % self.size;
+                x = rng.big() % self.size;
+                y = rng.big() % self.size;
             }
 
             invalid_positions.insert((x, y));
@@ -372,13 +431,15 @@ impl WolfensteinGame {
             self.enemies
                 .push(Enemy::new((x * 2) as f32 + 1.5, (y * 2) as f32 + 1.5));
         }
+
+        Ok(())
     }
 
     pub fn resolution(&self) -> Vec<u32> {
         vec![240, 240]
     }
 
-    pub fn tick(&mut self, lfsr: &mut LFSR, keys: Vec<u32>, dt: f32) -> i32 {
+    pub fn tick(&mut self, rng: &mut dyn Rng, keys: Vec<u32>, dt: f32) -> i32 {
         self.time += dt;
         self.shoot_cooldown = (self.shoot_cooldown - dt).max(0.0);
 
@@ -406,9 +467,9 @@ impl WolfensteinGame {
 
         // test collision
         if self.player_pos.pos.dist2(self.goal_pos) < 0.5 * 0.5 {
-            self.reset(lfsr);
+            self.reset(rng);
 
-            return -10_000_000;
+            return GOAL_SCORE;
         }
 
         // down
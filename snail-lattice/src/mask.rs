@@ -0,0 +1,92 @@
+// Shapes for Maze::set_mask. true means the cell is part of the maze.
+
+use crate::image::{glyph, SNAIL_IMAGE, SNAIL_IMAGE_SIZE};
+
+pub fn heart_mask<const S: usize>() -> [bool; S * S] {
+    let mut mask = [false; S * S];
+
+    for y in 0..S {
+        for x in 0..S {
+            // (x^2 + y^2 - 1)^3 - x^2 y^3 <= 0, scaled to fit the maze
+            let u = ((x as f32 + 0.5) / S as f32 - 0.5) * 2.5;
+            let v = (0.5 - (y as f32 + 0.5) / S as f32) * 2.5 + 0.2;
+
+            let a = u * u + v * v - 1.0;
+            mask[y * S + x] = a * a * a - u * u * v * v * v <= 0.0;
+        }
+    }
+
+    mask
+}
+
+// the snail sprite facing right, stretched over the whole maze
+pub fn snail_mask<const S: usize>() -> [bool; S * S] {
+    let mut mask = [false; S * S];
+
+    for y in 0..S {
+        for x in 0..S {
+            let sx = x * SNAIL_IMAGE_SIZE / S;
+            let sy = y * SNAIL_IMAGE_SIZE / S;
+
+            // the sprite faces down, so swap the axes
+            mask[y * S + x] = SNAIL_IMAGE[sx * SNAIL_IMAGE_SIZE + sy] != 255;
+        }
+    }
+
+    mask
+}
+
+// text in the bitmap font, scaled up as far as it fits and centered. the same characters as
+// Image::draw_text are supported. each line is underlined and the lines are joined on the left so
+// that the letters are connected to each other. letters which still aren't connected are left out
+// when the maze is generated.
+pub fn text_mask<const S: usize>(text: &str) -> [bool; S * S] {
+    let mut mask = [false; S * S];
+
+    let lines: Vec<&str> = text.lines().collect();
+    let columns = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+
+    if columns == 0 {
+        return mask;
+    }
+
+    // characters are 3x4 with a gap of one pixel, and the underline takes up the gap below
+    let width = 4 * columns - 1;
+    let height = 5 * lines.len();
+    let scale = (S / width).min(S / height).max(1);
+
+    let ox = S.saturating_sub(width * scale) / 2;
+    let oy = S.saturating_sub(height * scale) / 2;
+
+    let mut fill = |px: usize, py: usize| {
+        for y in (oy + py * scale)..(oy + (py + 1) * scale) {
+            for x in (ox + px * scale)..(ox + (px + 1) * scale) {
+                if x < S && y < S {
+                    mask[y * S + x] = true;
+                }
+            }
+        }
+    };
+
+    for (row, line) in lines.iter().enumerate() {
+        for (column, c) in line.chars().enumerate() {
+            if c != ' ' {
+                for (x, y) in glyph(c) {
+                    fill(column * 4 + x, row * 5 + y);
+                }
+            }
+        }
+
+        for x in 0..(line.len() * 4).saturating_sub(1) {
+            fill(x, row * 5 + 4);
+        }
+
+        if row > 0 {
+            for y in (row * 5 - 1)..(row * 5 + 4) {
+                fill(0, y);
+            }
+        }
+    }
+
+    mask
+}
@@ -2,11 +2,15 @@ use std::{collections::VecDeque, mem::size_of};
 
 use crate::{
     direction::Direction,
+    ghosts::{Ghost, GhostStatus, Personality, GHOST_MOVEMENT_TIME},
     image::Image,
-    lattice::TilableMaze,
-    lfsr::LFSR,
+    lattice::{TilableMaze, MAZE_RNG},
+    metrics::MazeMetrics,
+    pathfinding::{a_star, bfs, DistanceField, FieldCache, Graph},
+    rng::Rng,
     solvers::{SolveStatus, Solver},
     utils::Vec2,
+    vm::{Program, VmError},
 };
 
 pub const SNAIL_MOVEMENT_TIME: f32 = 250.0;
@@ -15,6 +19,124 @@ pub const ANIMATION_TIME: f32 = 500.0;
 // each cell is 4 bits, so 2 cells per byte
 pub const CELLS_PER_IDX: usize = size_of::<usize>() * 2;
 
+// maze modifiers are toggled per lattice, and are passed down the same way upgrades are.
+// solvers opt in to the modifiers they know how to handle with Solver::supported_modifiers
+pub const TERRAIN_MODIFIER: u32 = 0b1;
+
+// scales the reward for each solve by the difficulty of the maze. this works with every solver,
+// so it doesn't need to be in supported_modifiers
+pub const DIFFICULTY_REWARD_MODIFIER: u32 = 0b10;
+
+// locks some of the passages along the solution behind doors, which only open once the snail
+// has picked up the matching key
+pub const DOOR_MODIFIER: u32 = 0b100;
+
+// one color per key and door, which also limits how many doors a maze can have
+const KEY_COLORS: [[u8; 3]; 4] = [
+    [0xff, 0xd7, 0x00], // gold
+    [0xe0, 0x30, 0x30], // red
+    [0x30, 0x90, 0xff], // blue
+    [0xb0, 0x40, 0xff], // purple
+];
+
+// offsets of the pixels making up a key within a cell
+const KEY_PIXELS: [(usize, usize); 14] = [
+    (2, 3),
+    (3, 3),
+    (4, 3),
+    (2, 4),
+    (4, 4),
+    (5, 4),
+    (6, 4),
+    (7, 4),
+    (8, 4),
+    (2, 5),
+    (3, 5),
+    (4, 5),
+    (6, 5),
+    (8, 5),
+];
+
+// links pairs of cells, so that stepping onto one of them moves the snail to the other
+pub const PORTAL_MODIFIER: u32 = 0b1000;
+
+// one color per pair of portals, which also limits how many pairs a maze can have
+const PORTAL_COLORS: [[u8; 3]; 4] = [
+    [0x00, 0xe0, 0xe0], // cyan
+    [0xff, 0x40, 0xc0], // pink
+    [0x80, 0xff, 0x00], // lime
+    [0xff, 0x90, 0x00], // orange
+];
+
+// every SHIFT_TIME milliseconds some of the maze's walls move, see Maze::shift
+pub const SHIFTING_MODIFIER: u32 = 0b10000;
+pub const SHIFT_TIME: f32 = 2000.0;
+
+// connects the left edge of the maze to the right edge and the top edge to the bottom edge
+pub const TORUS_MODIFIER: u32 = 0b100000;
+
+// ghosts from PacSnail roam the maze, and send the snail back to the start when they catch it.
+// solvers can see where they are with Maze::has_predator
+pub const PREDATOR_MODIFIER: u32 = 0b1000000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Feature {
+    None,
+    Key(usize),
+
+    // the passage leading out of the cell in the given direction is locked, and there is a wall
+    // there until the key with the same index is collected
+    Door(usize, Direction),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Terrain {
+    Plain,
+    Grass,
+    Mud,
+    Ice,
+    Slime,
+    // left behind wherever one of Demolitionist's bombs goes off
+    Rubble,
+}
+
+impl Terrain {
+    // time it takes to cross a cell, in quarters of a regular step
+    pub fn weight(self) -> usize {
+        match self {
+            Terrain::Plain => 4,
+            Terrain::Grass => 5,
+            Terrain::Mud => 8,
+            Terrain::Ice => 2,
+            Terrain::Slime => 3,
+            Terrain::Rubble => 4,
+        }
+    }
+
+    // multiplier applied to a snail's movement time while crossing the cell
+    pub fn cost(self) -> f32 {
+        self.weight() as f32 / Terrain::Plain.weight() as f32
+    }
+
+    // blends the terrain color into the background color of the maze
+    fn tint(self, bg_color: [u8; 3]) -> [u8; 3] {
+        let color = match self {
+            Terrain::Plain => return bg_color,
+            Terrain::Grass => [0x00, 0xa8, 0x00],
+            Terrain::Mud => [0x70, 0x40, 0x10],
+            Terrain::Ice => [0xc0, 0xf0, 0xff],
+            Terrain::Slime => [0x80, 0xff, 0x80],
+            Terrain::Rubble => [0x40, 0x40, 0x40],
+        };
+
+        [
+            ((bg_color[0] as u16 + color[0] as u16) / 2) as u8,
+            ((bg_color[1] as u16 + color[1] as u16) / 2) as u8,
+            ((bg_color[2] as u16 + color[2] as u16) / 2) as u8,
+        ]
+    }
+}
+
 pub struct MazeCell(pub usize);
 
 impl MazeCell {
@@ -47,6 +169,26 @@ where
     // time since last movement
     movement_timer: f32,
 
+    modifiers: u32,
+
+    // reward multiplier for the current maze, and the fraction of a solve left over from
+    // previous rewards. only used with DIFFICULTY_REWARD_MODIFIER
+    difficulty: f32,
+    reward: f32,
+
+    // time since the walls last moved. only used with SHIFTING_MODIFIER
+    shift_timer: f32,
+
+    // the ghosts hunting the snail, how long they've been hunting in the current maze, and time
+    // since they last moved. only used with PREDATOR_MODIFIER
+    predators: Vec<Ghost<S>>,
+    predator_fields: FieldCache<Direction>,
+    hunt_time: f32,
+    predator_timer: f32,
+
+    // what the current maze was generated from, see TilableMaze::generate_from
+    seed: u32,
+
     pub maze: Maze<S>,
 }
 
@@ -55,12 +197,26 @@ where
     [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
 {
     const SIZE: usize = S;
+    const SHAREABLE: bool = true;
 
     fn new() -> AutoMaze<S, T> {
         AutoMaze {
             solver: T::new(),
             clock: 0.0,
             movement_timer: 0.0,
+            modifiers: 0,
+
+            difficulty: 1.0,
+            reward: 0.0,
+
+            shift_timer: 0.0,
+
+            predators: vec![],
+            predator_fields: FieldCache::new(),
+            hunt_time: 0.0,
+            predator_timer: 0.0,
+
+            seed: 0,
 
             maze: Maze::new(),
         }
@@ -69,7 +225,7 @@ where
     // progresses time a certain number of microseconds
     // notably, no rendering happens when we tick the time
     // returns true if the tick results in a new maze to be generated
-    fn tick(&mut self, mut dt: f32, lfsr: &mut LFSR) -> SolveStatus {
+    fn tick(&mut self, mut dt: f32, rng: &mut dyn Rng) -> SolveStatus {
         self.clock += dt;
         let mut total = 0;
         let mut rerender = false;
@@ -80,15 +236,51 @@ where
             let movement_time = self.solver.movement_time();
             dt -= movement_time;
 
-            match self.solver.step(&mut self.maze, lfsr) {
+            let status = self.solver.step(&mut self.maze, rng);
+            match status {
                 SolveStatus::Solved(count) => {
-                    total += count;
+                    if (self.modifiers & DIFFICULTY_REWARD_MODIFIER) != 0 {
+                        self.reward += count as f32 * self.difficulty;
+                        total += self.reward as usize;
+                        self.reward = self.reward.fract();
+                    } else {
+                        total += count;
+                    }
+
                     self.movement_timer = movement_time;
-                    self.generate(lfsr);
+                    self.generate(rng);
+
+                    // solvers can give up on a maze with Solved(0), which still needs a rerender
+                    rerender = true;
+                }
+                SolveStatus::KeyCollected => {
+                    // an opened door changes the paths the predators take
+                    self.predator_fields.clear();
+                    rerender = true;
                 }
                 SolveStatus::Rerender => rerender = true,
                 SolveStatus::None => {}
             }
+
+            if !matches!(status, SolveStatus::Solved(_)) {
+                self.hunt(movement_time, rng);
+            }
+
+            if (self.modifiers & SHIFTING_MODIFIER) != 0 {
+                self.shift_timer += movement_time;
+
+                if self.shift_timer >= SHIFT_TIME {
+                    self.shift_timer -= SHIFT_TIME;
+
+                    for _ in 0..(S / 4).max(1) {
+                        self.maze.shift(rng);
+                    }
+
+                    self.solver.maze_changed(&self.maze);
+                    self.predator_fields.clear();
+                    rerender = true;
+                }
+            }
         }
 
         self.movement_timer = dt;
@@ -104,12 +296,24 @@ where
         self.solver.set_upgrades(upgrades);
     }
 
-    fn draw_foreground(&mut self, lfsr: &mut LFSR, image: &mut Image, bx: usize, by: usize) {
+    // modifiers the solver doesn't know how to deal with are ignored
+    fn set_modifiers(&mut self, modifiers: u32) {
+        self.modifiers = modifiers & (T::supported_modifiers() | DIFFICULTY_REWARD_MODIFIER);
+    }
+
+    fn draw_foreground(&mut self, rng: &mut dyn Rng, image: &mut Image, bx: usize, by: usize) {
         let animation_cycle = (self.clock / ANIMATION_TIME).round() as usize % 2 == 0;
 
+        self.maze.draw_keys(image, bx, by);
+
         // draw "snail"
         self.solver
-            .draw(animation_cycle, self.movement_timer, lfsr, image, bx, by);
+            .draw(animation_cycle, self.movement_timer, rng, image, bx, by);
+
+        let progress = ((self.predator_timer + self.movement_timer) / GHOST_MOVEMENT_TIME).min(1.0);
+        for predator in &self.predators {
+            predator.draw(GhostStatus::Chase, animation_cycle, progress, image, bx, by);
+        }
 
         if !T::custom_goal() {
             self.maze
@@ -122,9 +326,173 @@ where
             .draw_background(T::palette()[4], T::palette()[5], image, bx, by);
     }
 
-    fn generate(&mut self, lfsr: &mut LFSR) {
-        self.maze.generate(lfsr);
-        self.solver.setup(&self.maze, lfsr);
+    fn generate(&mut self, rng: &mut dyn Rng) {
+        self.generate_from(rng.big() as u32, rng);
+    }
+
+    fn generate_from(&mut self, seed: u32, rng: &mut dyn Rng) {
+        self.seed = seed;
+        let mut maze_rng = MAZE_RNG.build(seed);
+
+        self.maze.set_torus((self.modifiers & TORUS_MODIFIER) != 0);
+        self.maze.generate(&mut *maze_rng);
+        self.shift_timer = 0.0;
+
+        if (self.modifiers & TERRAIN_MODIFIER) != 0 {
+            self.maze.generate_terrain(&mut *maze_rng);
+        }
+
+        self.measure();
+
+        if (self.modifiers & PORTAL_MODIFIER) != 0 {
+            self.maze.generate_portals(&mut *maze_rng);
+        }
+
+        if (self.modifiers & DOOR_MODIFIER) != 0 {
+            self.maze.generate_doors(&mut *maze_rng);
+        }
+
+        self.solver.setup(&self.maze, rng);
+        self.release_predators();
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    fn snapshot(&self) -> Vec<u8> {
+        self.solver.snapshot()
+    }
+
+    fn restore(&mut self, data: &[u8]) {
+        self.solver.restore(data);
+    }
+
+    fn set_program(&mut self, program: &Program) {
+        self.solver.set_program(program);
+    }
+
+    fn program_error(&self) -> Option<VmError> {
+        self.solver.program_error()
+    }
+
+    fn set_mode(&mut self, mode: u32) {
+        self.solver.set_mode(mode);
+    }
+}
+
+impl<const S: usize, T: Solver<S>> AutoMaze<S, T>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    pub fn solver(&self) -> &T {
+        &self.solver
+    }
+
+    // replaces the current maze, e.g. with a hand authored board. once it has been solved the
+    // next maze is generated as usual.
+    pub fn load(&mut self, maze: Maze<S>, rng: &mut dyn Rng) {
+        self.maze = maze;
+        self.movement_timer = 0.0;
+        self.measure();
+        self.solver.setup(&self.maze, rng);
+        self.release_predators();
+    }
+
+    // takes effect the next time a maze is generated. ignored by solvers which always need the
+    // full grid
+    pub fn set_mask(&mut self, mask: &[bool; S * S]) {
+        if T::supports_masks() {
+            self.maze.set_mask(mask);
+        }
+    }
+
+    // measured before the solver gets a chance to change the maze
+    fn measure(&mut self) {
+        if (self.modifiers & DIFFICULTY_REWARD_MODIFIER) != 0 {
+            self.difficulty = MazeMetrics::measure(&self.maze).difficulty(&self.maze);
+        }
+    }
+
+    // one ghost for every few cells of the maze's size, each starting from the cell closest to
+    // its corner which isn't too close to the snail
+    fn release_predators(&mut self) {
+        self.predators.clear();
+        self.predator_fields.clear();
+        self.hunt_time = 0.0;
+        self.predator_timer = 0.0;
+
+        if (self.modifiers & PREDATOR_MODIFIER) != 0 {
+            let cells = self.maze.reachable_cells(self.maze.start_pos);
+            let start_pos = self.maze.start_pos;
+
+            let personalities = [Personality::Blinky, Personality::Inky, Personality::Clyde];
+
+            for personality in personalities.iter().take((S / 8).clamp(1, 3)) {
+                let corner = personality.corner(S);
+                let home = cells
+                    .iter()
+                    .copied()
+                    .filter(|pos| pos.manhattan_dist(start_pos) > S / 2)
+                    .min_by_key(|pos| pos.manhattan_dist(corner));
+
+                if let Some(home) = home {
+                    self.predators.push(Ghost::new(*personality, home));
+                }
+            }
+        }
+
+        self.maze
+            .set_predators(self.predators.iter().map(|p| p.snail.pos).collect());
+    }
+
+    // moves the predators along for as long as the solver's step took, sending the snail back to
+    // the start whenever one of them catches it
+    fn hunt(&mut self, movement_time: f32, rng: &mut dyn Rng) {
+        if self.predators.is_empty() {
+            return;
+        }
+
+        self.catch(rng);
+
+        self.hunt_time += movement_time;
+        self.predator_timer += movement_time;
+
+        while self.predator_timer >= GHOST_MOVEMENT_TIME {
+            self.predator_timer -= GHOST_MOVEMENT_TIME;
+
+            let status = GhostStatus::scheduled(self.hunt_time);
+            let (snail_pos, snail_direction) = match self.solver.snail() {
+                Some(snail) => (snail.pos, snail.direction),
+                None => (self.maze.start_pos, Direction::Right),
+            };
+
+            for predator in &mut self.predators {
+                predator.step(
+                    status,
+                    rng,
+                    &self.maze,
+                    &mut self.predator_fields,
+                    snail_pos,
+                    snail_direction,
+                );
+            }
+
+            self.maze
+                .set_predators(self.predators.iter().map(|p| p.snail.pos).collect());
+            self.catch(rng);
+        }
+    }
+
+    fn catch(&mut self, rng: &mut dyn Rng) {
+        let caught = match self.solver.snail() {
+            Some(snail) => self.predators.iter().any(|p| p.catches(snail)),
+            None => false,
+        };
+
+        if caught {
+            self.solver.caught(&self.maze, rng);
+        }
     }
 }
 
@@ -133,11 +501,30 @@ pub struct Maze<const S: usize>
 where
     [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
 {
+    pub start_pos: Vec2,
     pub end_pos: Vec2,
 
     // each cell is 4 bits
     pub walls: [usize; (S * S) / CELLS_PER_IDX + 1],
     visited: [bool; S * S],
+
+    // cells outside of the mask are solid and never part of the maze
+    valid: [bool; S * S],
+
+    // all plain unless generate_terrain is called
+    terrain: [Terrain; S * S],
+
+    // keys and doors, only placed by generate_doors
+    features: [Feature; S * S],
+
+    // pairs of linked cells, only placed by generate_portals
+    portals: Vec<(Vec2, Vec2)>,
+
+    // cells with a predator on them, see PREDATOR_MODIFIER
+    predators: Vec<Vec2>,
+
+    // whether the edges of the maze wrap around to the other side
+    torus: bool,
 }
 
 impl<const S: usize> Maze<S>
@@ -146,22 +533,446 @@ where
 {
     pub fn new() -> Self {
         Maze::<S> {
+            start_pos: Vec2 { x: 0, y: 0 },
             end_pos: Vec2 { x: S - 1, y: S - 1 },
             walls: [0; _],
             visited: [false; _],
+            valid: [true; _],
+            terrain: [Terrain::Plain; _],
+            features: [Feature::None; _],
+            portals: vec![],
+            predators: vec![],
+            torus: false,
+        }
+    }
+
+    pub fn is_valid(&self, x: usize, y: usize) -> bool {
+        self.valid[y * S + x]
+    }
+
+    // takes effect the next time the maze is generated. the start is placed on the first valid
+    // cell and the goal on the last one. if the mask is split into several parts, only the
+    // largest one is kept.
+    pub fn set_mask(&mut self, mask: &[bool; S * S]) {
+        self.valid = *mask;
+    }
+
+    pub fn clear_mask(&mut self) {
+        self.valid = [true; _];
+    }
+
+    // takes effect the next time the maze is generated
+    pub fn set_torus(&mut self, torus: bool) {
+        self.torus = torus;
+    }
+
+    pub fn is_torus(&self) -> bool {
+        self.torus
+    }
+
+    pub fn get_terrain(&self, x: usize, y: usize) -> Terrain {
+        self.terrain[y * S + x]
+    }
+
+    pub fn set_terrain(&mut self, x: usize, y: usize, terrain: Terrain) {
+        self.terrain[y * S + x] = terrain;
+    }
+
+    // scatters small patches of random terrain around the maze
+    pub fn generate_terrain(&mut self, rng: &mut dyn Rng) {
+        for _ in 0..(S * S / 8) {
+            let x = rng.big() % S;
+            let y = rng.big() % S;
+
+            let terrain = match rng.big() % 4 {
+                0 => Terrain::Grass,
+                1 => Terrain::Mud,
+                2 => Terrain::Ice,
+                _ => Terrain::Slime,
+            };
+
+            self.terrain[y * S + x] = terrain;
+
+            // spread to some of the neighboring cells
+            for direction in rng.random_order() {
+                if rng.next() < 2 {
+                    continue;
+                }
+
+                match direction {
+                    0 if x < S - 1 => self.terrain[y * S + x + 1] = terrain,
+                    1 if x > 0 => self.terrain[y * S + x - 1] = terrain,
+                    2 if y > 0 => self.terrain[(y - 1) * S + x] = terrain,
+                    3 if y < S - 1 => self.terrain[(y + 1) * S + x] = terrain,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    // links a few pairs of cells which are far apart from each other. the start, the goal and
+    // dead ends are left alone, so a portal never leads somewhere the snail can only turn back from
+    pub fn generate_portals(&mut self, rng: &mut dyn Rng) {
+        let count = (S / 8).clamp(1, PORTAL_COLORS.len());
+
+        let mut candidates: Vec<Vec2> = (0..(S * S))
+            .map(|i| Vec2 { x: i % S, y: i / S })
+            .filter(|pos| {
+                self.get_cell(pos.x, pos.y).valid_directions().len() > 1
+                    && self.is_valid(pos.x, pos.y)
+                    && *pos != self.start_pos
+                    && *pos != self.end_pos
+            })
+            .collect();
+
+        for _ in 0..count {
+            if candidates.len() < 2 {
+                return;
+            }
+
+            let a = candidates.swap_remove(rng.big() % candidates.len());
+
+            // try a few times to find a twin on the other side of the maze
+            let mut b = rng.big() % candidates.len();
+            for _ in 0..8 {
+                if candidates[b].manhattan_dist(a) >= S / 2 {
+                    break;
+                }
+
+                b = rng.big() % candidates.len();
+            }
+            let b = candidates.swap_remove(b);
+
+            // keep portals from being right next to each other
+            candidates.retain(|pos| pos.manhattan_dist(a) > 1 && pos.manhattan_dist(b) > 1);
+
+            self.portals.push((a, b));
+        }
+    }
+
+    // the cell a snail stepping onto pos is moved to, if there is a portal there
+    pub fn portal_exit(&self, pos: Vec2) -> Option<Vec2> {
+        self.portals.iter().find_map(|&(a, b)| {
+            if a == pos {
+                Some(b)
+            } else if b == pos {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    }
+
+    // where a snail ends up when moving out of pos in the given direction, ignoring walls. there
+    // has to be a cell in that direction
+    pub fn next_pos(&self, pos: Vec2, direction: Direction) -> Vec2 {
+        let next = self.neighbor(pos, direction).unwrap();
+
+        self.portal_exit(next).unwrap_or(next)
+    }
+
+    pub fn set_predators(&mut self, predators: Vec<Vec2>) {
+        self.predators = predators;
+    }
+
+    // whether a predator is standing on pos, for solvers which want to stay out of their way
+    pub fn has_predator(&self, pos: Vec2) -> bool {
+        self.predators.contains(&pos)
+    }
+
+    pub fn get_feature(&self, x: usize, y: usize) -> Feature {
+        self.features[y * S + x]
+    }
+
+    // locks up to one door for every few cells of the maze's size along the solution. the key for
+    // each door is put in a dead end which can be reached using only the keys before it.
+    pub fn generate_doors(&mut self, rng: &mut dyn Rng) {
+        let count = (S / 6).clamp(1, KEY_COLORS.len());
+        let moves = self.get_solve_sequence(self.start_pos.x, self.start_pos.y, self.end_pos);
+
+        if moves.len() < 2 * (count + 1) {
+            return;
+        }
+
+        // has to be done before locking any doors, since a locked door looks like a wall
+        let dead_ends: Vec<Vec2> = (0..(S * S))
+            .map(|i| Vec2 { x: i % S, y: i / S })
+            .filter(|pos| {
+                self.get_cell(pos.x, pos.y).valid_directions().len() == 1
+                    && self.is_valid(pos.x, pos.y)
+                    && self.portal_exit(*pos).is_none()
+                    && *pos != self.start_pos
+                    && *pos != self.end_pos
+            })
+            .collect();
+
+        // split the solution into count + 1 segments, with a door somewhere in the second half
+        // of every segment but the last
+        let segment = moves.len() / (count + 1);
+        let mut doors = vec![];
+        let mut pos = self.start_pos;
+        let mut next_door = segment - 1 - rng.big() % (segment / 2);
+
+        for (i, direction) in moves.iter().enumerate() {
+            if i == next_door {
+                self.features[pos.y * S + pos.x] = Feature::Door(doors.len(), *direction);
+                self.add_wall(pos.x, pos.y, *direction);
+                doors.push((pos, *direction));
+
+                if doors.len() == count {
+                    break;
+                }
+
+                next_door = (doors.len() + 1) * segment - 1 - rng.big() % (segment / 2);
+            }
+
+            pos = self.next_pos(pos, *direction);
+        }
+
+        let mut previous = vec![false; S * S];
+
+        for (key, (door, direction)) in doors.iter().enumerate() {
+            let mut reachable = vec![false; S * S];
+            for pos in self.reachable_cells(self.start_pos) {
+                reachable[pos.y * S + pos.x] = true;
+            }
+
+            let free = |pos: &&Vec2| {
+                reachable[pos.y * S + pos.x] && self.features[pos.y * S + pos.x] == Feature::None
+            };
+
+            // prefer dead ends which only just became reachable, so that keys get spread out
+            // instead of all ending up near the start
+            let mut choices: Vec<&Vec2> = dead_ends
+                .iter()
+                .filter(free)
+                .filter(|pos| !previous[pos.y * S + pos.x])
+                .collect();
+
+            if choices.is_empty() {
+                choices = dead_ends.iter().filter(free).collect();
+            }
+
+            if !choices.is_empty() {
+                let pos = *choices[rng.big() % choices.len()];
+                self.features[pos.y * S + pos.x] = Feature::Key(key);
+            } else {
+                self.features[door.y * S + door.x] = Feature::None;
+            }
+
+            // open the door for now, so the next key can be placed behind it
+            self.remove_wall(door.x, door.y, *direction);
+            previous = reachable;
+        }
+
+        for (door, direction) in doors {
+            if let Feature::Door(_, _) = self.features[door.y * S + door.x] {
+                self.add_wall(door.x, door.y, direction);
+            }
+        }
+    }
+
+    // closes one of the open passages and opens a different wall to reconnect the two halves of
+    // the maze. locked doors count as walls and are never opened, so the cells each key opens up
+    // stay the same and every key can still be reached before its door.
+    pub fn shift(&mut self, rng: &mut dyn Rng) {
+        let mut passages = vec![];
+        for y in 0..S {
+            for x in 0..S {
+                for direction in [Direction::Right, Direction::Down] {
+                    if !self.get_cell(x, y).has_wall(direction) {
+                        passages.push((Vec2 { x, y }, direction));
+                    }
+                }
+            }
+        }
+
+        if passages.is_empty() {
+            return;
+        }
+
+        let (closed, closed_direction) = passages[rng.big() % passages.len()];
+        self.add_wall(closed.x, closed.y, closed_direction);
+
+        // there was a loop, so the maze is still connected
+        let side = self.connected(closed);
+        let other = self.neighbor(closed, closed_direction).unwrap();
+        if side[other.y * S + other.x] {
+            return;
+        }
+
+        let other_side = self.connected(other);
+        let locked = |pos: Vec2, direction: Direction| matches!(self.features[pos.y * S + pos.x], Feature::Door(_, door) if door == direction);
+
+        // any wall between the two halves other than the one that was just closed
+        let mut choices = vec![];
+        for y in 0..S {
+            for x in 0..S {
+                let pos = Vec2 { x, y };
+
+                if !side[y * S + x] {
+                    continue;
+                }
+
+                for direction in Direction::ALL {
+                    if let Some(neighbor) = self.neighbor(pos, direction) {
+                        if other_side[neighbor.y * S + neighbor.x]
+                            && !locked(pos, direction)
+                            && !locked(neighbor, direction.flip())
+                            && (pos, direction) != (closed, closed_direction)
+                        {
+                            choices.push((pos, direction));
+                        }
+                    }
+                }
+            }
         }
+
+        let (pos, direction) = if choices.is_empty() {
+            (closed, closed_direction)
+        } else {
+            choices[rng.big() % choices.len()]
+        };
+
+        self.remove_wall(pos.x, pos.y, direction);
+    }
+
+    // every cell connected to from through open passages, ignoring portals
+    fn connected(&self, from: Vec2) -> [bool; S * S] {
+        let mut seen = [false; S * S];
+        let mut queue = VecDeque::from([from]);
+        seen[from.y * S + from.x] = true;
+
+        while let Some(pos) = queue.pop_front() {
+            for direction in self.get_cell(pos.x, pos.y).valid_directions() {
+                let neighbor = match self.neighbor(pos, direction) {
+                    Some(neighbor) => neighbor,
+                    None => continue,
+                };
+
+                if !seen[neighbor.y * S + neighbor.x] {
+                    seen[neighbor.y * S + neighbor.x] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        seen
+    }
+
+    // the cell next to pos in the given direction, if it's inside the maze. on a torus every cell
+    // has a neighbor in every direction
+    pub fn neighbor(&self, pos: Vec2, direction: Direction) -> Option<Vec2> {
+        if self.torus {
+            return Some(match direction {
+                Direction::Up => Vec2 {
+                    x: pos.x,
+                    y: (pos.y + S - 1) % S,
+                },
+                Direction::Down => Vec2 {
+                    x: pos.x,
+                    y: (pos.y + 1) % S,
+                },
+                Direction::Left => Vec2 {
+                    x: (pos.x + S - 1) % S,
+                    y: pos.y,
+                },
+                Direction::Right => Vec2 {
+                    x: (pos.x + 1) % S,
+                    y: pos.y,
+                },
+            });
+        }
+
+        match direction {
+            Direction::Up if pos.y > 0 => Some(Vec2 {
+                x: pos.x,
+                y: pos.y - 1,
+            }),
+            Direction::Down if pos.y < S - 1 => Some(Vec2 {
+                x: pos.x,
+                y: pos.y + 1,
+            }),
+            Direction::Left if pos.x > 0 => Some(Vec2 {
+                x: pos.x - 1,
+                y: pos.y,
+            }),
+            Direction::Right if pos.x < S - 1 => Some(Vec2 {
+                x: pos.x + 1,
+                y: pos.y,
+            }),
+            _ => None,
+        }
+    }
+
+    // picks up a key if there is one at the given position and opens its door. returns the
+    // position and direction of the door that was opened.
+    pub fn visit(&mut self, x: usize, y: usize) -> Option<(Vec2, Direction)> {
+        let key = match self.features[y * S + x] {
+            Feature::Key(key) => key,
+            _ => return None,
+        };
+
+        self.features[y * S + x] = Feature::None;
+
+        for i in 0..(S * S) {
+            if let Feature::Door(door, direction) = self.features[i] {
+                if door == key {
+                    let pos = Vec2 { x: i % S, y: i / S };
+
+                    self.features[i] = Feature::None;
+                    self.remove_wall(pos.x, pos.y, direction);
+
+                    return Some((pos, direction));
+                }
+            }
+        }
+
+        None
+    }
+
+    // the closest key which can be reached without going through a locked door
+    pub fn nearest_key(&self, from: Vec2) -> Option<Vec2> {
+        self.reachable_cells(from)
+            .into_iter()
+            .find(|pos| matches!(self.features[pos.y * S + pos.x], Feature::Key(_)))
+    }
+
+    // every cell connected to from, in breadth first order
+    pub fn reachable_cells(&self, from: Vec2) -> Vec<Vec2> {
+        let mut seen = [false; S * S];
+        let mut cells = vec![from];
+        seen[from.y * S + from.x] = true;
+
+        let mut i = 0;
+        while i < cells.len() {
+            let pos = cells[i];
+            i += 1;
+
+            for direction in self.get_cell(pos.x, pos.y).valid_directions() {
+                let next = self.next_pos(pos, direction);
+
+                if !seen[next.y * S + next.x] {
+                    seen[next.y * S + next.x] = true;
+                    cells.push(next);
+                }
+            }
+        }
+
+        cells
     }
 
     pub fn remove_wall(&mut self, x: usize, y: usize, direction: Direction) {
         let cell = self.get_cell(x, y);
-
-        if cell.has_wall(direction)
-            && !(direction == Direction::Left && x == 0)
-            && !(direction == Direction::Up && y == 0)
-            && !(direction == Direction::Right && x == S - 1)
-            && !(direction == Direction::Down && y == S - 1)
-        {
-            self.set_wall(x, y, direction);
+        let neighbor = self.neighbor(Vec2 { x, y }, direction);
+
+        if let Some(neighbor) = neighbor {
+            if cell.has_wall(direction)
+                && self.is_valid(neighbor.x, neighbor.y)
+                && self.is_valid(x, y)
+            {
+                self.set_wall(x, y, direction);
+            }
         }
     }
 
@@ -175,12 +986,9 @@ where
 
     pub fn set_wall(&mut self, x: usize, y: usize, direction: Direction) {
         self.xor_cell(x, y, direction.to_wall());
-        match direction {
-            Direction::Up if y > 0 => self.xor_cell(x, y - 1, direction.flip().to_wall()),
-            Direction::Down if y < S - 1 => self.xor_cell(x, y + 1, direction.flip().to_wall()),
-            Direction::Left if x > 0 => self.xor_cell(x - 1, y, direction.flip().to_wall()),
-            Direction::Right if x < S - 1 => self.xor_cell(x + 1, y, direction.flip().to_wall()),
-            _ => {}
+
+        if let Some(neighbor) = self.neighbor(Vec2 { x, y }, direction) {
+            self.xor_cell(neighbor.x, neighbor.y, direction.flip().to_wall());
         }
     }
 
@@ -221,192 +1029,193 @@ where
         self.xor_cell(x, y, 1 << (3 - direction as usize));
     }
 
-    fn random_walk(&mut self, x: usize, y: usize, lfsr: &mut LFSR) {
-        let mut next = Some((x, y));
+    // the directions returned by Rng::random_order, in the order used while generating
+    fn carve_direction(direction: u16) -> Direction {
+        match direction {
+            0 => Direction::Right,
+            1 => Direction::Left,
+            2 => Direction::Up,
+            _ => Direction::Down,
+        }
+    }
+
+    // opens the wall between pos and its neighbor in the given direction while generating
+    fn carve(&mut self, pos: Vec2, direction: Direction) -> Vec2 {
+        let next = self.neighbor(pos, direction).unwrap();
+
+        self.set_cell_wall(pos.x, pos.y, direction);
+        self.set_cell_wall(next.x, next.y, direction.flip());
 
-        while let Some((x, y)) = next {
-            self.visited[y * S + x] = true;
+        next
+    }
+
+    fn random_walk(&mut self, x: usize, y: usize, rng: &mut dyn Rng) {
+        let mut next = Some(Vec2 { x, y });
+
+        while let Some(pos) = next {
+            self.visited[pos.y * S + pos.x] = true;
             next = None;
 
-            for direction in lfsr.random_order() {
-                // right
-                if direction == 0 && x < S - 1 && !self.visited[y * S + x + 1] {
-                    self.set_cell_wall(x, y, Direction::Right);
-                    self.set_cell_wall(x + 1, y, Direction::Left);
-                    next = Some((x + 1, y));
-                }
-                // left
-                else if direction == 1 && x > 0 && !self.visited[y * S + x - 1] {
-                    self.set_cell_wall(x, y, Direction::Left);
-                    self.set_cell_wall(x - 1, y, Direction::Right);
-                    next = Some((x - 1, y));
-                }
-                // up
-                else if direction == 2 && y > 0 && !self.visited[(y - 1) * S + x] {
-                    self.set_cell_wall(x, y, Direction::Up);
-                    self.set_cell_wall(x, y - 1, Direction::Down);
-                    next = Some((x, y - 1));
-                }
-                // down
-                else if direction == 3 && y < S - 1 && !self.visited[(y + 1) * S + x] {
-                    self.set_cell_wall(x, y, Direction::Down);
-                    self.set_cell_wall(x, y + 1, Direction::Up);
-                    next = Some((x, y + 1));
-                }
+            for direction in rng.random_order() {
+                let direction = Self::carve_direction(direction);
 
-                if next.is_some() {
-                    break;
+                if let Some(neighbor) = self.neighbor(pos, direction) {
+                    if !self.visited[neighbor.y * S + neighbor.x] {
+                        next = Some(self.carve(pos, direction));
+                        break;
+                    }
                 }
             }
         }
     }
 
+    // number of moves from (x, y) to every cell, or 0 if it can't be reached
     pub fn get_distances(&self, x: usize, y: usize, distances: &mut [usize; S * S]) {
-        let mut queue = VecDeque::new();
-        *distances = [0; S * S];
+        for (i, distance) in bfs(self, &[Vec2 { x, y }]).into_iter().enumerate() {
+            distances[i] = if distance == usize::MAX { 0 } else { distance };
+        }
+    }
 
-        queue.push_back((x, y));
+    // for each cell, the direction which leads along the cheapest path to source. the cost of a
+    // move is the terrain weight of the cell being moved out of, so on a maze without any terrain
+    // this is equivalent to a breadth first search. portals count as a single move.
+    pub fn get_directions(&self, source: Vec2) -> [Option<Direction>; S * S] {
+        let field = DistanceField::new(self, source);
+        let mut directions = [None; S * S];
 
-        while let Some((x, y)) = queue.pop_front() {
-            let cell = self.get_cell(x, y);
-            let distance = distances[y * S + x];
+        for (i, direction) in directions.iter_mut().enumerate() {
+            *direction = field.next_move(self, self.node(i));
+        }
 
-            if !cell.has_wall(Direction::Up) && distances[(y - 1) * S + x] == 0 {
-                queue.push_back((x, y - 1));
-                distances[(y - 1) * S + x] = distance + 1;
-            }
+        directions
+    }
 
-            if !cell.has_wall(Direction::Down) && distances[(y + 1) * S + x] == 0 {
-                queue.push_back((x, y + 1));
-                distances[(y + 1) * S + x] = distance + 1;
-            }
+    pub fn get_solve_sequence(&self, x: usize, y: usize, target: Vec2) -> Vec<Direction> {
+        a_star(self, Vec2 { x, y }, target).unwrap_or_default()
+    }
 
-            if !cell.has_wall(Direction::Left) && distances[y * S + x - 1] == 0 {
-                queue.push_back((x - 1, y));
-                distances[y * S + x - 1] = distance + 1;
-            }
+    pub fn generate(&mut self, rng: &mut dyn Rng) {
+        // set all elements in vector to 1s
+        self.walls = [!0usize; _];
+        self.terrain = [Terrain::Plain; _];
+        self.features = [Feature::None; _];
+        self.portals.clear();
 
-            if !cell.has_wall(Direction::Right) && distances[y * S + x + 1] == 0 {
-                queue.push_back((x + 1, y));
-                distances[y * S + x + 1] = distance + 1;
-            }
+        if !self.valid.contains(&true) {
+            self.clear_mask();
         }
-    }
 
-    pub fn get_directions(&self, source: Vec2) -> [Option<Direction>; S * S] {
-        let mut visited = [false; S * S];
-        let mut directions = [None; S * S];
+        self.keep_largest_region();
 
-        let mut queue = VecDeque::new();
-        queue.push_back((source.x, source.y));
+        // cells outside of the mask count as visited so the random walk never enters them
+        for i in 0..(S * S) {
+            self.visited[i] = !self.valid[i];
+        }
 
-        while let Some((x, y)) = queue.pop_front() {
-            let cell = self.get_cell(x, y);
-            if !cell.has_wall(Direction::Up) && !visited[(y - 1) * S + x] {
-                queue.push_back((x, y - 1));
-                visited[(y - 1) * S + x] = true;
-                directions[(y - 1) * S + x] = Some(Direction::Down);
+        let start = self.valid.iter().position(|valid| *valid).unwrap_or(0);
+        self.start_pos = Vec2 {
+            x: start % S,
+            y: start / S,
+        };
+
+        self.random_walk(self.start_pos.x, self.start_pos.y, rng);
+
+        // with a mask, some cells might not be next to a visited cell until a later row has been
+        // filled in, so keep going until nothing changes
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for y in 0..S {
+                for x in 0..S {
+                    if !self.visited[y * S + x] {
+                        let pos = Vec2 { x, y };
+
+                        for direction in rng.random_order() {
+                            let direction = Self::carve_direction(direction);
+
+                            if let Some(neighbor) = self.neighbor(pos, direction) {
+                                if self.is_carved(neighbor.x, neighbor.y) {
+                                    self.carve(pos, direction);
+                                    self.random_walk(x, y, rng);
+                                    changed = true;
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                }
             }
+        }
 
-            if !cell.has_wall(Direction::Down) && !visited[(y + 1) * S + x] {
-                queue.push_back((x, y + 1));
-                visited[(y + 1) * S + x] = true;
-                directions[(y + 1) * S + x] = Some(Direction::Up);
-            }
+        let mut end = self
+            .valid
+            .iter()
+            .rposition(|valid| *valid)
+            .unwrap_or(S * S - 1);
 
-            if !cell.has_wall(Direction::Left) && !visited[y * S + x - 1] {
-                queue.push_back((x - 1, y));
-                visited[y * S + x - 1] = true;
-                directions[y * S + x - 1] = Some(Direction::Right);
-            }
+        // the corners of a torus are right next to each other, so use the cell furthest from the
+        // start instead
+        if self.torus {
+            let mut distances = [0; S * S];
+            self.get_distances(self.start_pos.x, self.start_pos.y, &mut distances);
 
-            if !cell.has_wall(Direction::Right) && !visited[y * S + x + 1] {
-                queue.push_back((x + 1, y));
-                visited[y * S + x + 1] = true;
-                directions[y * S + x + 1] = Some(Direction::Left);
-            }
+            end = (0..(S * S)).max_by_key(|i| distances[*i]).unwrap_or(end);
         }
 
-        directions
+        self.end_pos = Vec2 {
+            x: end % S,
+            y: end / S,
+        };
     }
 
-    pub fn get_solve_sequence(&self, x: usize, y: usize, target: Vec2) -> Vec<Direction> {
-        let directions = self.get_directions(target);
-
-        let mut pos = Vec2 { x, y };
-        let mut moves = vec![];
+    fn is_carved(&self, x: usize, y: usize) -> bool {
+        self.visited[y * S + x] && self.valid[y * S + x]
+    }
 
-        while pos != target {
-            match directions[pos.y * S + pos.x] {
-                Some(Direction::Up) => {
-                    pos.y -= 1;
-                    moves.push(Direction::Up);
-                }
-                Some(Direction::Down) => {
-                    pos.y += 1;
-                    moves.push(Direction::Down);
-                }
-                Some(Direction::Left) => {
-                    pos.x -= 1;
-                    moves.push(Direction::Left);
-                }
-                Some(Direction::Right) => {
-                    pos.x += 1;
-                    moves.push(Direction::Right);
-                }
+    // removes every part of the mask that isn't connected to its largest part, since those could
+    // never be reached
+    fn keep_largest_region(&mut self) {
+        let mut regions = [usize::MAX; S * S];
+        let mut largest = (0, 0);
+        let mut region = 0;
 
-                None => {
-                    return vec![];
-                }
+        for i in 0..(S * S) {
+            if !self.valid[i] || regions[i] != usize::MAX {
+                continue;
             }
-        }
 
-        moves
-    }
+            let mut size = 0;
+            let mut queue = VecDeque::from([i]);
+            regions[i] = region;
 
-    pub fn generate(&mut self, lfsr: &mut LFSR) {
-        // set all elements in vector to 1s
-        self.walls = [!0usize; _];
+            while let Some(i) = queue.pop_front() {
+                size += 1;
 
-        self.visited = [false; S * S];
+                let pos = Vec2 { x: i % S, y: i / S };
 
-        self.random_walk(0, 0, lfsr);
+                for direction in Direction::ALL {
+                    let n = match self.neighbor(pos, direction) {
+                        Some(neighbor) => neighbor.y * S + neighbor.x,
+                        None => continue,
+                    };
 
-        for y in 0..S {
-            for x in 0..S {
-                if !self.visited[y * S + x] {
-                    for direction in lfsr.random_order() {
-                        // right
-                        if direction == 0 && x < S - 1 && self.visited[y * S + x + 1] {
-                            self.set_cell_wall(x, y, Direction::Right);
-                            self.set_cell_wall(x + 1, y, Direction::Left);
-                            self.random_walk(x, y, lfsr);
-                            break;
-                        }
-                        // left
-                        else if direction == 1 && x > 0 && self.visited[y * S + x - 1] {
-                            self.set_cell_wall(x, y, Direction::Left);
-                            self.set_cell_wall(x - 1, y, Direction::Right);
-                            self.random_walk(x, y, lfsr);
-                            break;
-                        }
-                        // up
-                        else if direction == 2 && y > 0 && self.visited[(y - 1) * S + x] {
-                            self.set_cell_wall(x, y, Direction::Up);
-                            self.set_cell_wall(x, y - 1, Direction::Down);
-                            self.random_walk(x, y, lfsr);
-                            break;
-                        }
-                        // down
-                        else if direction == 3 && y < S - 1 && self.visited[(y + 1) * S + x] {
-                            self.set_cell_wall(x, y, Direction::Down);
-                            self.set_cell_wall(x, y + 1, Direction::Up);
-                            self.random_walk(x, y, lfsr);
-                            break;
-                        }
+                    if self.valid[n] && regions[n] == usize::MAX {
+                        regions[n] = region;
+                        queue.push_back(n);
                     }
                 }
             }
+
+            if size > largest.1 {
+                largest = (region, size);
+            }
+
+            region += 1;
+        }
+
+        for i in 0..(S * S) {
+            self.valid[i] = regions[i] == largest.0;
         }
     }
 
@@ -421,8 +1230,17 @@ where
         for y in 0..(S * 10) {
             for x in 0..S {
                 let cell = self.get_cell(x, y / 10);
+                let bg_color = self.terrain[(y / 10) * S + x].tint(bg_color);
                 let px = ((by + y) * image.width + bx + (x * 10)) * 4;
 
+                if !self.valid[(y / 10) * S + x] {
+                    for l in (px..(px + 4 * 10)).step_by(4) {
+                        image.draw_pixel(l, fg_color);
+                    }
+
+                    continue;
+                }
+
                 // Checking the bottom wall is redundant
                 if y % 10 == 0 && cell.has_wall(Direction::Up) {
                     for l in (px..(px + 4 * 10)).step_by(4) {
@@ -442,18 +1260,82 @@ where
                 }
             }
 
-            // fill end pixel
+            // fill end pixel, unless the passage wraps around to the left edge
             let px = 4 * ((by + y) * image.width + bx + S * 10);
-            image.draw_pixel(px, fg_color);
+            let cell = self.get_cell(S - 1, y / 10);
+
+            if self.torus && y % 10 != 0 && !cell.has_wall(Direction::Right) {
+                image.draw_pixel(px, self.terrain[(y / 10) * S + S - 1].tint(bg_color));
+            } else {
+                image.draw_pixel(px, fg_color);
+            }
         }
 
         let px = 4 * ((by + S * 10) * image.width + bx);
-        for l in (px..(px + 4 * (1 + 10 * S))).step_by(4) {
-            image.draw_pixel(l, fg_color);
+        for (x, l) in (px..(px + 4 * (1 + 10 * S))).step_by(4).enumerate() {
+            // same for passages wrapping around to the top edge
+            if self.torus
+                && x % 10 != 0
+                && x < S * 10
+                && !self.get_cell(x / 10, S - 1).has_wall(Direction::Down)
+            {
+                image.draw_pixel(l, self.terrain[(S - 1) * S + x / 10].tint(bg_color));
+            } else {
+                image.draw_pixel(l, fg_color);
+            }
+        }
+
+        self.draw_portals(bg_color, image, bx, by);
+
+        // locked doors are drawn as a thick wall in the color of their key
+        for y in 0..S {
+            for x in 0..S {
+                if let Feature::Door(key, direction) = self.features[y * S + x] {
+                    let color = KEY_COLORS[key];
+                    let (cx, cy) = (bx + x * 10, by + y * 10);
+
+                    for i in 0..=10 {
+                        let (px, py, inner_x, inner_y) = match direction {
+                            Direction::Up => (cx + i, cy, cx + i, cy + 1),
+                            Direction::Down => (cx + i, cy + 10, cx + i, cy + 9),
+                            Direction::Left => (cx, cy + i, cx + 1, cy + i),
+                            Direction::Right => (cx + 10, cy + i, cx + 9, cy + i),
+                        };
+
+                        image.draw_pixel_xy(color, px, py);
+                        image.draw_pixel_xy(color, inner_x, inner_y);
+                    }
+                }
+            }
+        }
+    }
+
+    // portals are drawn as a ring in the color of their pair
+    fn draw_portals(&self, bg_color: [u8; 3], image: &mut Image, bx: usize, by: usize) {
+        for (i, (a, b)) in self.portals.iter().enumerate() {
+            for pos in [a, b] {
+                let (cx, cy) = (bx + pos.x * 10 + 5, by + pos.y * 10 + 5);
+                let bg_color = self.terrain[pos.y * S + pos.x].tint(bg_color);
+
+                image.draw_circle(PORTAL_COLORS[i], cx, cy, 4);
+                image.draw_circle(bg_color, cx, cy, 2);
+            }
+        }
+    }
+
+    fn draw_keys(&self, image: &mut Image, bx: usize, by: usize) {
+        for y in 0..S {
+            for x in 0..S {
+                if let Feature::Key(key) = self.features[y * S + x] {
+                    for (dx, dy) in KEY_PIXELS {
+                        image.draw_pixel_xy(KEY_COLORS[key], bx + x * 10 + dx, by + y * 10 + dy);
+                    }
+                }
+            }
         }
     }
 
-    fn draw_foreground(
+    pub fn draw_foreground(
         &self,
         goal_color: [u8; 3],
         animation_cycle: bool,
@@ -471,3 +1353,180 @@ where
         }
     }
 }
+
+impl<const S: usize> Graph for Maze<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    type Node = Vec2;
+    type Move = Direction;
+
+    fn node_count(&self) -> usize {
+        S * S
+    }
+
+    fn index(&self, node: Vec2) -> usize {
+        node.y * S + node.x
+    }
+
+    fn node(&self, index: usize) -> Vec2 {
+        Vec2 {
+            x: index % S,
+            y: index / S,
+        }
+    }
+
+    // the cost of a move is the terrain weight of the cell it starts from, the same as the
+    // solvers, which slow down for the step out of the cell they're standing on
+    fn edges(&self, pos: Vec2) -> Vec<(Direction, Vec2, usize)> {
+        let weight = self.terrain[pos.y * S + pos.x].weight();
+
+        self.get_cell(pos.x, pos.y)
+            .valid_directions()
+            .into_iter()
+            .map(|direction| (direction, self.next_pos(pos, direction), weight))
+            .collect()
+    }
+
+    fn reverse_edges(&self, pos: Vec2) -> Vec<(Direction, Vec2, usize)> {
+        // a snail only ends up on a portal by stepping onto its twin
+        let entrance = self.portal_exit(pos).unwrap_or(pos);
+
+        self.get_cell(entrance.x, entrance.y)
+            .valid_directions()
+            .into_iter()
+            .map(|direction| {
+                let previous = self.neighbor(entrance, direction).unwrap();
+                let weight = self.terrain[previous.y * S + previous.x].weight();
+                (direction.flip(), previous, weight)
+            })
+            .collect()
+    }
+
+    // portals and wrapping around the edges both make the manhattan distance an overestimate
+    fn heuristic(&self, from: Vec2, to: Vec2) -> usize {
+        if self.portals.is_empty() && !self.torus {
+            from.manhattan_dist(to) * Terrain::Ice.weight()
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Feature, Maze, CELLS_PER_IDX};
+    use crate::{direction::Direction, rng::Xoshiro256, utils::Vec2};
+
+    fn valid_count<const S: usize>(maze: &Maze<S>) -> usize
+    where
+        [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+    {
+        (0..(S * S)).filter(|i| maze.is_valid(i % S, i / S)).count()
+    }
+
+    #[test]
+    fn shifting_keeps_every_cell_reachable() {
+        for torus in [false, true] {
+            let mut rng = Xoshiro256::new(11);
+            let mut maze = Maze::<9>::new();
+            maze.set_torus(torus);
+            maze.generate(&mut rng);
+
+            for _ in 0..200 {
+                maze.shift(&mut rng);
+
+                assert_eq!(
+                    maze.reachable_cells(maze.start_pos).len(),
+                    valid_count(&maze)
+                );
+            }
+        }
+    }
+
+    // where key is lying, if it hasn't been picked up yet
+    fn find_key(maze: &Maze<19>, key: usize) -> Option<Vec2> {
+        (0..(19 * 19))
+            .map(|i| Vec2 {
+                x: i % 19,
+                y: i / 19,
+            })
+            .find(|pos| maze.get_feature(pos.x, pos.y) == Feature::Key(key))
+    }
+
+    // picks up every key in order, shifting the walls a few times before each one. only the
+    // doors of the keys collected so far are open, so each key has to be reachable without the
+    // ones after it. returns how many keys there were
+    fn collect_keys(maze: &mut Maze<19>, rng: &mut Xoshiro256, shifts: usize) -> usize {
+        let mut key = 0;
+
+        while let Some(pos) = find_key(maze, key) {
+            for _ in 0..shifts {
+                maze.shift(rng);
+            }
+
+            assert!(maze.reachable_cells(maze.start_pos).contains(&pos));
+            assert!(maze.visit(pos.x, pos.y).is_some());
+            key += 1;
+        }
+
+        for _ in 0..shifts {
+            maze.shift(rng);
+        }
+
+        assert!(maze.reachable_cells(maze.start_pos).contains(&maze.end_pos));
+        key
+    }
+
+    #[test]
+    fn keys_come_before_their_doors() {
+        for seed in 0..20 {
+            let mut rng = Xoshiro256::new(seed);
+            let mut maze = Maze::<19>::new();
+            maze.generate(&mut rng);
+            maze.generate_doors(&mut rng);
+
+            assert!(collect_keys(&mut maze, &mut rng, 0) > 0);
+        }
+    }
+
+    #[test]
+    fn shifting_keeps_keys_before_their_doors() {
+        for seed in 0..50 {
+            let mut rng = Xoshiro256::new(seed);
+            let mut maze = Maze::<19>::new();
+            maze.generate(&mut rng);
+            maze.generate_doors(&mut rng);
+
+            for _ in 0..50 {
+                maze.shift(&mut rng);
+            }
+
+            assert!(collect_keys(&mut maze, &mut rng, 4) > 0);
+        }
+    }
+
+    #[test]
+    fn torus_edges_wrap() {
+        let mut maze = Maze::<5>::new();
+        let corner = Vec2 { x: 0, y: 0 };
+        let far = Vec2 { x: 4, y: 4 };
+
+        assert_eq!(maze.neighbor(corner, Direction::Left), None);
+        assert_eq!(maze.neighbor(corner, Direction::Up), None);
+        assert_eq!(maze.neighbor(far, Direction::Right), None);
+        assert_eq!(maze.neighbor(far, Direction::Down), None);
+
+        maze.set_torus(true);
+
+        assert_eq!(maze.next_pos(corner, Direction::Left), Vec2 { x: 4, y: 0 });
+        assert_eq!(maze.next_pos(corner, Direction::Up), Vec2 { x: 0, y: 4 });
+        assert_eq!(maze.next_pos(far, Direction::Right), Vec2 { x: 0, y: 4 });
+        assert_eq!(maze.next_pos(far, Direction::Down), Vec2 { x: 4, y: 0 });
+
+        for direction in Direction::ALL {
+            let next = maze.next_pos(corner, direction);
+            assert_eq!(maze.next_pos(next, direction.flip()), corner);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{
+    direction::Direction,
+    maze::{Maze, CELLS_PER_IDX},
+    utils::Vec2,
+};
+
+// Numbers describing how hard a maze is to solve
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MazeMetrics {
+    // number of moves along the shortest path from start to goal
+    pub solution_length: usize,
+
+    // cells with only one opening
+    pub dead_ends: usize,
+
+    // cells with three or more openings
+    pub junctions: usize,
+
+    // average number of new paths a snail can choose between when it isn't in a dead end
+    pub branching_factor: f32,
+
+    // longest straight line of connected cells
+    pub longest_corridor: usize,
+
+    // average number of cells in each branch leaving the solution path. mazes with a high river
+    // factor have a few long, winding side passages instead of many short dead ends
+    pub river: f32,
+}
+
+impl MazeMetrics {
+    pub fn measure<const S: usize>(maze: &Maze<S>) -> MazeMetrics
+    where
+        [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+    {
+        let mut metrics = MazeMetrics::default();
+
+        let mut distances = [0; S * S];
+        maze.get_distances(maze.start_pos.x, maze.start_pos.y, &mut distances);
+        metrics.solution_length = distances[maze.end_pos.y * S + maze.end_pos.x];
+
+        let mut branches = 0;
+        let mut open_cells = 0;
+
+        for y in 0..S {
+            for x in 0..S {
+                let openings = maze.get_cell(x, y).valid_directions().len();
+
+                match openings {
+                    0 => {}
+                    1 => metrics.dead_ends += 1,
+                    2 => open_cells += 1,
+                    _ => {
+                        metrics.junctions += 1;
+                        open_cells += 1;
+                    }
+                }
+
+                if openings > 1 {
+                    branches += openings - 1;
+                }
+            }
+        }
+
+        if open_cells > 0 {
+            metrics.branching_factor = branches as f32 / open_cells as f32;
+        }
+
+        metrics.longest_corridor = longest_corridor(maze);
+        metrics.river = river(maze);
+
+        metrics
+    }
+
+    // how much longer the solution is than the manhattan distance between start and goal. a maze
+    // with a direct path from start to goal has a difficulty of 1
+    pub fn difficulty<const S: usize>(&self, maze: &Maze<S>) -> f32
+    where
+        [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+    {
+        let manhattan =
+            maze.start_pos.x.abs_diff(maze.end_pos.x) + maze.start_pos.y.abs_diff(maze.end_pos.y);
+
+        if manhattan == 0 {
+            1.0
+        } else {
+            self.solution_length.max(manhattan) as f32 / manhattan as f32
+        }
+    }
+}
+
+fn longest_corridor<const S: usize>(maze: &Maze<S>) -> usize
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    let mut longest = 1;
+
+    for i in 0..S {
+        let mut horizontal = 1;
+        let mut vertical = 1;
+
+        for j in 0..(S - 1) {
+            if maze.get_cell(j, i).has_wall(Direction::Right) {
+                horizontal = 1;
+            } else {
+                horizontal += 1;
+            }
+
+            if maze.get_cell(i, j).has_wall(Direction::Down) {
+                vertical = 1;
+            } else {
+                vertical += 1;
+            }
+
+            longest = longest.max(horizontal).max(vertical);
+        }
+    }
+
+    longest
+}
+
+fn river<const S: usize>(maze: &Maze<S>) -> f32
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    let directions = maze.get_directions(maze.end_pos);
+
+    let mut on_path = [false; S * S];
+    let mut pos = maze.start_pos;
+    on_path[pos.y * S + pos.x] = true;
+
+    while pos != maze.end_pos {
+        match directions[pos.y * S + pos.x] {
+            Some(direction) => pos = maze.next_pos(pos, direction),
+            None => break,
+        }
+
+        on_path[pos.y * S + pos.x] = true;
+    }
+
+    // every cell off the solution path belongs to a branch. a branch starts at the cell whose next
+    // step towards the goal is back onto the solution path
+    let mut branch_cells = 0;
+    let mut branches = 0;
+
+    for y in 0..S {
+        for x in 0..S {
+            if on_path[y * S + x] {
+                continue;
+            }
+
+            let next = match directions[y * S + x] {
+                Some(direction) => maze.next_pos(Vec2 { x, y }, direction),
+                None => continue,
+            };
+
+            branch_cells += 1;
+
+            if on_path[next.y * S + next.x] {
+                branches += 1;
+            }
+        }
+    }
+
+    if branches == 0 {
+        0.0
+    } else {
+        branch_cells as f32 / branches as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MazeMetrics;
+    use crate::ascii::Board;
+
+    // a perfect 4x4 maze where the solution runs along the top and down the right side, with
+    // three branches hanging off of it
+    const BOARD: &str = concat!(
+        "#########\n",
+        "#S      #\n",
+        "# ##### #\n",
+        "#   #   #\n",
+        "# ##### #\n",
+        "# #   # #\n",
+        "# ### # #\n",
+        "#   #  G#\n",
+        "#########\n",
+    );
+
+    #[test]
+    fn measure_known_board() {
+        let maze = Board::<4>::parse(BOARD).unwrap().maze;
+        let metrics = MazeMetrics::measure(&maze);
+
+        assert_eq!(
+            metrics,
+            MazeMetrics {
+                solution_length: 6,
+                dead_ends: 4,
+                junctions: 2,
+                branching_factor: 14.0 / 12.0,
+                longest_corridor: 4,
+                river: 3.0,
+            }
+        );
+
+        // the solution is as short as it could possibly be
+        assert_eq!(metrics.difficulty(&maze), 1.0);
+    }
+}
@@ -0,0 +1,349 @@
+// Shortest paths over anything that can be described as a graph of numbered nodes, like a maze,
+// a tower of mazes or the grid used by the wolfenstein game.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+pub trait Graph {
+    type Node: Copy + Eq;
+    type Move: Copy;
+
+    // nodes are numbered from 0 to node_count() - 1
+    fn node_count(&self) -> usize;
+    fn index(&self, node: Self::Node) -> usize;
+    fn node(&self, index: usize) -> Self::Node;
+
+    // every move out of node, along with where it leads and what it costs
+    fn edges(&self, node: Self::Node) -> Vec<(Self::Move, Self::Node, usize)>;
+
+    // every move into node, along with the node it's made from and what it costs. for graphs
+    // where every move can be undone this is just edges() with the moves flipped around
+    fn reverse_edges(&self, node: Self::Node) -> Vec<(Self::Move, Self::Node, usize)>;
+
+    // a lower bound on the cost of getting from one node to another, used by a_star. the default
+    // of 0 is always correct, but makes a_star no faster than dijkstra
+    fn heuristic(&self, _from: Self::Node, _to: Self::Node) -> usize {
+        0
+    }
+}
+
+// the cheapest cost of getting from source to every node, usize::MAX if it can't be reached
+pub fn dijkstra<G: Graph>(graph: &G, source: G::Node) -> Vec<usize> {
+    let mut costs = vec![usize::MAX; graph.node_count()];
+    let mut queue = BinaryHeap::new();
+
+    costs[graph.index(source)] = 0;
+    queue.push(Reverse((0, graph.index(source))));
+
+    while let Some(Reverse((cost, index))) = queue.pop() {
+        if cost > costs[index] {
+            continue;
+        }
+
+        for (_, next, weight) in graph.edges(graph.node(index)) {
+            let next = graph.index(next);
+
+            if cost + weight < costs[next] {
+                costs[next] = cost + weight;
+                queue.push(Reverse((cost + weight, next)));
+            }
+        }
+    }
+
+    costs
+}
+
+// the cheapest sequence of moves from start to goal, if there is one
+pub fn a_star<G: Graph>(graph: &G, start: G::Node, goal: G::Node) -> Option<Vec<G::Move>> {
+    let mut costs = vec![usize::MAX; graph.node_count()];
+    let mut came_from: Vec<Option<(G::Move, usize)>> = vec![None; graph.node_count()];
+    let mut queue = BinaryHeap::new();
+
+    let goal_index = graph.index(goal);
+    costs[graph.index(start)] = 0;
+    queue.push(Reverse((
+        graph.heuristic(start, goal),
+        0,
+        graph.index(start),
+    )));
+
+    while let Some(Reverse((_, cost, index))) = queue.pop() {
+        if index == goal_index {
+            let mut moves = vec![];
+            let mut index = goal_index;
+
+            while let Some((mv, previous)) = came_from[index] {
+                moves.push(mv);
+                index = previous;
+            }
+
+            moves.reverse();
+            return Some(moves);
+        }
+
+        if cost > costs[index] {
+            continue;
+        }
+
+        for (mv, next, weight) in graph.edges(graph.node(index)) {
+            let next_index = graph.index(next);
+
+            if cost + weight < costs[next_index] {
+                costs[next_index] = cost + weight;
+                came_from[next_index] = Some((mv, index));
+
+                let estimate = cost + weight + graph.heuristic(next, goal);
+                queue.push(Reverse((estimate, cost + weight, next_index)));
+            }
+        }
+    }
+
+    None
+}
+
+// the number of moves from each node to the closest of the sources, ignoring the cost of the
+// moves. usize::MAX if none of the sources can be reached
+pub fn bfs<G: Graph>(graph: &G, sources: &[G::Node]) -> Vec<usize> {
+    let mut distances = vec![usize::MAX; graph.node_count()];
+    let mut queue = VecDeque::new();
+
+    for source in sources {
+        distances[graph.index(*source)] = 0;
+        queue.push_back(graph.index(*source));
+    }
+
+    while let Some(index) = queue.pop_front() {
+        for (_, next, _) in graph.edges(graph.node(index)) {
+            let next = graph.index(next);
+
+            if distances[next] == usize::MAX {
+                distances[next] = distances[index] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    distances
+}
+
+// the cheapest way to get to target from every node. building one costs as much as a single call
+// to dijkstra, after which any number of paths to the same target can be looked up for free
+pub struct DistanceField<M> {
+    target: usize,
+    costs: Vec<usize>,
+
+    // the first move along the path, and the node it leads to
+    next: Vec<Option<(M, usize)>>,
+}
+
+impl<M: Copy> DistanceField<M> {
+    pub fn new<G: Graph<Move = M>>(graph: &G, target: G::Node) -> DistanceField<M> {
+        let mut costs = vec![usize::MAX; graph.node_count()];
+        let mut next = vec![None; graph.node_count()];
+        let mut queue = BinaryHeap::new();
+
+        let target = graph.index(target);
+        costs[target] = 0;
+        queue.push(Reverse((0, target)));
+
+        // dijkstra, but walking the moves backwards from the target
+        while let Some(Reverse((cost, index))) = queue.pop() {
+            if cost > costs[index] {
+                continue;
+            }
+
+            for (mv, previous, weight) in graph.reverse_edges(graph.node(index)) {
+                let previous = graph.index(previous);
+
+                if cost + weight < costs[previous] {
+                    costs[previous] = cost + weight;
+                    next[previous] = Some((mv, index));
+                    queue.push(Reverse((cost + weight, previous)));
+                }
+            }
+        }
+
+        DistanceField {
+            target,
+            costs,
+            next,
+        }
+    }
+
+    // usize::MAX if the target can't be reached from node
+    pub fn cost<G: Graph<Move = M>>(&self, graph: &G, node: G::Node) -> usize {
+        self.costs[graph.index(node)]
+    }
+
+    // None at the target itself, or if the target can't be reached
+    pub fn next_move<G: Graph<Move = M>>(&self, graph: &G, node: G::Node) -> Option<M> {
+        self.next[graph.index(node)].map(|(mv, _)| mv)
+    }
+
+    // every move on the way from node to the target, if it can be reached
+    pub fn path<G: Graph<Move = M>>(&self, graph: &G, node: G::Node) -> Option<Vec<M>> {
+        let mut index = graph.index(node);
+        let mut moves = vec![];
+
+        while index != self.target {
+            let (mv, next) = self.next[index]?;
+
+            moves.push(mv);
+            index = next;
+        }
+
+        Some(moves)
+    }
+}
+
+// distance fields for a graph that doesn't change, built the first time each target is asked for
+pub struct FieldCache<M> {
+    fields: HashMap<usize, DistanceField<M>>,
+}
+
+impl<M: Copy> Default for FieldCache<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Copy> FieldCache<M> {
+    pub fn new() -> FieldCache<M> {
+        FieldCache {
+            fields: HashMap::new(),
+        }
+    }
+
+    pub fn get<G: Graph<Move = M>>(&mut self, graph: &G, target: G::Node) -> &DistanceField<M> {
+        self.fields
+            .entry(graph.index(target))
+            .or_insert_with(|| DistanceField::new(graph, target))
+    }
+
+    // has to be called whenever the graph changes
+    pub fn clear(&mut self) {
+        self.fields.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{a_star, bfs, dijkstra, DistanceField, FieldCache, Graph};
+    use crate::{ascii::Board, direction::Direction, maze::Maze, utils::Vec2};
+
+    // a 4x4 maze with a loop through mud on the left and ice on the right
+    const BOARD: &str = concat!(
+        "#########\n",
+        "#S i i i#\n",
+        "# ##### #\n",
+        "#m#   #i#\n",
+        "# # ### #\n",
+        "#m   i i#\n",
+        "##### ###\n",
+        "#G      #\n",
+        "#########\n",
+    );
+
+    fn maze() -> Maze<4> {
+        Board::parse(BOARD).unwrap().maze
+    }
+
+    fn pos(x: usize, y: usize) -> Vec2 {
+        Vec2 { x, y }
+    }
+
+    // follows moves from start, checking that each one goes through an open passage. returns
+    // where the moves lead and their total cost
+    fn walk(maze: &Maze<4>, start: Vec2, moves: &[Direction]) -> (Vec2, usize) {
+        moves.iter().fold((start, 0), |(at, cost), direction| {
+            assert!(!maze.get_cell(at.x, at.y).has_wall(*direction));
+
+            let next = maze.next_pos(at, *direction);
+            (next, cost + maze.get_terrain(at.x, at.y).weight())
+        })
+    }
+
+    #[test]
+    fn dijkstra_avoids_mud() {
+        let maze = maze();
+        let costs = dijkstra(&maze, maze.start_pos);
+
+        assert_eq!(costs[maze.index(pos(0, 0))], 0);
+        assert_eq!(costs[maze.index(pos(3, 0))], 4 + 2 * 2);
+
+        // straight down through the mud is shorter, but sliding around on the ice is cheaper
+        assert_eq!(costs[maze.index(pos(1, 2))], 4 + 6 * 2);
+        assert!(costs[maze.index(pos(1, 2))] < 4 + 8 * 2);
+        assert_eq!(bfs(&maze, &[maze.start_pos])[maze.index(pos(1, 2))], 3);
+    }
+
+    #[test]
+    fn a_star_matches_dijkstra() {
+        let maze = maze();
+        let costs = dijkstra(&maze, maze.start_pos);
+
+        for y in 0..4 {
+            for x in 0..4 {
+                let moves = a_star(&maze, maze.start_pos, pos(x, y)).unwrap();
+
+                assert_eq!(
+                    walk(&maze, maze.start_pos, &moves),
+                    (pos(x, y), costs[maze.index(pos(x, y))])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn distance_field_paths() {
+        let maze = maze();
+        let mut cache = FieldCache::new();
+        let field = cache.get(&maze, maze.end_pos);
+
+        assert_eq!(field.next_move(&maze, maze.end_pos), None);
+
+        for y in 0..4 {
+            for x in 0..4 {
+                let path = field.path(&maze, pos(x, y)).unwrap();
+
+                assert_eq!(
+                    walk(&maze, pos(x, y), &path),
+                    (maze.end_pos, field.cost(&maze, pos(x, y)))
+                );
+                assert_eq!(
+                    field.cost(&maze, pos(x, y)),
+                    dijkstra(&maze, pos(x, y))[maze.index(maze.end_pos)]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn multi_source_bfs() {
+        let maze = maze();
+        let distances = bfs(&maze, &[pos(0, 0), pos(3, 3)]);
+
+        assert_eq!(distances[maze.index(pos(0, 0))], 0);
+        assert_eq!(distances[maze.index(pos(3, 3))], 0);
+        assert_eq!(distances[maze.index(pos(0, 3))], 3);
+        assert_eq!(distances[maze.index(pos(3, 0))], 3);
+    }
+
+    #[test]
+    fn unreachable() {
+        let mut maze = maze();
+        maze.add_wall(2, 2, Direction::Down);
+
+        assert_eq!(a_star(&maze, maze.start_pos, maze.end_pos), None);
+        assert_eq!(
+            dijkstra(&maze, maze.start_pos)[maze.index(maze.end_pos)],
+            usize::MAX
+        );
+
+        let field = DistanceField::new(&maze, maze.end_pos);
+        assert_eq!(field.cost(&maze, maze.start_pos), usize::MAX);
+        assert_eq!(field.path(&maze, maze.start_pos), None);
+    }
+}
@@ -0,0 +1,367 @@
+// Several different solvers racing through the same maze. Each one moves at its own pace, see
+// Solver::movement_time, and when each of them finishes is kept so they can be compared over
+// many mazes. The solvers all share one maze, so doors aren't used, since one snail picking up a
+// key would open the door for everyone.
+
+use crate::{
+    image::Image,
+    lattice::{TilableMaze, MAZE_RNG},
+    maze::{
+        Maze, ANIMATION_TIME, CELLS_PER_IDX, PORTAL_MODIFIER, SNAIL_MOVEMENT_TIME,
+        TERRAIN_MODIFIER, TORUS_MODIFIER,
+    },
+    rng::Rng,
+    snail::{DEFAULT_PALETTE, GREEN_PALETTE, INVERTED_PALETTE, RED_PALETTE},
+    solvers::{Clones, HoldLeft, RandomWalk, SolveStatus, Solver, Tremaux},
+};
+
+// the modifiers a race can have, as long as every racer supports them
+const RACE_MODIFIERS: u32 = TERRAIN_MODIFIER | PORTAL_MODIFIER | TORUS_MODIFIER;
+
+// how long a race can go on for if nobody finishes
+const TIME_LIMIT: f32 = 1000.0 * SNAIL_MOVEMENT_TIME;
+// once the winner finishes, the rest have until this many times the winner's time to finish too
+const FINISH_WINDOW: f32 = 2.0;
+
+struct Racer<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    solver: Box<dyn Solver<S>>,
+
+    // the race time the solver moves next at
+    next_move: f32,
+    finish_time: Option<f32>,
+}
+
+impl<const S: usize> Racer<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn is_racing(&self) -> bool {
+        self.finish_time.is_none()
+    }
+}
+
+// when each racer finished, in the order they were added, or None if it ran out of time
+#[derive(Clone, PartialEq, Debug)]
+pub struct RaceResult {
+    pub times: Vec<Option<f32>>,
+}
+
+impl RaceResult {
+    // racers which finished from first to last, then the ones which didn't
+    pub fn order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.times.len()).collect();
+        order.sort_by(|a, b| match (self.times[*a], self.times[*b]) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+
+        order
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        self.order()
+            .first()
+            .copied()
+            .filter(|racer| self.times[*racer].is_some())
+    }
+}
+
+pub struct Race<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    maze: Maze<S>,
+    racers: Vec<Racer<S>>,
+    // what the current maze was generated from, see TilableMaze::generate_from
+    seed: u32,
+
+    modifiers: u32,
+    // the modifiers every racer supports
+    supported_modifiers: u32,
+
+    // time since start for animations, and time since the current race started
+    clock: f32,
+    race_time: f32,
+
+    last_result: Option<RaceResult>,
+    // races won by each racer, and races run in total
+    wins: Vec<usize>,
+    races: usize,
+}
+
+impl<const S: usize> Race<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    // a race with nobody in it yet
+    pub fn empty() -> Race<S> {
+        Race {
+            maze: Maze::new(),
+            racers: vec![],
+            seed: 0,
+            modifiers: 0,
+            supported_modifiers: RACE_MODIFIERS,
+            clock: 0.0,
+            race_time: 0.0,
+            last_result: None,
+            wins: vec![],
+            races: 0,
+        }
+    }
+
+    // racers should all be added before the first maze is generated
+    pub fn add_racer<T: Solver<S> + 'static>(&mut self, palette: [[u8; 3]; 6]) {
+        let mut solver = T::new();
+        solver.set_palette(palette);
+
+        self.racers.push(Racer {
+            solver: Box::new(solver),
+            next_move: 0.0,
+            finish_time: None,
+        });
+        self.wins.push(0);
+        self.supported_modifiers &= T::supported_modifiers();
+    }
+
+    pub fn last_result(&self) -> Option<&RaceResult> {
+        self.last_result.as_ref()
+    }
+
+    pub fn wins(&self) -> &[usize] {
+        &self.wins
+    }
+
+    pub fn races(&self) -> usize {
+        self.races
+    }
+
+    // the fraction of the races so far each racer has won
+    pub fn win_rates(&self) -> Vec<f32> {
+        self.wins
+            .iter()
+            .map(|wins| *wins as f32 / self.races.max(1) as f32)
+            .collect()
+    }
+
+    fn start(&mut self, rng: &mut dyn Rng) {
+        self.start_from(rng.big() as u32, rng);
+    }
+
+    fn start_from(&mut self, seed: u32, rng: &mut dyn Rng) {
+        let modifiers = self.modifiers & self.supported_modifiers;
+        let mut maze_rng = MAZE_RNG.build(seed);
+        self.seed = seed;
+
+        self.maze.set_torus((modifiers & TORUS_MODIFIER) != 0);
+        self.maze.generate(&mut *maze_rng);
+
+        if (modifiers & TERRAIN_MODIFIER) != 0 {
+            self.maze.generate_terrain(&mut *maze_rng);
+        }
+
+        if (modifiers & PORTAL_MODIFIER) != 0 {
+            self.maze.generate_portals(&mut *maze_rng);
+        }
+
+        self.race_time = 0.0;
+
+        for racer in &mut self.racers {
+            racer.solver.setup(&self.maze, rng);
+            racer.next_move = racer.solver.movement_time();
+            racer.finish_time = None;
+        }
+    }
+
+    // when the race ends if nobody else finishes
+    fn deadline(&self) -> f32 {
+        let winning_time = self
+            .racers
+            .iter()
+            .filter_map(|racer| racer.finish_time)
+            .reduce(f32::min);
+
+        match winning_time {
+            Some(time) => (time * FINISH_WINDOW).min(TIME_LIMIT),
+            None => TIME_LIMIT,
+        }
+    }
+
+    fn finish(&mut self) {
+        let result = RaceResult {
+            times: self.racers.iter().map(|racer| racer.finish_time).collect(),
+        };
+
+        if let Some(winner) = result.winner() {
+            self.wins[winner] += 1;
+        }
+
+        self.races += 1;
+        self.last_result = Some(result);
+    }
+}
+
+impl<const S: usize> TilableMaze for Race<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    const SIZE: usize = S;
+    const SHAREABLE: bool = true;
+
+    // the four original snails
+    fn new() -> Race<S> {
+        let mut race = Race::empty();
+        race.add_racer::<HoldLeft<S>>(DEFAULT_PALETTE);
+        race.add_racer::<Tremaux<S>>(INVERTED_PALETTE);
+        race.add_racer::<RandomWalk<S>>(GREEN_PALETTE);
+        race.add_racer::<Clones<S>>(RED_PALETTE);
+
+        race
+    }
+
+    // the racers always move in the order they're due to, no matter how much time passes at once
+    fn tick(&mut self, dt: f32, rng: &mut dyn Rng) -> SolveStatus {
+        self.clock += dt;
+
+        if self.racers.is_empty() {
+            return SolveStatus::None;
+        }
+
+        let mut end = self.race_time + dt;
+        let mut total = 0;
+        let mut rerender = false;
+
+        loop {
+            let deadline = self.deadline();
+            let next = (0..self.racers.len())
+                .filter(|i| self.racers[*i].is_racing())
+                .min_by(|a, b| {
+                    let (a, b) = (self.racers[*a].next_move, self.racers[*b].next_move);
+                    a.total_cmp(&b)
+                });
+
+            match next {
+                Some(i) if self.racers[i].next_move <= end.min(deadline) => {
+                    let racer = &mut self.racers[i];
+                    self.race_time = racer.next_move;
+
+                    match racer.solver.step(&mut self.maze, rng) {
+                        SolveStatus::Solved(_) => {
+                            racer.finish_time = Some(self.race_time);
+
+                            // the first snail to finish solves the maze
+                            if self.racers.iter().filter(|r| !r.is_racing()).count() == 1 {
+                                total += 1;
+                            }
+                        }
+                        SolveStatus::Rerender | SolveStatus::KeyCollected => rerender = true,
+                        SolveStatus::None => {}
+                    }
+
+                    let racer = &mut self.racers[i];
+                    racer.next_move += racer.solver.movement_time();
+                }
+                // everyone has finished, or the time's up
+                _ if next.is_none() || deadline <= end => {
+                    // the next race starts from when this one ended
+                    end -= if next.is_none() {
+                        self.race_time
+                    } else {
+                        deadline
+                    };
+
+                    self.finish();
+                    self.start(rng);
+                    rerender = true;
+                }
+                _ => break,
+            }
+        }
+
+        self.race_time = end;
+
+        match (total, rerender) {
+            (0, true) => SolveStatus::Rerender,
+            (0, false) => SolveStatus::None,
+            (num, _) => SolveStatus::Solved(num),
+        }
+    }
+
+    // it wouldn't be a fair race with upgrades
+    fn set_upgrades(&mut self, _upgrades: u32) {}
+
+    // takes effect from the next race
+    fn set_modifiers(&mut self, modifiers: u32) {
+        self.modifiers = modifiers;
+    }
+
+    fn draw_foreground(&mut self, rng: &mut dyn Rng, image: &mut Image, bx: usize, by: usize) {
+        let animation_cycle = (self.clock / ANIMATION_TIME).round() as usize % 2 == 0;
+
+        for racer in self.racers.iter_mut().filter(|racer| racer.is_racing()) {
+            let movement_time = racer.solver.movement_time();
+            let movement_timer = (movement_time - (racer.next_move - self.race_time)).max(0.0);
+
+            racer
+                .solver
+                .draw(animation_cycle, movement_timer, rng, image, bx, by);
+        }
+
+        self.maze
+            .draw_foreground(DEFAULT_PALETTE[0], animation_cycle, image, bx, by);
+    }
+
+    fn draw_background(&mut self, image: &mut Image, bx: usize, by: usize) {
+        self.maze
+            .draw_background(DEFAULT_PALETTE[4], DEFAULT_PALETTE[5], image, bx, by);
+    }
+
+    fn generate(&mut self, rng: &mut dyn Rng) {
+        self.start(rng);
+    }
+
+    fn generate_from(&mut self, seed: u32, rng: &mut dyn Rng) {
+        self.start_from(seed, rng);
+    }
+
+    fn seed(&self) -> u32 {
+        self.seed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Race;
+    use crate::{lattice::TilableMaze, maze::SNAIL_MOVEMENT_TIME, rng::Xoshiro256};
+
+    fn race(ticks: usize, dt: f32) -> Race<11> {
+        let mut rng = Xoshiro256::new(0xBEEF);
+        let mut race = Race::new();
+        race.generate(&mut rng);
+
+        for _ in 0..ticks {
+            race.tick(dt, &mut rng);
+        }
+
+        race
+    }
+
+    #[test]
+    fn same_results_however_time_passes() {
+        let coarse = race(1, 4000.0 * SNAIL_MOVEMENT_TIME);
+        let fine = race(40000, 0.1 * SNAIL_MOVEMENT_TIME);
+
+        assert!(coarse.races() > 0);
+        assert_eq!(coarse.races(), fine.races());
+        assert_eq!(coarse.wins(), fine.wins());
+        assert_eq!(coarse.last_result(), fine.last_result());
+
+        let result = coarse.last_result().unwrap();
+        let winner = result.winner().unwrap();
+        for time in result.times.iter().flatten() {
+            assert!(result.times[winner].unwrap() <= *time);
+        }
+    }
+}
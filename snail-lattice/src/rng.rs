@@ -0,0 +1,161 @@
+use crate::lfsr::LFSR;
+
+// the source of randomness for maze generation, solvers and the manual games. the mazes only
+// ever need small numbers, so next() hands out 2 bits at a time
+pub trait Rng {
+    // returns a random value between 0 and 4
+    fn next(&mut self) -> u16;
+
+    // returns a random usize
+    fn big(&mut self) -> usize;
+
+    // returns the numbers 0 through 3 in a random order
+    // used for exploring the cardinal directions in maze generation
+    fn random_order(&mut self) -> [u16; 4] {
+        let mut order = [0, 1, 2, 3];
+
+        for i in (1..4).rev() {
+            let next = self.next() << 2 | self.next();
+            let j = next % (i + 1);
+            order.swap(i as usize, j as usize);
+        }
+
+        order
+    }
+}
+
+// xoshiro256**, a 64 bit generator with a period of 2^256 - 1
+// https://prng.di.unimi.it/
+pub struct Xoshiro256 {
+    state: [u64; 4],
+}
+
+impl Xoshiro256 {
+    pub fn new(seed: u64) -> Xoshiro256 {
+        // the state can't be all zeroes, so it's filled in with splitmix64 like the authors
+        // recommend, which never outputs 4 zeroes in a row
+        let mut seed = seed;
+        let mut state = [0; 4];
+
+        for word in &mut state {
+            seed = seed.wrapping_add(0x9E3779B97F4A7C15);
+
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+            *word = z ^ (z >> 31);
+        }
+
+        Xoshiro256 { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        result
+    }
+}
+
+impl Rng for Xoshiro256 {
+    fn next(&mut self) -> u16 {
+        // the high bits are the best ones
+        (self.next_u64() >> 62) as u16
+    }
+
+    fn big(&mut self) -> usize {
+        self.next_u64() as usize
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RngKind {
+    Lfsr,
+    Xoshiro,
+}
+
+impl RngKind {
+    pub fn from_number(num: u32) -> Option<RngKind> {
+        match num {
+            0 => Some(RngKind::Lfsr),
+            1 => Some(RngKind::Xoshiro),
+            _ => None,
+        }
+    }
+
+    pub fn number(self) -> u32 {
+        match self {
+            RngKind::Lfsr => 0,
+            RngKind::Xoshiro => 1,
+        }
+    }
+
+    pub fn build(self, seed: u32) -> Box<dyn Rng> {
+        match self {
+            // the lfsr only has 16 bits of state, so the top half of the seed is folded into the
+            // bottom half. seeds below 2^16 give the same sequence as they always have
+            RngKind::Lfsr => Box::new(LFSR::new((seed ^ (seed >> 16)) as u16)),
+            RngKind::Xoshiro => Box::new(Xoshiro256::new(seed as u64)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Rng, RngKind, Xoshiro256};
+
+    #[test]
+    fn xoshiro_reference_output() {
+        // first outputs of the reference implementation for the state [1, 2, 3, 4]
+        let mut rng = Xoshiro256 {
+            state: [1, 2, 3, 4],
+        };
+
+        assert_eq!(rng.next_u64(), 11520);
+        assert_eq!(rng.next_u64(), 0);
+        assert_eq!(rng.next_u64(), 1509978240);
+        assert_eq!(rng.next_u64(), 1215971899390074240);
+    }
+
+    #[test]
+    fn seeds_are_deterministic() {
+        for kind in [RngKind::Lfsr, RngKind::Xoshiro] {
+            let mut a = kind.build(0xDEAD_BEEF);
+            let mut b = kind.build(0xDEAD_BEEF);
+
+            for _ in 0..100 {
+                assert_eq!(a.big(), b.big());
+            }
+        }
+    }
+
+    #[test]
+    fn wide_seeds_differ() {
+        let mut a = RngKind::Xoshiro.build(0x0001_0000);
+        let mut b = RngKind::Xoshiro.build(0x0002_0000);
+
+        assert_ne!(
+            (0..4).map(|_| a.big()).collect::<Vec<_>>(),
+            (0..4).map(|_| b.big()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn next_covers_every_value() {
+        let mut rng = Xoshiro256::new(7);
+        let mut counts = [0; 4];
+
+        for _ in 0..4000 {
+            counts[rng.next() as usize] += 1;
+        }
+
+        assert!(counts.iter().all(|&count| count > 800));
+    }
+}
@@ -0,0 +1,311 @@
+// Short codes for sharing a maze, like "0400-E080-R3ZY-WB8N". A code holds everything needed to
+// build the same maze again:
+//
+//   version    bumped whenever maze generation changes, so old codes don't build the wrong maze
+//   algorithm  what the code is for, one of the constants below or a lattice id
+//   size       the width of the maze in cells
+//   rng        which random number generator the seed is for, see RngKind
+//   seed       32 bits
+//
+// followed by a 16 bit checksum. The 10 bytes are written in Crockford's base32, which leaves out
+// I, L, O and U so codes can be read out loud and typed without mixing up letters. When parsing,
+// case and dashes don't matter, and I, L and O are read as 1, 1 and 0.
+
+use std::{convert::TryInto, fmt};
+
+use crate::{
+    maze::{Maze, CELLS_PER_IDX},
+    rng::{Rng, RngKind},
+};
+
+pub const SHARE_CODE_VERSION: u8 = 2;
+
+// the manual games use the same numbers as Game::set_game, lattices are numbered from 16
+pub const MANUAL_MAZE_ALGORITHM: u8 = 0;
+pub const WOLFENSTEIN_ALGORITHM: u8 = 3;
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const CODE_BYTES: usize = 10;
+const CODE_LENGTH: usize = CODE_BYTES * 8 / 5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CodeError {
+    WrongLength { found: usize },
+    InvalidCharacter(char),
+    BadChecksum,
+    UnsupportedVersion(u8),
+    UnknownRng(u8),
+    WrongAlgorithm { expected: u8, found: u8 },
+    WrongSize { expected: usize, found: usize },
+    // the size has to fit in a byte
+    TooBig { size: usize },
+    // the lattice doesn't have a single maze a code could stand for, see TilableMaze::SHAREABLE
+    NotShareable,
+    NoMaze { index: usize },
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            CodeError::WrongLength { found } => {
+                write!(f, "expected {CODE_LENGTH} characters, found {found}")
+            }
+            CodeError::InvalidCharacter(character) => write!(f, "unexpected {character:?}"),
+            CodeError::BadChecksum => write!(f, "the code has a typo in it"),
+            CodeError::UnsupportedVersion(version) => {
+                write!(f, "the code is from an unsupported version ({version})")
+            }
+            CodeError::UnknownRng(rng) => write!(f, "unknown random number generator {rng}"),
+            CodeError::WrongAlgorithm { expected, found } => {
+                write!(f, "the code is for algorithm {found}, not {expected}")
+            }
+            CodeError::WrongSize { expected, found } => {
+                write!(
+                    f,
+                    "the code is for a {found}x{found} maze, not {expected}x{expected}"
+                )
+            }
+            CodeError::TooBig { size } => {
+                write!(f, "a {size}x{size} maze is too big to share")
+            }
+            CodeError::NotShareable => write!(f, "these mazes can't be shared"),
+            CodeError::NoMaze { index } => write!(f, "there is no maze {index} to share"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ShareCode {
+    pub version: u8,
+    pub algorithm: u8,
+    pub size: u8,
+    pub rng: RngKind,
+    pub seed: u32,
+}
+
+impl ShareCode {
+    pub fn new(
+        algorithm: u8,
+        size: usize,
+        rng: RngKind,
+        seed: u32,
+    ) -> Result<ShareCode, CodeError> {
+        let size = size.try_into().map_err(|_| CodeError::TooBig { size })?;
+
+        Ok(ShareCode {
+            version: SHARE_CODE_VERSION,
+            algorithm,
+            size,
+            rng,
+            seed,
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<ShareCode, CodeError> {
+        let mut values = vec![];
+
+        for character in text.chars().filter(|c| *c != '-' && !c.is_whitespace()) {
+            values.push(match character.to_ascii_uppercase() {
+                'I' | 'L' => 1,
+                'O' => 0,
+                c => ALPHABET
+                    .iter()
+                    .position(|a| *a as char == c)
+                    .ok_or(CodeError::InvalidCharacter(character))?,
+            });
+        }
+
+        if values.len() != CODE_LENGTH {
+            return Err(CodeError::WrongLength {
+                found: values.len(),
+            });
+        }
+
+        let bits = values
+            .iter()
+            .fold(0u128, |bits, value| (bits << 5) | *value as u128);
+
+        let bytes = &bits.to_be_bytes()[(16 - CODE_BYTES)..];
+        if checksum(&bytes[..8]) != u16::from_be_bytes([bytes[8], bytes[9]]) {
+            return Err(CodeError::BadChecksum);
+        }
+
+        if bytes[0] != SHARE_CODE_VERSION {
+            return Err(CodeError::UnsupportedVersion(bytes[0]));
+        }
+
+        let rng = RngKind::from_number(bytes[3] as u32).ok_or(CodeError::UnknownRng(bytes[3]))?;
+
+        Ok(ShareCode {
+            version: bytes[0],
+            algorithm: bytes[1],
+            size: bytes[2],
+            rng,
+            seed: u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+        })
+    }
+
+    // checks that the code is meant for the given algorithm and maze size
+    pub fn expect(&self, algorithm: u8, size: usize) -> Result<(), CodeError> {
+        if self.algorithm != algorithm {
+            Err(CodeError::WrongAlgorithm {
+                expected: algorithm,
+                found: self.algorithm,
+            })
+        } else if self.size as usize != size {
+            Err(CodeError::WrongSize {
+                expected: size,
+                found: self.size as usize,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    // a fresh generator which replays everything that happened after the code was made
+    pub fn build_rng(&self) -> Box<dyn Rng> {
+        self.rng.build(self.seed)
+    }
+
+    pub fn generate_maze<const S: usize>(&self, algorithm: u8) -> Result<Maze<S>, CodeError>
+    where
+        [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+    {
+        self.expect(algorithm, S)?;
+
+        let mut maze = Maze::new();
+        maze.generate(&mut *self.build_rng());
+
+        Ok(maze)
+    }
+}
+
+impl fmt::Display for ShareCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = [0; CODE_BYTES];
+        bytes[0] = self.version;
+        bytes[1] = self.algorithm;
+        bytes[2] = self.size;
+        bytes[3] = self.rng.number() as u8;
+        bytes[4..8].copy_from_slice(&self.seed.to_be_bytes());
+
+        let sum = checksum(&bytes[..8]);
+        bytes[8..].copy_from_slice(&sum.to_be_bytes());
+
+        let bits = bytes
+            .iter()
+            .fold(0u128, |bits, byte| (bits << 8) | *byte as u128);
+
+        for i in 0..CODE_LENGTH {
+            if i > 0 && i % 4 == 0 {
+                write!(f, "-")?;
+            }
+
+            let value = (bits >> (5 * (CODE_LENGTH - 1 - i))) & 0b11111;
+            write!(f, "{}", ALPHABET[value as usize] as char)?;
+        }
+
+        Ok(())
+    }
+}
+
+// crc-16/ccitt, which catches any single typo and any two neighbouring characters being swapped
+fn checksum(bytes: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+
+    for byte in bytes {
+        crc ^= (*byte as u16) << 8;
+
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CodeError, ShareCode, MANUAL_MAZE_ALGORITHM, WOLFENSTEIN_ALGORITHM};
+    use crate::{ascii::Board, rng::RngKind};
+
+    #[test]
+    fn round_trip() {
+        for rng in [RngKind::Lfsr, RngKind::Xoshiro] {
+            for seed in [0, 1, 0xBEEF, 0xDEAD_BEEF, u32::MAX] {
+                let code = ShareCode::new(WOLFENSTEIN_ALGORITHM, 5, rng, seed).unwrap();
+                let text = code.to_string();
+
+                assert_eq!(text.len(), 19);
+                assert_eq!(ShareCode::parse(&text), Ok(code));
+                assert_eq!(ShareCode::parse(&text.to_lowercase()), Ok(code));
+                assert_eq!(ShareCode::parse(&text.replace('-', "")), Ok(code));
+            }
+        }
+    }
+
+    #[test]
+    fn typos() {
+        let text = ShareCode::new(MANUAL_MAZE_ALGORITHM, 7, RngKind::Xoshiro, 1234)
+            .unwrap()
+            .to_string();
+        let chars: Vec<char> = text.chars().collect();
+
+        for i in (0..chars.len()).filter(|i| chars[*i] != '-') {
+            let mut typo = chars.clone();
+            typo[i] = if typo[i] == 'Z' { 'Y' } else { 'Z' };
+
+            assert!(ShareCode::parse(&typo.iter().collect::<String>()).is_err());
+        }
+
+        assert_eq!(
+            ShareCode::parse("0440-E0G0"),
+            Err(CodeError::WrongLength { found: 8 })
+        );
+        assert_eq!(
+            ShareCode::parse("0440-E0G0-7FZG-Y0HU"),
+            Err(CodeError::InvalidCharacter('U'))
+        );
+    }
+
+    #[test]
+    fn sizes_fit_in_a_byte() {
+        assert!(ShareCode::new(MANUAL_MAZE_ALGORITHM, 255, RngKind::Xoshiro, 0).is_ok());
+        assert_eq!(
+            ShareCode::new(MANUAL_MAZE_ALGORITHM, 256, RngKind::Xoshiro, 0),
+            Err(CodeError::TooBig { size: 256 })
+        );
+    }
+
+    #[test]
+    fn same_maze() {
+        let code = ShareCode::new(MANUAL_MAZE_ALGORITHM, 7, RngKind::Xoshiro, 0xC0FFEE).unwrap();
+        let parsed = ShareCode::parse(&code.to_string()).unwrap();
+
+        let a = code.generate_maze::<7>(MANUAL_MAZE_ALGORITHM).unwrap();
+        let b = parsed.generate_maze::<7>(MANUAL_MAZE_ALGORITHM).unwrap();
+        assert_eq!(
+            Board::from_maze(a).to_string(),
+            Board::from_maze(b).to_string()
+        );
+
+        assert_eq!(
+            code.generate_maze::<9>(MANUAL_MAZE_ALGORITHM).err(),
+            Some(CodeError::WrongSize {
+                expected: 9,
+                found: 7
+            })
+        );
+        assert_eq!(
+            code.generate_maze::<7>(WOLFENSTEIN_ALGORITHM).err(),
+            Some(CodeError::WrongAlgorithm {
+                expected: WOLFENSTEIN_ALGORITHM,
+                found: MANUAL_MAZE_ALGORITHM
+            })
+        );
+    }
+}
@@ -33,6 +33,24 @@ pub const INVERTED_PALETTE: [[u8; 3]; 6] = [
     [0xee, 0xf5, 0x10], // yellow
 ];
 
+pub const GREEN_PALETTE: [[u8; 3]; 6] = [
+    [0x58, 0xf8, 0x98], // light green
+    [0x00, 0x78, 0x40], // dark green
+    [0x00, 0xb8, 0x00], // green
+    [0xff, 0xff, 0xff], // white
+    [0x06, 0x8F, 0xEF], // light blue
+    [0x11, 0x0A, 0xEF], // dark blue
+];
+
+pub const RED_PALETTE: [[u8; 3]; 6] = [
+    [0xf8, 0x78, 0x78], // pink
+    [0xa8, 0x10, 0x20], // dark red
+    [0xe4, 0x00, 0x58], // red
+    [0xff, 0xff, 0xff], // white
+    [0x06, 0x8F, 0xEF], // light blue
+    [0x11, 0x0A, 0xEF], // dark blue
+];
+
 pub const GRAYSCALE_PALETTE: [[u8; 3]; 6] = [
     [0xdf, 0xdf, 0xdf], // yellow
     [0x6c, 0x6c, 0x6c], // purple
@@ -78,7 +96,10 @@ where
         bx: usize,
         by: usize,
     ) {
-        let offset_y = if self.prev_pos.y != self.pos.y {
+        // wrapping around the edge of a torus or going through a portal happens instantly
+        let jumped = self.prev_pos.manhattan_dist(self.pos) > 1;
+
+        let offset_y = if self.prev_pos.y != self.pos.y && !jumped {
             lerpi(
                 (self.prev_pos.y * 10) as i32,
                 (self.pos.y * 10) as i32,
@@ -88,7 +109,7 @@ where
             (self.pos.y * 10) as i32
         };
 
-        let offset_x = if self.prev_pos.x != self.pos.x {
+        let offset_x = if self.prev_pos.x != self.pos.x && !jumped {
             lerpi(
                 (self.prev_pos.x * 10) as i32,
                 (self.pos.x * 10) as i32,
@@ -112,20 +133,7 @@ where
         self.prev_pos = self.pos;
 
         if !cell.has_wall(self.direction) {
-            match self.direction {
-                Direction::Up => {
-                    self.pos.y -= 1;
-                }
-                Direction::Down => {
-                    self.pos.y += 1;
-                }
-                Direction::Left => {
-                    self.pos.x -= 1;
-                }
-                Direction::Right => {
-                    self.pos.x += 1;
-                }
-            }
+            self.pos = maze.next_pos(self.pos, self.direction);
 
             true
         } else {
@@ -134,8 +142,11 @@ where
     }
 
     pub fn reset(&mut self) {
-        self.pos.x = 0;
-        self.pos.y = 0;
+        self.place(Vec2 { x: 0, y: 0 });
+    }
+
+    pub fn place(&mut self, pos: Vec2) {
+        self.pos = pos;
         self.prev_pos = self.pos;
     }
 }
@@ -0,0 +1,319 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{
+    direction::Direction,
+    image::Image,
+    maze::{
+        Maze, CELLS_PER_IDX, PORTAL_MODIFIER, SHIFTING_MODIFIER, SNAIL_MOVEMENT_TIME,
+        TERRAIN_MODIFIER, TORUS_MODIFIER,
+    },
+    pathfinding::Graph,
+    rng::Rng,
+    snail::{Snail, DEFAULT_PALETTE},
+    solvers::Solver,
+    utils::Vec2,
+};
+
+use super::SolveStatus;
+
+const OPEN_COLOR: [u8; 3] = [0x05, 0x50, 0x77];
+const CLOSED_COLOR: [u8; 3] = [0x08, 0x05, 0x77];
+const PATH_COLOR: [u8; 3] = DEFAULT_PALETTE[2];
+const SEARCH_TIME: f32 = SNAIL_MOVEMENT_TIME / 2.0;
+
+/// A* Snail Upgrades:
+/// - Overconfidence: A* Snail trusts its guesses twice as much, finding a way faster but not always the best one.
+/// - Tie Breaker:    When two cells look equally good, A* Snail looks at the one furthest from the start first.
+/// - Corridor Skip:  A* Snail looks down a whole corridor at once instead of one cell at a time.
+
+pub struct AStar<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    snail: Snail<S>,
+    upgrades: u32,
+    terrain_cost: f32,
+
+    // the search, one cell is taken off the open set every step. entries are (estimated total
+    // cost, tie breaker, cost so far, cell), with outdated ones skipped when they come up
+    open: BinaryHeap<Reverse<(usize, usize, usize, usize)>>,
+    costs: [usize; S * S],
+    came_from: [Option<(Direction, usize)>; S * S],
+    closed: [bool; S * S],
+    // the cell most recently taken off the open set, the best path so far leads to it
+    current: usize,
+
+    // once the goal is found, the moves left to get there, starting with the last one
+    path: Vec<Direction>,
+    searching: bool,
+}
+
+impl<const S: usize> AStar<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn start_search(&mut self, maze: &Maze<S>) {
+        let start = maze.index(self.snail.pos);
+
+        self.open.clear();
+        self.costs = [usize::MAX; S * S];
+        self.came_from = [None; S * S];
+        self.closed = [false; S * S];
+        self.current = start;
+        self.path.clear();
+        self.searching = true;
+
+        self.relax(maze, start, None, 0);
+    }
+
+    // records a new way of reaching cell if it's cheaper than the best one so far
+    fn relax(
+        &mut self,
+        maze: &Maze<S>,
+        cell: usize,
+        from: Option<(Direction, usize)>,
+        cost: usize,
+    ) -> bool {
+        if cost >= self.costs[cell] {
+            return false;
+        }
+
+        self.costs[cell] = cost;
+        self.came_from[cell] = from;
+
+        // the heuristic is the manhattan distance to the goal, see Graph::heuristic
+        let mut estimate = maze.heuristic(maze.node(cell), maze.end_pos);
+
+        // overconfidence
+        if (self.upgrades & 0b1) != 0 {
+            estimate *= 2;
+        }
+
+        // tie breaker
+        let tie = if (self.upgrades & 0b10) != 0 {
+            usize::MAX - cost
+        } else {
+            0
+        };
+
+        self.open.push(Reverse((cost + estimate, tie, cost, cell)));
+
+        true
+    }
+
+    // takes the most promising cell off the open set and adds its neighbors. returns false if
+    // there is nothing left to look at
+    fn expand(&mut self, maze: &Maze<S>) -> bool {
+        let (cost, cell) = loop {
+            match self.open.pop() {
+                Some(Reverse((_, _, cost, cell))) => {
+                    if !self.closed[cell] && cost <= self.costs[cell] {
+                        break (cost, cell);
+                    }
+                }
+                None => return false,
+            }
+        };
+
+        self.closed[cell] = true;
+        self.current = cell;
+
+        if cell == maze.index(maze.end_pos) {
+            self.path.clear();
+
+            let mut cell = cell;
+            while let Some((direction, previous)) = self.came_from[cell] {
+                self.path.push(direction);
+                cell = previous;
+            }
+
+            self.searching = false;
+
+            return true;
+        }
+
+        for (direction, next, weight) in maze.edges(maze.node(cell)) {
+            let mut from = cell;
+            let mut direction = direction;
+            let mut next = maze.index(next);
+            let mut cost = cost + weight;
+
+            while self.relax(maze, next, Some((direction, from)), cost) {
+                // corridor skip
+                if (self.upgrades & 0b100) == 0
+                    || self.closed[next]
+                    || next == maze.index(maze.end_pos)
+                {
+                    break;
+                }
+
+                let edges = maze.edges(maze.node(next));
+                let onward: Vec<_> = edges
+                    .iter()
+                    .filter(|(_, after, _)| maze.index(*after) != from)
+                    .collect();
+
+                if edges.len() != 2 || onward.len() != 1 {
+                    break;
+                }
+
+                // there's only one way to go from here, so there's no need to wait for it to
+                // come up in the open set
+                let (onward_direction, after, onward_weight) = *onward[0];
+                self.closed[next] = true;
+
+                from = next;
+                direction = onward_direction;
+                next = maze.index(after);
+                cost += onward_weight;
+            }
+        }
+
+        true
+    }
+
+    fn draw_cell(image: &mut Image, color: [u8; 3], pos: Vec2, bx: usize, by: usize) {
+        image.draw_rectangle_with(pos.x * 10 + 1, pos.y * 10 + 1, 9, 9, || color, bx, by);
+    }
+}
+
+impl<const S: usize> Solver<S> for AStar<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn new() -> Self {
+        AStar {
+            snail: Snail::new(),
+            upgrades: 0,
+            terrain_cost: 1.0,
+            open: BinaryHeap::new(),
+            costs: [usize::MAX; S * S],
+            came_from: [None; S * S],
+            closed: [false; S * S],
+            current: 0,
+            path: vec![],
+            searching: true,
+        }
+    }
+
+    fn set_upgrades(&mut self, upgrades: u32) {
+        self.upgrades = upgrades;
+    }
+
+    fn draw(
+        &mut self,
+        animation_cycle: bool,
+        movement_timer: f32,
+        _rng: &mut dyn Rng,
+        image: &mut Image,
+        bx: usize,
+        by: usize,
+    ) {
+        if self.searching {
+            for i in 0..(S * S) {
+                let pos = Vec2 { x: i % S, y: i / S };
+
+                if self.closed[i] {
+                    Self::draw_cell(image, CLOSED_COLOR, pos, bx, by);
+                } else if self.costs[i] != usize::MAX {
+                    Self::draw_cell(image, OPEN_COLOR, pos, bx, by);
+                }
+            }
+        }
+
+        // the best path so far while searching, and what's left of it while walking
+        let snail_cell = self.snail.pos.y * S + self.snail.pos.x;
+        let mut cell = self.current;
+        while let Some((_, previous)) = self.came_from[cell] {
+            if !self.searching && cell == snail_cell {
+                break;
+            }
+
+            image.draw_rectangle_with(
+                (cell % S) * 10 + 4,
+                (cell / S) * 10 + 4,
+                3,
+                3,
+                || PATH_COLOR,
+                bx,
+                by,
+            );
+
+            cell = previous;
+        }
+
+        self.snail.draw(
+            DEFAULT_PALETTE,
+            animation_cycle,
+            movement_timer / self.movement_time(),
+            image,
+            bx,
+            by,
+        );
+    }
+
+    fn setup(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
+        self.snail.place(maze.start_pos);
+        self.terrain_cost = 1.0;
+        self.start_search(maze);
+    }
+
+    fn step(&mut self, maze: &mut Maze<S>, _rng: &mut dyn Rng) -> SolveStatus {
+        if self.searching {
+            if !self.expand(maze) {
+                // the goal couldn't be reached, which shouldn't happen, so try again
+                self.start_search(maze);
+            }
+
+            return SolveStatus::None;
+        }
+
+        if let Some(direction) = self.path.pop() {
+            self.snail.direction = direction;
+        }
+
+        // the path was found with walls which have moved since
+        if !self.snail.move_forward(maze) {
+            self.start_search(maze);
+            return SolveStatus::None;
+        }
+
+        self.terrain_cost = maze.get_terrain(self.snail.pos.x, self.snail.pos.y).cost();
+
+        if self.snail.pos == maze.end_pos {
+            SolveStatus::Solved(1)
+        } else {
+            SolveStatus::None
+        }
+    }
+
+    fn maze_changed(&mut self, maze: &Maze<S>) {
+        // a search carries on with the new walls, since starting over every time the walls move
+        // could keep it from ever finishing. only a path which is now blocked is thrown away.
+        if self.searching {
+            return;
+        }
+
+        let mut pos = self.snail.pos;
+        for direction in self.path.iter().rev() {
+            if maze.get_cell(pos.x, pos.y).has_wall(*direction) {
+                self.start_search(maze);
+                return;
+            }
+
+            pos = maze.next_pos(pos, *direction);
+        }
+    }
+
+    fn movement_time(&self) -> f32 {
+        if self.searching {
+            SEARCH_TIME
+        } else {
+            SNAIL_MOVEMENT_TIME * self.terrain_cost
+        }
+    }
+
+    fn supported_modifiers() -> u32 {
+        TERRAIN_MODIFIER | PORTAL_MODIFIER | SHIFTING_MODIFIER | TORUS_MODIFIER
+    }
+}
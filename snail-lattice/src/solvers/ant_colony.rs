@@ -0,0 +1,306 @@
+use crate::{
+    direction::Direction,
+    image::Image,
+    maze::{
+        Maze, CELLS_PER_IDX, PORTAL_MODIFIER, SHIFTING_MODIFIER, SNAIL_MOVEMENT_TIME,
+        TORUS_MODIFIER,
+    },
+    rng::Rng,
+    snail::DEFAULT_PALETTE,
+    solvers::Solver,
+    utils::{lerpi, Vec2},
+};
+
+use super::SolveStatus;
+
+const ANT_COUNT: usize = 8;
+const ANT_MOVEMENT_TIME: f32 = SNAIL_MOVEMENT_TIME / 2.0;
+
+// the maze is solved once this many ants have found the goal. each one that does goes back to the
+// nest to tell the others, so the ones after it have a trail to follow
+const FOOD_NEEDED: usize = 5;
+
+// pheromone left on each cell of the way to the goal by an ant which finds it
+const DEPOSIT: f32 = 1.0;
+// fraction of the pheromone which is left after every step
+const EVAPORATION: f32 = 0.998;
+// how strong the trails left from the last maze are at most when a new maze is generated
+const CARRY_OVER: f32 = 0.1;
+
+const TRAIL_COLOR: [u8; 3] = [0xf8, 0x54, 0x00];
+
+struct Ant {
+    pos: Vec2,
+    prev_pos: Vec2,
+    direction: Direction,
+
+    // the way the ant came, with every detour it came back from cut out, so no cell is in it
+    // twice and it never gets longer than the maze has cells
+    trail: Vec<Vec2>,
+}
+
+impl Ant {
+    fn new(pos: Vec2) -> Ant {
+        Ant {
+            pos,
+            prev_pos: pos,
+            direction: Direction::Right,
+            trail: vec![pos],
+        }
+    }
+
+    fn move_to(&mut self, pos: Vec2) {
+        self.prev_pos = self.pos;
+        self.pos = pos;
+
+        match self.trail.iter().position(|p| *p == pos) {
+            Some(i) => self.trail.truncate(i + 1),
+            None => self.trail.push(pos),
+        }
+    }
+
+    fn draw(&self, image: &mut Image, progress: f32, bx: usize, by: usize) {
+        // ants going around the edge of a torus jump across
+        let (x, y) = if self.pos.manhattan_dist(self.prev_pos) > 1 {
+            (10 * self.pos.x as i32, 10 * self.pos.y as i32)
+        } else {
+            (
+                lerpi(
+                    10 * self.prev_pos.x as i32,
+                    10 * self.pos.x as i32,
+                    progress,
+                ),
+                lerpi(
+                    10 * self.prev_pos.y as i32,
+                    10 * self.pos.y as i32,
+                    progress,
+                ),
+            )
+        };
+
+        image.draw_rectangle_with(
+            x as usize + 4,
+            y as usize + 4,
+            3,
+            3,
+            || DEFAULT_PALETTE[0],
+            bx,
+            by,
+        );
+    }
+}
+
+/// Ant Colony Upgrades:
+/// - Bigger Colony: The Queen Ant lays more eggs, doubling the number of ants.
+/// - Strong Scent:  Ants follow pheromone trails much more closely.
+/// - Big Glands:    Ants lay twice as much pheromone when they find the goal.
+
+pub struct AntColony<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    ants: Vec<Ant>,
+    upgrades: u32,
+    food: usize,
+
+    // how strongly each cell smells of pheromone, kept between mazes
+    pheromone: [f32; S * S],
+}
+
+impl<const S: usize> AntColony<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn ant_count(&self) -> usize {
+        // bigger colony
+        if (self.upgrades & 0b1) != 0 {
+            2 * ANT_COUNT
+        } else {
+            ANT_COUNT
+        }
+    }
+
+    fn deposit(&self) -> f32 {
+        // big glands
+        if (self.upgrades & 0b100) != 0 {
+            2.0 * DEPOSIT
+        } else {
+            DEPOSIT
+        }
+    }
+
+    // how likely an ant is to move onto the cell, compared to the others it could go to
+    fn weight(&self, pos: Vec2) -> u32 {
+        let smell = 1.0 + 4.0 * self.pheromone[pos.y * S + pos.x];
+
+        // strong scent
+        let weight = if (self.upgrades & 0b10) != 0 {
+            smell * smell
+        } else {
+            smell
+        };
+
+        (16.0 * weight) as u32
+    }
+
+    // picks where the ant goes next. ants go down passages with more pheromone more often, and
+    // rarely turn around unless they're at a dead end
+    fn choose(&self, maze: &Maze<S>, ant: &Ant, rng: &mut dyn Rng) -> Direction {
+        let back = ant.direction.flip();
+        let options = maze.get_cell(ant.pos.x, ant.pos.y).valid_directions();
+
+        let weights: Vec<u32> = options
+            .iter()
+            .map(|direction| {
+                if *direction == back && options.len() > 1 {
+                    1
+                } else {
+                    self.weight(maze.next_pos(ant.pos, *direction))
+                }
+            })
+            .collect();
+
+        let mut choice = (rng.big() as u32) % weights.iter().sum::<u32>().max(1);
+        for (direction, weight) in options.iter().zip(weights) {
+            if choice < weight {
+                return *direction;
+            }
+
+            choice -= weight;
+        }
+
+        back
+    }
+}
+
+impl<const S: usize> Solver<S> for AntColony<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn new() -> Self {
+        AntColony {
+            ants: vec![],
+            upgrades: 0,
+            food: 0,
+            pheromone: [0.0; S * S],
+        }
+    }
+
+    fn set_upgrades(&mut self, upgrades: u32) {
+        self.upgrades = upgrades;
+    }
+
+    fn draw(
+        &mut self,
+        _animation_cycle: bool,
+        movement_timer: f32,
+        _rng: &mut dyn Rng,
+        image: &mut Image,
+        bx: usize,
+        by: usize,
+    ) {
+        let bg_color = DEFAULT_PALETTE[5];
+
+        for (i, pheromone) in self.pheromone.iter().enumerate() {
+            let strength = pheromone.min(1.0);
+            if strength < 0.05 {
+                continue;
+            }
+
+            let mut color = [0; 3];
+            for c in 0..3 {
+                color[c] = (bg_color[c] as f32
+                    + (TRAIL_COLOR[c] as f32 - bg_color[c] as f32) * strength * 0.8)
+                    as u8;
+            }
+
+            let (x, y) = ((i % S) * 10 + 1, (i / S) * 10 + 1);
+            image.draw_rectangle_with(x, y, 9, 9, || color, bx, by);
+        }
+
+        let progress = movement_timer / self.movement_time();
+        for ant in &self.ants {
+            ant.draw(image, progress, bx, by);
+        }
+    }
+
+    fn setup(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
+        // the old maze's trails are still a rough guess at which way the goal is, but only faintly
+        // so they don't lead the ants too far astray
+        for pheromone in self.pheromone.iter_mut() {
+            *pheromone = pheromone.min(1.0) * CARRY_OVER;
+        }
+
+        self.ants = (0..self.ant_count())
+            .map(|_| Ant::new(maze.start_pos))
+            .collect();
+        self.food = 0;
+    }
+
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
+        for pheromone in self.pheromone.iter_mut() {
+            *pheromone *= EVAPORATION;
+        }
+
+        let deposit = self.deposit();
+        for i in 0..self.ants.len() {
+            let direction = self.choose(maze, &self.ants[i], rng);
+            let ant = &mut self.ants[i];
+
+            ant.direction = direction;
+            ant.move_to(maze.next_pos(ant.pos, direction));
+
+            if ant.pos == maze.end_pos {
+                for pos in &ant.trail {
+                    self.pheromone[pos.y * S + pos.x] += deposit;
+                }
+
+                *ant = Ant::new(maze.start_pos);
+                self.food += 1;
+
+                if self.food >= FOOD_NEEDED {
+                    return SolveStatus::Solved(1);
+                }
+            }
+        }
+
+        SolveStatus::None
+    }
+
+    fn movement_time(&self) -> f32 {
+        ANT_MOVEMENT_TIME
+    }
+
+    fn supported_modifiers() -> u32 {
+        PORTAL_MODIFIER | SHIFTING_MODIFIER | TORUS_MODIFIER
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ant;
+    use crate::utils::Vec2;
+
+    #[test]
+    fn trail_cuts_out_detours() {
+        let mut ant = Ant::new(Vec2 { x: 0, y: 0 });
+
+        // wander back and forth for a long time, then go around a loop
+        for _ in 0..1000 {
+            ant.move_to(Vec2 { x: 1, y: 0 });
+            ant.move_to(Vec2 { x: 0, y: 0 });
+        }
+        for (x, y) in [(1, 0), (1, 1), (0, 1), (0, 0), (1, 0), (2, 0)] {
+            ant.move_to(Vec2 { x, y });
+        }
+
+        assert_eq!(
+            ant.trail,
+            vec![
+                Vec2 { x: 0, y: 0 },
+                Vec2 { x: 1, y: 0 },
+                Vec2 { x: 2, y: 0 }
+            ]
+        );
+    }
+}
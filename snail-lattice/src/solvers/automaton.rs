@@ -3,8 +3,8 @@ use std::mem;
 use crate::{
     direction::Direction,
     image::Image,
-    lfsr::LFSR,
     maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    rng::Rng,
     snail::{Snail, DEFAULT_PALETTE, GRAYSCALE_PALETTE, PHASE_2_PALETTE},
     solvers::Solver,
 };
@@ -68,7 +68,7 @@ where
         &mut self,
         animation_cycle: bool,
         _movement_timer: f32,
-        _lfsr: &mut LFSR,
+        _rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
@@ -91,22 +91,22 @@ where
         }
     }
 
-    fn setup(&mut self, _maze: &Maze<S>, lfsr: &mut LFSR) {
+    fn setup(&mut self, _maze: &Maze<S>, rng: &mut dyn Rng) {
         self.timeout = 0;
         self.spawned_count = 0;
 
         if self.upgrades & 0b10 != 0 {
             self.grid.fill_with(|| {
-                if lfsr.next() == 1 {
-                    ((lfsr.next() << 6) | 1) as u8
+                if rng.next() == 1 {
+                    ((rng.next() << 6) | 1) as u8
                 } else {
                     0
                 }
             });
         } else {
             self.grid.fill_with(|| {
-                if lfsr.next() == 1 {
-                    ((lfsr.next() << 6) | ((lfsr.next() & 1) + 1)) as u8
+                if rng.next() == 1 {
+                    ((rng.next() << 6) | ((rng.next() & 1) + 1)) as u8
                 } else {
                     0
                 }
@@ -114,7 +114,7 @@ where
         }
     }
 
-    fn step(&mut self, _maze: &mut Maze<S>, lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, _maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
         // conway's game of life
         if self.upgrades & 0b10 != 0 {
             for y in 0..S {
@@ -126,14 +126,14 @@ where
                         match self.neighbor_count(x, y) {
                             c if c < 2 => self.swap_grid[y * S + x] = 0,
                             c if c > 3 => self.swap_grid[y * S + x] = 0,
-                            _ => self.swap_grid[y * S + x] = ((lfsr.next() << 6) | 1) as u8,
+                            _ => self.swap_grid[y * S + x] = ((rng.next() << 6) | 1) as u8,
                         }
                     }
                     // dead cells
                     else {
                         if self.neighbor_count(x, y) == 3 {
                             self.spawned_count += 1;
-                            self.swap_grid[y * S + x] = ((lfsr.next() << 6) | 1) as u8;
+                            self.swap_grid[y * S + x] = ((rng.next() << 6) | 1) as u8;
                         } else {
                             self.swap_grid[y * S + x] = 0;
                         }
@@ -159,7 +159,7 @@ where
                     else {
                         if self.neighbor_count(x, y) == 2 {
                             self.spawned_count += 1;
-                            self.swap_grid[y * S + x] = ((lfsr.next() << 6) | 1) as u8;
+                            self.swap_grid[y * S + x] = ((rng.next() << 6) | 1) as u8;
                         } else {
                             self.swap_grid[y * S + x] = 0;
                         }
@@ -182,6 +182,10 @@ where
         true
     }
 
+    fn supports_masks() -> bool {
+        false
+    }
+
     fn palette() -> [[u8; 3]; 6] {
         PHASE_2_PALETTE
     }
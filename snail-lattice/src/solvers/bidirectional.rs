@@ -0,0 +1,315 @@
+use std::collections::VecDeque;
+
+use crate::{
+    direction::Direction,
+    image::Image,
+    maze::{
+        Maze, CELLS_PER_IDX, SHIFTING_MODIFIER, SNAIL_MOVEMENT_TIME, TERRAIN_MODIFIER,
+        TORUS_MODIFIER,
+    },
+    pathfinding::Graph,
+    rng::Rng,
+    snail::{Snail, DEFAULT_PALETTE, INVERTED_PALETTE},
+    solvers::Solver,
+    utils::Vec2,
+};
+
+use super::SolveStatus;
+
+const SEARCH_TIME: f32 = SNAIL_MOVEMENT_TIME / 2.0;
+
+// [seen, frontier] for the start snail's search and the goal snail's search
+const START_COLORS: [[u8; 3]; 2] = [[0x55, 0x2a, 0x00], [0xa8, 0x54, 0x00]];
+const GOAL_COLORS: [[u8; 3]; 2] = [[0x05, 0x45, 0x20], [0x0a, 0x8a, 0x40]];
+
+// a breadth first search out from one of the snails
+struct Search<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    frontier: VecDeque<usize>,
+    came_from: [Option<(Direction, usize)>; S * S],
+    seen: [bool; S * S],
+
+    // the moves left to get to the meeting cell once it's found, starting with the last one
+    path: Vec<Direction>,
+}
+
+impl<const S: usize> Search<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn new() -> Search<S> {
+        Search {
+            frontier: VecDeque::new(),
+            came_from: [None; S * S],
+            seen: [false; S * S],
+            path: vec![],
+        }
+    }
+
+    fn start(&mut self, from: usize) {
+        self.frontier.clear();
+        self.frontier.push_back(from);
+        self.came_from = [None; S * S];
+        self.seen = [false; S * S];
+        self.seen[from] = true;
+        self.path.clear();
+    }
+
+    // looks at the next cell in the frontier, returns a cell the other search has already seen
+    // if one is found
+    fn expand(&mut self, maze: &Maze<S>, other: &Search<S>) -> Option<usize> {
+        let cell = self.frontier.pop_front()?;
+
+        for (direction, next, _) in maze.edges(maze.node(cell)) {
+            let next = maze.index(next);
+
+            if !self.seen[next] {
+                self.seen[next] = true;
+                self.came_from[next] = Some((direction, cell));
+                self.frontier.push_back(next);
+
+                if other.seen[next] {
+                    return Some(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn follow_back(&mut self, mut cell: usize) {
+        self.path.clear();
+
+        while let Some((direction, previous)) = self.came_from[cell] {
+            self.path.push(direction);
+            cell = previous;
+        }
+    }
+
+    // whether the rest of the path can still be walked from pos
+    fn is_open(&self, maze: &Maze<S>, mut pos: Vec2) -> bool {
+        for direction in self.path.iter().rev() {
+            if maze.get_cell(pos.x, pos.y).has_wall(*direction) {
+                return false;
+            }
+
+            pos = maze.next_pos(pos, *direction);
+        }
+
+        true
+    }
+
+    fn draw(&self, colors: [[u8; 3]; 2], image: &mut Image, bx: usize, by: usize) {
+        for (i, seen) in self.seen.iter().enumerate() {
+            if *seen {
+                let color = colors[self.frontier.contains(&i) as usize];
+                let (x, y) = ((i % S) * 10 + 1, (i / S) * 10 + 1);
+
+                image.draw_rectangle_with(x, y, 9, 9, || color, bx, by);
+            }
+        }
+    }
+}
+
+/// Bidirectional Snail Upgrades:
+/// - Binoculars:    Both snails look at two cells at a time while searching.
+/// - Walkie Talkie: Once they know where to meet, the snails move 25% faster.
+/// - Periscope:     Both snails look at every cell at the edge of their search at once.
+
+pub struct Bidirectional<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    snail: Snail<S>,
+    goal_snail: Snail<S>,
+    upgrades: u32,
+    terrain_cost: f32,
+
+    search: Search<S>,
+    goal_search: Search<S>,
+    searching: bool,
+}
+
+impl<const S: usize> Bidirectional<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn start_search(&mut self, maze: &Maze<S>) {
+        self.search.start(maze.index(self.snail.pos));
+        self.goal_search.start(maze.index(self.goal_snail.pos));
+        self.searching = true;
+    }
+
+    // expands both searches, returns where they touch if they do
+    fn search_step(&mut self, maze: &Maze<S>) -> Option<usize> {
+        // periscope
+        let count = if (self.upgrades & 0b100) != 0 {
+            self.search
+                .frontier
+                .len()
+                .max(self.goal_search.frontier.len())
+        }
+        // binoculars
+        else if (self.upgrades & 0b1) != 0 {
+            2
+        } else {
+            1
+        };
+
+        for _ in 0..count {
+            if let Some(cell) = self.search.expand(maze, &self.goal_search) {
+                return Some(cell);
+            }
+
+            if let Some(cell) = self.goal_search.expand(maze, &self.search) {
+                return Some(cell);
+            }
+        }
+
+        None
+    }
+
+    fn walk(snail: &mut Snail<S>, path: &mut Vec<Direction>, maze: &Maze<S>) -> bool {
+        match path.pop() {
+            Some(direction) => {
+                snail.direction = direction;
+                snail.move_forward(maze)
+            }
+            None => {
+                snail.place(snail.pos);
+                true
+            }
+        }
+    }
+}
+
+impl<const S: usize> Solver<S> for Bidirectional<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn new() -> Self {
+        Bidirectional {
+            snail: Snail::new(),
+            goal_snail: Snail::new(),
+            upgrades: 0,
+            terrain_cost: 1.0,
+            search: Search::new(),
+            goal_search: Search::new(),
+            searching: true,
+        }
+    }
+
+    fn set_upgrades(&mut self, upgrades: u32) {
+        self.upgrades = upgrades;
+    }
+
+    fn draw(
+        &mut self,
+        animation_cycle: bool,
+        movement_timer: f32,
+        _rng: &mut dyn Rng,
+        image: &mut Image,
+        bx: usize,
+        by: usize,
+    ) {
+        if self.searching {
+            self.search.draw(START_COLORS, image, bx, by);
+            self.goal_search.draw(GOAL_COLORS, image, bx, by);
+        }
+
+        let progress = movement_timer / self.movement_time();
+
+        self.goal_snail
+            .draw(INVERTED_PALETTE, animation_cycle, progress, image, bx, by);
+        self.snail
+            .draw(DEFAULT_PALETTE, animation_cycle, progress, image, bx, by);
+    }
+
+    fn setup(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
+        self.snail.place(maze.start_pos);
+        self.snail.direction = Direction::Right;
+        self.goal_snail.place(maze.end_pos);
+        self.goal_snail.direction = Direction::Left;
+        self.terrain_cost = 1.0;
+        self.start_search(maze);
+    }
+
+    fn step(&mut self, maze: &mut Maze<S>, _rng: &mut dyn Rng) -> SolveStatus {
+        if self.searching {
+            self.snail.place(self.snail.pos);
+            self.goal_snail.place(self.goal_snail.pos);
+
+            match self.search_step(maze) {
+                Some(cell) => {
+                    self.search.follow_back(cell);
+                    self.goal_search.follow_back(cell);
+                    self.searching = false;
+                }
+                // every cell either snail can reach has been seen without the searches
+                // touching, which only happens if the walls moved in the way. try again
+                None if self.search.frontier.is_empty() && self.goal_search.frontier.is_empty() => {
+                    self.start_search(maze);
+                }
+                None => {}
+            }
+
+            return SolveStatus::None;
+        }
+
+        let moved = Self::walk(&mut self.snail, &mut self.search.path, maze);
+        let goal_moved = Self::walk(&mut self.goal_snail, &mut self.goal_search.path, maze);
+
+        // the paths were found with walls which have moved since
+        if !moved || !goal_moved {
+            self.start_search(maze);
+            return SolveStatus::None;
+        }
+
+        self.terrain_cost = maze
+            .get_terrain(self.snail.pos.x, self.snail.pos.y)
+            .cost()
+            .max(
+                maze.get_terrain(self.goal_snail.pos.x, self.goal_snail.pos.y)
+                    .cost(),
+            );
+
+        if self.snail.pos == self.goal_snail.pos {
+            SolveStatus::Solved(1)
+        } else {
+            SolveStatus::None
+        }
+    }
+
+    fn maze_changed(&mut self, maze: &Maze<S>) {
+        // a search carries on with the new walls, and only paths which are now blocked are
+        // thrown away
+        if !self.searching
+            && (!self.search.is_open(maze, self.snail.pos)
+                || !self.goal_search.is_open(maze, self.goal_snail.pos))
+        {
+            self.start_search(maze);
+        }
+    }
+
+    fn movement_time(&self) -> f32 {
+        if self.searching {
+            SEARCH_TIME
+        }
+        // walkie talkie
+        else if (self.upgrades & 0b10) != 0 {
+            SNAIL_MOVEMENT_TIME * 0.75 * self.terrain_cost
+        } else {
+            SNAIL_MOVEMENT_TIME * self.terrain_cost
+        }
+    }
+
+    fn custom_goal() -> bool {
+        true
+    }
+
+    fn supported_modifiers() -> u32 {
+        TERRAIN_MODIFIER | SHIFTING_MODIFIER | TORUS_MODIFIER
+    }
+}
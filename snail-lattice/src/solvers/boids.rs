@@ -0,0 +1,321 @@
+use crate::{
+    direction::Direction,
+    image::Image,
+    maze::{Maze, CELLS_PER_IDX, SHIFTING_MODIFIER, SNAIL_MOVEMENT_TIME, TERRAIN_MODIFIER},
+    rng::Rng,
+    snail::PHASE_2_PALETTE,
+    solvers::Solver,
+    utils::Vec2f,
+};
+
+use super::SolveStatus;
+
+const FLOCK_SIZE: usize = 6;
+const BOID_MOVEMENT_TIME: f32 = SNAIL_MOVEMENT_TIME / 4.0;
+
+// distances are in cells, speeds are in cells per step
+const MAX_SPEED: f32 = 0.15;
+// boids only pay attention to the rest of the flock within this distance
+const VIEW_RADIUS: f32 = 1.5;
+// and try to keep at least this far apart
+const SEPARATION_RADIUS: f32 = 0.4;
+// how close a boid can get to a wall
+const WALL_MARGIN: f32 = 0.2;
+
+// how strongly each rule steers a boid every step
+const SEPARATION: f32 = 0.02;
+const ALIGNMENT: f32 = 0.05;
+const COHESION: f32 = 0.005;
+const FLOW: f32 = 0.2;
+// pulls boids towards the middle of the corridor they're in, so they don't get caught on corners
+const CENTERING: f32 = 0.02;
+
+struct Boid {
+    pos: Vec2f,
+    prev_pos: Vec2f,
+    velocity: Vec2f,
+}
+
+impl Boid {
+    fn cell(&self) -> (usize, usize) {
+        (self.pos.x as usize, self.pos.y as usize)
+    }
+
+    // whichever way it's mostly going, for drawing
+    fn direction(&self) -> Direction {
+        if self.velocity.x.abs() >= self.velocity.y.abs() {
+            if self.velocity.x < 0.0 {
+                Direction::Left
+            } else {
+                Direction::Right
+            }
+        } else if self.velocity.y < 0.0 {
+            Direction::Up
+        } else {
+            Direction::Down
+        }
+    }
+}
+
+fn length(v: Vec2f) -> f32 {
+    (v.x * v.x + v.y * v.y).sqrt()
+}
+
+/// Boids Snail Upgrades:
+/// - Bigger Flock:  Four more snails join the flock.
+/// - Close Knit:    The flock sticks together three times as tightly.
+/// - Tailwind:      The flock flies 50% faster.
+
+pub struct Boids<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    upgrades: u32,
+    flock: Vec<Boid>,
+
+    // the direction which leads towards the goal from each cell
+    flow: [Option<Direction>; S * S],
+}
+
+impl<const S: usize> Boids<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn cohesion(&self) -> f32 {
+        // close knit
+        if (self.upgrades & 0b10) != 0 {
+            3.0 * COHESION
+        } else {
+            COHESION
+        }
+    }
+
+    fn max_speed(&self, maze: &Maze<S>, boid: &Boid) -> f32 {
+        let (x, y) = boid.cell();
+        let speed = MAX_SPEED / maze.get_terrain(x, y).cost();
+
+        // tailwind
+        if (self.upgrades & 0b100) != 0 {
+            speed * 1.5
+        } else {
+            speed
+        }
+    }
+
+    // the boids rules, plus following the flow field towards the goal
+    fn steer(&self, maze: &Maze<S>, i: usize) -> Vec2f {
+        let boid = &self.flock[i];
+
+        let mut separation = Vec2f::new(0.0, 0.0);
+        let mut average_velocity = Vec2f::new(0.0, 0.0);
+        let mut center = Vec2f::new(0.0, 0.0);
+        let mut neighbors = 0;
+
+        for (j, other) in self.flock.iter().enumerate() {
+            let dist2 = boid.pos.dist2(other.pos);
+            if j == i || dist2 > VIEW_RADIUS * VIEW_RADIUS {
+                continue;
+            }
+
+            if dist2 < SEPARATION_RADIUS * SEPARATION_RADIUS {
+                // pushes harder the closer the two are
+                separation += (boid.pos - other.pos) * (1.0 / dist2.max(0.01));
+            }
+
+            average_velocity += other.velocity;
+            center += other.pos;
+            neighbors += 1;
+        }
+
+        let mut steering = separation * SEPARATION;
+
+        if neighbors > 0 {
+            let neighbors = neighbors as f32;
+
+            steering += (average_velocity * (1.0 / neighbors) - boid.velocity) * ALIGNMENT;
+            steering += (center * (1.0 / neighbors) - boid.pos) * self.cohesion();
+        }
+
+        let (x, y) = boid.cell();
+        if let Some(direction) = self.flow[y * S + x] {
+            let (dx, dy) = match direction {
+                Direction::Up => (0.0, -1.0),
+                Direction::Down => (0.0, 1.0),
+                Direction::Left => (-1.0, 0.0),
+                Direction::Right => (1.0, 0.0),
+            };
+            // steers towards flying at full speed that way, which also slows it down for corners
+            let desired = Vec2f::new(dx, dy) * self.max_speed(maze, boid);
+            steering += (desired - boid.velocity) * FLOW;
+
+            // only across the way to the goal, so it doesn't hold boids back
+            let offset = Vec2f::new(x as f32 + 0.5, y as f32 + 0.5) - boid.pos;
+            steering += Vec2f::new(offset.x * dy.abs(), offset.y * dx.abs()) * CENTERING;
+        }
+
+        steering
+    }
+
+    // moves a boid along one axis, stopping it at any walls in the way
+    fn move_axis(maze: &Maze<S>, boid: &mut Boid, horizontal: bool) {
+        let (x, y) = boid.cell();
+        let cell = maze.get_cell(x, y);
+
+        let (pos, velocity, start, low, high) = if horizontal {
+            (
+                &mut boid.pos.x,
+                &mut boid.velocity.x,
+                x as f32,
+                Direction::Left,
+                Direction::Right,
+            )
+        } else {
+            (
+                &mut boid.pos.y,
+                &mut boid.velocity.y,
+                y as f32,
+                Direction::Up,
+                Direction::Down,
+            )
+        };
+
+        *pos += *velocity;
+
+        let min = if cell.has_wall(low) {
+            start + WALL_MARGIN
+        } else {
+            start - 0.5
+        };
+        let max = if cell.has_wall(high) {
+            start + 1.0 - WALL_MARGIN
+        } else {
+            start + 1.5
+        };
+
+        if *pos < min || *pos > max {
+            *pos = pos.clamp(min, max);
+            *velocity = 0.0;
+        }
+    }
+}
+
+impl<const S: usize> Solver<S> for Boids<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn new() -> Self {
+        Boids {
+            upgrades: 0,
+            flock: vec![],
+            flow: [None; S * S],
+        }
+    }
+
+    fn set_upgrades(&mut self, upgrades: u32) {
+        self.upgrades = upgrades;
+    }
+
+    fn draw(
+        &mut self,
+        animation_cycle: bool,
+        movement_timer: f32,
+        _rng: &mut dyn Rng,
+        image: &mut Image,
+        bx: usize,
+        by: usize,
+    ) {
+        let progress = movement_timer / self.movement_time();
+        let max = ((S - 1) * 10) as f32;
+
+        for boid in &self.flock {
+            let pos = boid.prev_pos + (boid.pos - boid.prev_pos) * progress;
+
+            // the sprite is centered on the boid, but can't go outside the maze
+            image.draw_snail(
+                PHASE_2_PALETTE,
+                animation_cycle,
+                boid.direction(),
+                bx + (pos.x * 10.0 - 6.0).clamp(0.0, max) as usize,
+                by + (pos.y * 10.0 - 6.0).clamp(0.0, max) as usize,
+            );
+        }
+    }
+
+    fn setup(&mut self, maze: &Maze<S>, rng: &mut dyn Rng) {
+        self.flow = maze.get_directions(maze.end_pos);
+
+        // bigger flock
+        let flock_size = if (self.upgrades & 0b1) != 0 {
+            FLOCK_SIZE + 4
+        } else {
+            FLOCK_SIZE
+        };
+
+        let start = Vec2f::new(maze.start_pos.x as f32, maze.start_pos.y as f32);
+        let spread = 1.0 - 2.0 * WALL_MARGIN;
+
+        self.flock = (0..flock_size)
+            .map(|_| {
+                let offset = Vec2f::new(
+                    WALL_MARGIN + spread * (rng.big() % 101) as f32 / 100.0,
+                    WALL_MARGIN + spread * (rng.big() % 101) as f32 / 100.0,
+                );
+
+                Boid {
+                    pos: start + offset,
+                    prev_pos: start + offset,
+                    velocity: Vec2f::new(0.0, 0.0),
+                }
+            })
+            .collect();
+    }
+
+    fn step(&mut self, maze: &mut Maze<S>, _rng: &mut dyn Rng) -> SolveStatus {
+        let steering: Vec<Vec2f> = (0..self.flock.len()).map(|i| self.steer(maze, i)).collect();
+
+        for (i, steering) in steering.into_iter().enumerate() {
+            let max_speed = self.max_speed(maze, &self.flock[i]);
+            let boid = &mut self.flock[i];
+
+            boid.velocity += steering;
+
+            let speed = length(boid.velocity);
+            if speed > max_speed {
+                boid.velocity = boid.velocity * (max_speed / speed);
+            }
+
+            boid.prev_pos = boid.pos;
+            Self::move_axis(maze, boid, true);
+            Self::move_axis(maze, boid, false);
+        }
+
+        // boids which make it to the goal land there
+        self.flock.retain(|boid| {
+            let (x, y) = boid.cell();
+            x != maze.end_pos.x || y != maze.end_pos.y
+        });
+
+        // the maze counts once, however big the flock is
+        if self.flock.is_empty() {
+            SolveStatus::Solved(1)
+        } else {
+            SolveStatus::None
+        }
+    }
+
+    fn maze_changed(&mut self, maze: &Maze<S>) {
+        self.flow = maze.get_directions(maze.end_pos);
+    }
+
+    fn movement_time(&self) -> f32 {
+        BOID_MOVEMENT_TIME
+    }
+
+    fn palette() -> [[u8; 3]; 6] {
+        PHASE_2_PALETTE
+    }
+
+    fn supported_modifiers() -> u32 {
+        TERRAIN_MODIFIER | SHIFTING_MODIFIER
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
+    direction::Direction,
     image::Image,
-    lfsr::LFSR,
-    maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    maze::{Maze, CELLS_PER_IDX, DOOR_MODIFIER, SNAIL_MOVEMENT_TIME, TORUS_MODIFIER},
+    rng::Rng,
     snail::{Snail, DEFAULT_PALETTE, GRAYSCALE_PALETTE},
     solvers::Solver,
 };
@@ -20,6 +21,13 @@ where
     inactive_snails: Vec<Snail<S>>,
     move_count: usize,
     upgrades: u32,
+
+    // cells any of the snails have been to, so that a new clone can be sent through a door if
+    // it's opened after the snails have already passed it by
+    visited: Vec<bool>,
+
+    // for the active snails, the inactive ones are always gray
+    palette: [[u8; 3]; 6],
 }
 
 impl<const S: usize> Solver<S> for Clones<S>
@@ -32,6 +40,8 @@ where
             inactive_snails: vec![],
             move_count: 0,
             upgrades: 0,
+            visited: vec![],
+            palette: DEFAULT_PALETTE,
         }
     }
 
@@ -43,7 +53,7 @@ where
         &mut self,
         animation_cycle: bool,
         mut movement_timer: f32,
-        _lfsr: &mut LFSR,
+        _rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
@@ -63,7 +73,7 @@ where
 
         for snail in self.active_snails.iter() {
             snail.draw(
-                DEFAULT_PALETTE,
+                self.palette,
                 animation_cycle,
                 movement_timer / self.movement_time(),
                 image,
@@ -73,16 +83,32 @@ where
         }
     }
 
-    fn setup(&mut self, _maze: &Maze<S>, _lfsr: &mut LFSR) {
+    fn setup(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
         self.move_count = 0;
         self.active_snails.clear();
-        self.active_snails.push(Snail::new());
+
+        let mut snail = Snail::new();
+        snail.place(maze.start_pos);
+        self.active_snails.push(snail);
         self.inactive_snails.clear();
+
+        // snails never turn around, so on a torus the start might also need one facing left
+        let start = maze.get_cell(maze.start_pos.x, maze.start_pos.y);
+        if !start.has_wall(Direction::Left) {
+            let mut snail = Snail::new();
+            snail.place(maze.start_pos);
+            snail.direction = Direction::Left;
+            self.active_snails.push(snail);
+        }
+
+        self.visited = vec![false; S * S];
+        self.visited[maze.start_pos.y * S + maze.start_pos.x] = true;
     }
 
-    fn step(&mut self, maze: &mut Maze<S>, _lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, maze: &mut Maze<S>, _rng: &mut dyn Rng) -> SolveStatus {
         self.move_count += 1;
         let mut new_snails = Vec::new();
+        let mut moved = Vec::new();
 
         let mut i = 0;
         while i < self.active_snails.len() {
@@ -118,6 +144,7 @@ where
                     return SolveStatus::Solved(1);
                 }
 
+                moved.push(snail.pos);
                 i += 1;
             }
         }
@@ -131,10 +158,32 @@ where
                 return SolveStatus::Solved(1);
             }
 
+            moved.push(snail.pos);
             self.active_snails.push(snail);
         }
 
-        SolveStatus::None
+        let mut key_collected = false;
+
+        for pos in moved {
+            self.visited[pos.y * S + pos.x] = true;
+
+            if let Some((door, direction)) = maze.visit(pos.x, pos.y) {
+                key_collected = true;
+
+                if self.visited[door.y * S + door.x] {
+                    let mut snail = Snail::new();
+                    snail.place(door);
+                    snail.direction = direction;
+                    self.active_snails.push(snail);
+                }
+            }
+        }
+
+        if key_collected {
+            SolveStatus::KeyCollected
+        } else {
+            SolveStatus::None
+        }
     }
 
     fn movement_time(&self) -> f32 {
@@ -152,4 +201,12 @@ where
 
         movement_time.max(10.0).min(SNAIL_MOVEMENT_TIME)
     }
+
+    fn set_palette(&mut self, palette: [[u8; 3]; 6]) {
+        self.palette = palette;
+    }
+
+    fn supported_modifiers() -> u32 {
+        DOOR_MODIFIER | TORUS_MODIFIER
+    }
 }
@@ -0,0 +1,449 @@
+// Solvers built out of other solvers, for trying out new combinations without writing a whole new
+// solver, e.g. `Sequence<S, RandomWalk<S>, Tremaux<S>, 200>` or `FirstOf<S, HoldLeft<S>,
+// Inverted<S>>`. Upgrades, palettes, programs and snapshots are passed on to both halves.
+
+use std::convert::TryInto;
+
+use crate::{
+    image::Image,
+    maze::{Maze, CELLS_PER_IDX, PREDATOR_MODIFIER},
+    rng::Rng,
+    snail::Snail,
+    solvers::Solver,
+    vm::{Program, VmError},
+};
+
+use super::SolveStatus;
+
+// both halves' snapshots, the first one prefixed with its length the way SnailLattice::snapshot
+// does it
+fn join_snapshots(first: Vec<u8>, second: Vec<u8>) -> Vec<u8> {
+    let mut data = (first.len() as u32).to_le_bytes().to_vec();
+    data.extend_from_slice(&first);
+    data.extend_from_slice(&second);
+
+    data
+}
+
+fn split_snapshots(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    if data.len() < 4 {
+        return None;
+    }
+
+    let (len, rest) = data.split_at(4);
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+
+    if rest.len() < len {
+        return None;
+    }
+
+    Some(rest.split_at(len))
+}
+
+// runs A for STEPS steps of each maze, and if it hasn't solved the maze by then, B takes over
+// from the start
+pub struct Sequence<const S: usize, A, B, const STEPS: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    first: A,
+    second: B,
+
+    // steps A has taken in this maze
+    steps: usize,
+}
+
+impl<const S: usize, A, B, const STEPS: usize> Sequence<S, A, B, STEPS>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn switched(&self) -> bool {
+        self.steps >= STEPS
+    }
+}
+
+impl<const S: usize, A: Solver<S>, B: Solver<S>, const STEPS: usize> Solver<S>
+    for Sequence<S, A, B, STEPS>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn new() -> Self {
+        Sequence {
+            first: A::new(),
+            second: B::new(),
+            steps: 0,
+        }
+    }
+
+    fn set_upgrades(&mut self, upgrades: u32) {
+        self.first.set_upgrades(upgrades);
+        self.second.set_upgrades(upgrades);
+    }
+
+    fn draw(
+        &mut self,
+        animation_cycle: bool,
+        movement_timer: f32,
+        rng: &mut dyn Rng,
+        image: &mut Image,
+        bx: usize,
+        by: usize,
+    ) {
+        if self.switched() {
+            self.second
+                .draw(animation_cycle, movement_timer, rng, image, bx, by);
+        } else {
+            self.first
+                .draw(animation_cycle, movement_timer, rng, image, bx, by);
+        }
+    }
+
+    fn setup(&mut self, maze: &Maze<S>, rng: &mut dyn Rng) {
+        self.steps = 0;
+
+        if self.switched() {
+            self.second.setup(maze, rng);
+        } else {
+            self.first.setup(maze, rng);
+        }
+    }
+
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
+        if self.switched() {
+            return self.second.step(maze, rng);
+        }
+
+        self.steps += 1;
+        let status = self.first.step(maze, rng);
+
+        if self.switched() && !matches!(status, SolveStatus::Solved(_)) {
+            self.second.setup(maze, rng);
+            return SolveStatus::Rerender;
+        }
+
+        status
+    }
+
+    fn maze_changed(&mut self, maze: &Maze<S>) {
+        if self.switched() {
+            self.second.maze_changed(maze);
+        } else {
+            self.first.maze_changed(maze);
+        }
+    }
+
+    fn movement_time(&self) -> f32 {
+        if self.switched() {
+            self.second.movement_time()
+        } else {
+            self.first.movement_time()
+        }
+    }
+
+    fn snail(&self) -> Option<&Snail<S>> {
+        if self.switched() {
+            self.second.snail()
+        } else {
+            self.first.snail()
+        }
+    }
+
+    // only the half which is running starts over, so once B has taken over it keeps going
+    fn caught(&mut self, maze: &Maze<S>, rng: &mut dyn Rng) {
+        if self.switched() {
+            self.second.caught(maze, rng);
+        } else {
+            self.first.caught(maze, rng);
+        }
+    }
+
+    fn snapshot(&self) -> Vec<u8> {
+        join_snapshots(self.first.snapshot(), self.second.snapshot())
+    }
+
+    fn restore(&mut self, data: &[u8]) {
+        if let Some((first, second)) = split_snapshots(data) {
+            self.first.restore(first);
+            self.second.restore(second);
+        }
+    }
+
+    fn set_program(&mut self, program: &Program) {
+        self.first.set_program(program);
+        self.second.set_program(program);
+    }
+
+    fn program_error(&self) -> Option<VmError> {
+        self.first
+            .program_error()
+            .or_else(|| self.second.program_error())
+    }
+
+    fn set_mode(&mut self, mode: u32) {
+        self.first.set_mode(mode);
+        self.second.set_mode(mode);
+    }
+
+    fn set_palette(&mut self, palette: [[u8; 3]; 6]) {
+        self.first.set_palette(palette);
+        self.second.set_palette(palette);
+    }
+
+    fn custom_goal() -> bool {
+        A::custom_goal() && B::custom_goal()
+    }
+
+    fn supported_modifiers() -> u32 {
+        A::supported_modifiers() & B::supported_modifiers()
+    }
+
+    fn supports_masks() -> bool {
+        A::supports_masks() && B::supports_masks()
+    }
+
+    fn palette() -> [[u8; 3]; 6] {
+        A::palette()
+    }
+}
+
+// runs A and B side by side in the same maze, each at its own pace, and the maze is solved as
+// soon as either of them gets to the goal. like with Race, one of them picking up a key opens the
+// door for both
+pub struct FirstOf<const S: usize, A, B>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    first: A,
+    second: B,
+
+    // time until each of them moves next, as of the last step
+    first_timer: f32,
+    second_timer: f32,
+}
+
+impl<const S: usize, A: Solver<S>, B: Solver<S>> Solver<S> for FirstOf<S, A, B>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn new() -> Self {
+        let first = A::new();
+        let second = B::new();
+
+        FirstOf {
+            first_timer: first.movement_time(),
+            second_timer: second.movement_time(),
+            first,
+            second,
+        }
+    }
+
+    fn set_upgrades(&mut self, upgrades: u32) {
+        self.first.set_upgrades(upgrades);
+        self.second.set_upgrades(upgrades);
+    }
+
+    fn draw(
+        &mut self,
+        animation_cycle: bool,
+        movement_timer: f32,
+        rng: &mut dyn Rng,
+        image: &mut Image,
+        bx: usize,
+        by: usize,
+    ) {
+        // how far along each of them is in its own step
+        let first_timer = (self.first.movement_time() - self.first_timer + movement_timer).max(0.0);
+        let second_timer =
+            (self.second.movement_time() - self.second_timer + movement_timer).max(0.0);
+
+        self.second
+            .draw(animation_cycle, second_timer, rng, image, bx, by);
+        self.first
+            .draw(animation_cycle, first_timer, rng, image, bx, by);
+    }
+
+    fn setup(&mut self, maze: &Maze<S>, rng: &mut dyn Rng) {
+        self.first.setup(maze, rng);
+        self.second.setup(maze, rng);
+
+        self.first_timer = self.first.movement_time();
+        self.second_timer = self.second.movement_time();
+    }
+
+    // only whichever of them is due moves, or both if they're due at the same time
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
+        let elapsed = self.movement_time();
+        self.first_timer -= elapsed;
+        self.second_timer -= elapsed;
+
+        let mut status = SolveStatus::None;
+
+        if self.first_timer <= 0.0 {
+            status = self.first.step(maze, rng);
+            self.first_timer += self.first.movement_time();
+
+            if let SolveStatus::Solved(count) = status {
+                return SolveStatus::Solved(count);
+            }
+        }
+
+        if self.second_timer <= 0.0 {
+            status = match (self.second.step(maze, rng), status) {
+                (SolveStatus::Solved(count), _) => return SolveStatus::Solved(count),
+                (SolveStatus::KeyCollected, _) | (_, SolveStatus::KeyCollected) => {
+                    SolveStatus::KeyCollected
+                }
+                (SolveStatus::Rerender, _) | (_, SolveStatus::Rerender) => SolveStatus::Rerender,
+                _ => SolveStatus::None,
+            };
+            self.second_timer += self.second.movement_time();
+        }
+
+        status
+    }
+
+    fn maze_changed(&mut self, maze: &Maze<S>) {
+        self.first.maze_changed(maze);
+        self.second.maze_changed(maze);
+    }
+
+    // time until the next of them is due to move
+    fn movement_time(&self) -> f32 {
+        self.first_timer.min(self.second_timer)
+    }
+
+    fn snapshot(&self) -> Vec<u8> {
+        join_snapshots(self.first.snapshot(), self.second.snapshot())
+    }
+
+    fn restore(&mut self, data: &[u8]) {
+        if let Some((first, second)) = split_snapshots(data) {
+            self.first.restore(first);
+            self.second.restore(second);
+        }
+    }
+
+    fn set_program(&mut self, program: &Program) {
+        self.first.set_program(program);
+        self.second.set_program(program);
+    }
+
+    fn program_error(&self) -> Option<VmError> {
+        self.first
+            .program_error()
+            .or_else(|| self.second.program_error())
+    }
+
+    fn set_mode(&mut self, mode: u32) {
+        self.first.set_mode(mode);
+        self.second.set_mode(mode);
+    }
+
+    fn set_palette(&mut self, palette: [[u8; 3]; 6]) {
+        self.first.set_palette(palette);
+        self.second.set_palette(palette);
+    }
+
+    fn custom_goal() -> bool {
+        A::custom_goal() && B::custom_goal()
+    }
+
+    // there's no one snail for predators to hunt
+    fn supported_modifiers() -> u32 {
+        A::supported_modifiers() & B::supported_modifiers() & !PREDATOR_MODIFIER
+    }
+
+    fn supports_masks() -> bool {
+        A::supports_masks() && B::supports_masks()
+    }
+
+    fn palette() -> [[u8; 3]; 6] {
+        A::palette()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FirstOf, Sequence};
+    use crate::{
+        lattice::TilableMaze,
+        maze::{AutoMaze, SNAIL_MOVEMENT_TIME},
+        rng::Xoshiro256,
+        solvers::{HoldLeft, Inverted, QLearning, RandomWalk, Solver, Tremaux},
+    };
+
+    // neither hand on the wall uses the rng, so every solver sees the same mazes
+    fn solves<T: Solver<11>>() -> usize {
+        let mut rng = Xoshiro256::new(0xBEEF);
+        let mut maze = AutoMaze::<11, T>::new();
+        maze.generate(&mut rng);
+
+        (0..2000)
+            .map(|_| maze.tick(SNAIL_MOVEMENT_TIME, &mut rng).get_count())
+            .sum()
+    }
+
+    #[test]
+    fn sequence_switches_after_steps() {
+        let hold_left = solves::<HoldLeft<11>>();
+        assert!(hold_left > 0);
+
+        assert_eq!(
+            solves::<Sequence<11, HoldLeft<11>, Inverted<11>, { usize::MAX }>>(),
+            hold_left
+        );
+        assert_eq!(
+            solves::<Sequence<11, Inverted<11>, HoldLeft<11>, 0>>(),
+            hold_left
+        );
+    }
+
+    #[test]
+    fn first_of_is_never_slower() {
+        let hold_left = solves::<HoldLeft<11>>();
+        let inverted = solves::<Inverted<11>>();
+        let first_of = solves::<FirstOf<11, HoldLeft<11>, Inverted<11>>>();
+
+        assert!(first_of >= hold_left.max(inverted));
+    }
+
+    #[test]
+    fn caught_after_switching() {
+        let mut rng = Xoshiro256::new(0xBEEF);
+        let mut maze = AutoMaze::<11, HoldLeft<11>>::new();
+        maze.generate(&mut rng);
+        let mut maze = maze.maze;
+
+        let mut sequence = Sequence::<11, RandomWalk<11>, Tremaux<11>, 3>::new();
+        sequence.setup(&maze, &mut rng);
+        for _ in 0..3 {
+            sequence.step(&mut maze, &mut rng);
+        }
+        assert!(sequence.switched());
+
+        sequence.caught(&maze, &mut rng);
+        assert!(sequence.switched());
+        assert_eq!(sequence.snail().unwrap().pos, maze.start_pos);
+    }
+
+    #[test]
+    fn snapshots_both_halves() {
+        let mut rng = Xoshiro256::new(0xBEEF);
+        let mut maze = AutoMaze::<11, FirstOf<11, QLearning<11>, QLearning<11>>>::new();
+        maze.generate(&mut rng);
+        for _ in 0..1000 {
+            maze.tick(SNAIL_MOVEMENT_TIME, &mut rng);
+        }
+
+        let snapshot = maze.snapshot();
+        assert!(snapshot.iter().any(|byte| *byte != 0));
+
+        let mut restored = FirstOf::<11, QLearning<11>, QLearning<11>>::new();
+        restored.restore(&snapshot);
+        assert_eq!(restored.snapshot(), snapshot);
+
+        let mut sequence = Sequence::<11, QLearning<11>, QLearning<11>, 10>::new();
+        sequence.restore(&snapshot);
+        assert_eq!(sequence.snapshot(), snapshot);
+    }
+}
@@ -0,0 +1,362 @@
+use crate::{
+    direction::Direction,
+    image::Image,
+    maze::{
+        Maze, CELLS_PER_IDX, SHIFTING_MODIFIER, SNAIL_MOVEMENT_TIME, TERRAIN_MODIFIER,
+        TORUS_MODIFIER,
+    },
+    rng::Rng,
+    snail::{Snail, DEFAULT_PALETTE},
+    solvers::Solver,
+    utils::Vec2,
+};
+
+use super::SolveStatus;
+
+// dark blue at half brightness
+const FILL_COLOR: [u8; 3] = [0x08, 0x05, 0x77];
+const FILL_TIME: f32 = SNAIL_MOVEMENT_TIME / 2.0;
+
+/// Dead End Snail Upgrades:
+/// - Second Brush: Dead End Snail fills two corridors at a time.
+/// - Cul-de-sac:   Dead End Snail also fills loops which only lead back to where they started.
+/// - Instant Walk: Dead End Snail is at the goal as soon as the only path left is the solution.
+
+pub struct DeadEndFilling<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    snail: Snail<S>,
+    upgrades: u32,
+    terrain_cost: f32,
+
+    filled: [bool; S * S],
+    // dead ends which haven't been filled yet, and the corridors currently being filled
+    dead_ends: Vec<Vec2>,
+    corridors: Vec<Vec2>,
+    // the rest of the cul-de-sac being filled, with the cells furthest from its entrance last
+    cul_de_sac: Vec<Vec2>,
+    // where the snail starts walking from once the filling is done, which is never filled
+    origin: Vec2,
+    filling: bool,
+}
+
+impl<const S: usize> DeadEndFilling<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    // the open, unfilled neighbors of a cell
+    fn open_neighbors(&self, maze: &Maze<S>, pos: Vec2) -> Vec<(Direction, Vec2)> {
+        maze.get_cell(pos.x, pos.y)
+            .valid_directions()
+            .into_iter()
+            .filter_map(|direction| {
+                let next = maze.neighbor(pos, direction)?;
+                (!self.filled[next.y * S + next.x]).then_some((direction, next))
+            })
+            .collect()
+    }
+
+    fn is_dead_end(&self, maze: &Maze<S>, pos: Vec2) -> bool {
+        pos != self.origin
+            && pos != maze.end_pos
+            && maze.is_valid(pos.x, pos.y)
+            && !self.filled[pos.y * S + pos.x]
+            && self.open_neighbors(maze, pos).len() <= 1
+    }
+
+    fn start_filling(&mut self, maze: &Maze<S>) {
+        self.corridors.clear();
+        self.cul_de_sac.clear();
+        self.origin = self.snail.pos;
+        self.filling = true;
+        self.find_dead_ends(maze);
+    }
+
+    fn find_dead_ends(&mut self, maze: &Maze<S>) {
+        self.dead_ends = (0..(S * S))
+            .rev()
+            .map(|i| Vec2 { x: i % S, y: i / S })
+            .filter(|pos| self.is_dead_end(maze, *pos))
+            .collect();
+    }
+
+    // once every dead end is filled, the only unfilled cells which aren't on a way to the goal
+    // are in loops. a loop which can only be got into through one cell, without the snail or the
+    // goal in it, is a cul-de-sac, and everything past that cell can be filled in. returns the
+    // cells past the entrance of the first one found, nearest to the entrance first.
+    fn find_cul_de_sac(&self, maze: &Maze<S>) -> Option<Vec<Vec2>> {
+        (0..(S * S))
+            .map(|i| Vec2 { x: i % S, y: i / S })
+            .filter(|pos| maze.is_valid(pos.x, pos.y) && !self.filled[pos.y * S + pos.x])
+            .find_map(|entrance| {
+                self.open_neighbors(maze, entrance)
+                    .into_iter()
+                    .find_map(|(_, next)| self.cells_past(maze, entrance, next))
+            })
+    }
+
+    // everything that can be reached from start without going through entrance, unless that
+    // includes the snail or the goal
+    fn cells_past(&self, maze: &Maze<S>, entrance: Vec2, start: Vec2) -> Option<Vec<Vec2>> {
+        let mut seen = [false; S * S];
+        seen[entrance.y * S + entrance.x] = true;
+        seen[start.y * S + start.x] = true;
+
+        let mut cells = vec![start];
+        let mut i = 0;
+        while let Some(&pos) = cells.get(i) {
+            if pos == self.origin || pos == maze.end_pos {
+                return None;
+            }
+
+            for (_, next) in self.open_neighbors(maze, pos) {
+                if !seen[next.y * S + next.x] {
+                    seen[next.y * S + next.x] = true;
+                    cells.push(next);
+                }
+            }
+            i += 1;
+        }
+
+        Some(cells)
+    }
+
+    // fills in one more cell of each corridor, returns false once there is nothing left to fill
+    fn fill(&mut self, maze: &Maze<S>) -> bool {
+        // second brush
+        let brushes = if (self.upgrades & 0b1) != 0 { 2 } else { 1 };
+
+        while self.corridors.len() < brushes {
+            match self.dead_ends.pop() {
+                // an earlier corridor might have already reached it
+                Some(pos) if self.is_dead_end(maze, pos) => self.corridors.push(pos),
+                Some(_) => {}
+                None => break,
+            }
+        }
+
+        if self.corridors.is_empty() {
+            return self.fill_cul_de_sac(maze, brushes);
+        }
+
+        let mut corridors = std::mem::take(&mut self.corridors);
+        corridors.retain_mut(|pos| {
+            // two corridors can run into each other at a junction
+            if !self.is_dead_end(maze, *pos) {
+                return false;
+            }
+
+            let next = self
+                .open_neighbors(maze, *pos)
+                .first()
+                .map(|(_, next)| *next);
+            self.filled[pos.y * S + pos.x] = true;
+
+            match next {
+                Some(next) if self.is_dead_end(maze, next) => {
+                    *pos = next;
+                    true
+                }
+                _ => false,
+            }
+        });
+        self.corridors = corridors;
+
+        true
+    }
+
+    // fills in the cells of a cul-de-sac from the far end back towards its entrance, which can
+    // leave the entrance as a new dead end. returns false if there aren't any cul-de-sacs left
+    fn fill_cul_de_sac(&mut self, maze: &Maze<S>, brushes: usize) -> bool {
+        // cul-de-sac
+        if (self.upgrades & 0b10) == 0 {
+            return false;
+        }
+
+        if self.cul_de_sac.is_empty() {
+            match self.find_cul_de_sac(maze) {
+                Some(cells) => self.cul_de_sac = cells,
+                None => return false,
+            }
+        }
+
+        for _ in 0..brushes {
+            if let Some(pos) = self.cul_de_sac.pop() {
+                self.filled[pos.y * S + pos.x] = true;
+            }
+        }
+
+        if self.cul_de_sac.is_empty() {
+            self.find_dead_ends(maze);
+        }
+
+        true
+    }
+}
+
+impl<const S: usize> Solver<S> for DeadEndFilling<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn new() -> Self {
+        DeadEndFilling {
+            snail: Snail::new(),
+            upgrades: 0,
+            terrain_cost: 1.0,
+            filled: [false; S * S],
+            dead_ends: vec![],
+            corridors: vec![],
+            cul_de_sac: vec![],
+            origin: Vec2 { x: 0, y: 0 },
+            filling: true,
+        }
+    }
+
+    fn set_upgrades(&mut self, upgrades: u32) {
+        self.upgrades = upgrades;
+    }
+
+    fn draw(
+        &mut self,
+        animation_cycle: bool,
+        movement_timer: f32,
+        _rng: &mut dyn Rng,
+        image: &mut Image,
+        bx: usize,
+        by: usize,
+    ) {
+        for (i, filled) in self.filled.iter().enumerate() {
+            if *filled {
+                let x = (i % S) * 10 + 1;
+                let y = (i / S) * 10 + 1;
+
+                image.draw_rectangle_with(x, y, 9, 9, || FILL_COLOR, bx, by);
+            }
+        }
+
+        self.snail.draw(
+            DEFAULT_PALETTE,
+            animation_cycle,
+            movement_timer / self.movement_time(),
+            image,
+            bx,
+            by,
+        );
+    }
+
+    fn setup(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
+        self.snail.place(maze.start_pos);
+        self.terrain_cost = 1.0;
+        self.filled = [false; S * S];
+        self.start_filling(maze);
+    }
+
+    fn step(&mut self, maze: &mut Maze<S>, _rng: &mut dyn Rng) -> SolveStatus {
+        if self.filling {
+            if self.fill(maze) {
+                return SolveStatus::None;
+            }
+
+            self.filling = false;
+
+            // instant walk
+            if (self.upgrades & 0b100) != 0 {
+                return SolveStatus::Solved(1);
+            }
+        }
+
+        // loops on the way to the goal don't get filled in, so pick the quickest way through
+        // what's left. it never goes into anything which has been filled, since that would mean
+        // coming back out the same way
+        let pos = self.snail.pos;
+        if let Some(direction) = maze.get_solve_sequence(pos.x, pos.y, maze.end_pos).first() {
+            self.snail.direction = *direction;
+        }
+
+        self.snail.move_forward(maze);
+        self.terrain_cost = maze.get_terrain(self.snail.pos.x, self.snail.pos.y).cost();
+
+        if self.snail.pos == maze.end_pos {
+            SolveStatus::Solved(1)
+        } else {
+            SolveStatus::None
+        }
+    }
+
+    fn maze_changed(&mut self, maze: &Maze<S>) {
+        // anything off the new way to the goal can stay filled, since whatever is left unfilled
+        // around it still ends in dead ends. the rest gets filled starting from where the snail is.
+        let mut pos = self.snail.pos;
+        self.filled[pos.y * S + pos.x] = false;
+
+        for direction in maze.get_solve_sequence(pos.x, pos.y, maze.end_pos) {
+            pos = maze.neighbor(pos, direction).unwrap();
+            self.filled[pos.y * S + pos.x] = false;
+        }
+
+        self.start_filling(maze);
+    }
+
+    fn movement_time(&self) -> f32 {
+        if self.filling {
+            FILL_TIME
+        } else {
+            SNAIL_MOVEMENT_TIME * self.terrain_cost
+        }
+    }
+
+    fn supported_modifiers() -> u32 {
+        TERRAIN_MODIFIER | SHIFTING_MODIFIER | TORUS_MODIFIER
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeadEndFilling;
+    use crate::{
+        ascii::Board,
+        rng::Xoshiro256,
+        solvers::{SolveStatus, Solver},
+    };
+
+    // the way to the goal goes along the top and down the right, with a loop hanging off it
+    const LOOP: &str = concat!(
+        "###########\n",
+        "#S        #\n",
+        "##### ### #\n",
+        "# #     # #\n",
+        "### ### # #\n",
+        "# #     # #\n",
+        "######### #\n",
+        "# # # # # #\n",
+        "######### #\n",
+        "# # # # #G#\n",
+        "###########\n",
+    );
+
+    // fills until only the way to the goal is left, and returns which of the loop's cells are
+    // still unfilled
+    fn fill_loop(upgrades: u32) -> Vec<bool> {
+        let mut rng = Xoshiro256::new(0xBEEF);
+        let mut maze = Board::<5>::parse(LOOP).unwrap().maze;
+        let mut solver = DeadEndFilling::new();
+        solver.set_upgrades(upgrades);
+        solver.setup(&maze, &mut rng);
+
+        let solved =
+            (0..100).any(|_| matches!(solver.step(&mut maze, &mut rng), SolveStatus::Solved(_)));
+        assert!(solved);
+
+        [(1, 1), (2, 1), (3, 1), (1, 2), (2, 2), (3, 2)]
+            .iter()
+            .map(|(x, y)| !solver.filled[y * 5 + x])
+            .collect()
+    }
+
+    #[test]
+    fn fills_cul_de_sacs() {
+        assert!(fill_loop(0).iter().all(|unfilled| *unfilled));
+        assert!(fill_loop(0b10).iter().all(|unfilled| !*unfilled));
+    }
+}
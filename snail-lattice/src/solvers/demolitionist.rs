@@ -3,8 +3,11 @@ use std::{collections::HashSet, f32::consts::PI};
 use crate::{
     direction::Direction,
     image::Image,
-    lfsr::LFSR,
-    maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    maze::{
+        Maze, Terrain, CELLS_PER_IDX, PORTAL_MODIFIER, SHIFTING_MODIFIER, SNAIL_MOVEMENT_TIME,
+        TERRAIN_MODIFIER, TORUS_MODIFIER,
+    },
+    rng::Rng,
     snail::{Snail, DEFAULT_PALETTE, PHASE_2_PALETTE},
     solvers::Solver,
     utils::Vec2,
@@ -93,8 +96,8 @@ where
     upgrades: u32,
     solve_sequence: Vec<Direction>,
     bombs: Vec<Bomb>,
-    destroyed_squares: Vec<bool>,
     walked_tiles: f32,
+    terrain_cost: f32,
 }
 
 impl<const S: usize> Solver<S> for Demolitionist<S>
@@ -107,8 +110,8 @@ where
             upgrades: 0,
             solve_sequence: vec![],
             bombs: vec![],
-            destroyed_squares: vec![false; S * S],
             walked_tiles: 0.0,
+            terrain_cost: 1.0,
         }
     }
 
@@ -120,7 +123,7 @@ where
         &mut self,
         animation_cycle: bool,
         movement_timer: f32,
-        _lfsr: &mut LFSR,
+        _rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
@@ -135,11 +138,11 @@ where
         }
     }
 
-    fn setup(&mut self, _maze: &Maze<S>, lfsr: &mut LFSR) {
+    fn setup(&mut self, maze: &Maze<S>, rng: &mut dyn Rng) {
         self.bombs.clear();
-        self.snail.reset();
-        self.destroyed_squares.fill(false);
+        self.snail.place(maze.start_pos);
         self.walked_tiles = 0.0;
+        self.terrain_cost = 1.0;
 
         let mut invalid_positions = HashSet::new();
         //
@@ -150,12 +153,12 @@ where
 
         // generate some random enemies in random locations
         for _ in 0..bomb_count {
-            let mut x = lfsr.big() % S;
-            let mut y = lfsr.big() % S;
+            let mut x = rng.big() % S;
+            let mut y = rng.big() % S;
 
             while invalid_positions.contains(&(x, y)) {
-                x = lfsr.big() % S;
-                y = lfsr.big() % S;
+                x = rng.big() % S;
+                y = rng.big() % S;
             }
 
             invalid_positions.insert((x, y));
@@ -164,7 +167,7 @@ where
         }
     }
 
-    fn step(&mut self, maze: &mut Maze<S>, _lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, maze: &mut Maze<S>, _rng: &mut dyn Rng) -> SolveStatus {
         if !self.bombs.is_empty() {
             let mut bomb_exploded = false;
 
@@ -180,7 +183,9 @@ where
                     maze.remove_wall(pos.x, pos.y, Direction::Right);
                     bomb_exploded = true;
 
-                    self.destroyed_squares[pos.y * S + pos.x] = true;
+                    // the blasted cell is left covered in rubble, which the snail gets faster
+                    // at crossing with Distructive Habits
+                    maze.set_terrain(pos.x, pos.y, Terrain::Rubble);
                 }
 
                 if res {
@@ -205,7 +210,10 @@ where
             self.snail.direction = self.solve_sequence.pop().unwrap();
             self.snail.move_forward(maze);
 
-            if self.destroyed_squares[self.snail.pos.y * S + self.snail.pos.x] {
+            let terrain = maze.get_terrain(self.snail.pos.x, self.snail.pos.y);
+            self.terrain_cost = terrain.cost();
+
+            if terrain == Terrain::Rubble {
                 self.walked_tiles += 1.0;
                 // println!("{}", self.walked_tiles);
             }
@@ -218,15 +226,23 @@ where
         }
     }
 
+    fn maze_changed(&mut self, _maze: &Maze<S>) {
+        self.solve_sequence.clear();
+    }
+
     fn palette() -> [[u8; 3]; 6] {
         PHASE_2_PALETTE
     }
 
     fn movement_time(&self) -> f32 {
         if self.upgrades & 0b100 != 0 {
-            (SNAIL_MOVEMENT_TIME - (50.0 * self.walked_tiles)).max(10.0)
+            (SNAIL_MOVEMENT_TIME - (50.0 * self.walked_tiles)).max(10.0) * self.terrain_cost
         } else {
-            SNAIL_MOVEMENT_TIME
+            SNAIL_MOVEMENT_TIME * self.terrain_cost
         }
     }
+
+    fn supported_modifiers() -> u32 {
+        TERRAIN_MODIFIER | PORTAL_MODIFIER | SHIFTING_MODIFIER | TORUS_MODIFIER
+    }
 }
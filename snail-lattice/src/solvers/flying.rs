@@ -1,8 +1,8 @@
 use crate::{
     direction::Direction,
     image::Image,
-    lfsr::LFSR,
     maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    rng::Rng,
     snail::{PHASE_2_PALETTE},
     solvers::Solver,
 };
@@ -53,7 +53,7 @@ where
         &mut self,
         animation_cycle: bool,
         movement_timer: f32,
-        _lfsr: &mut LFSR,
+        _rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
@@ -74,7 +74,7 @@ where
     }
 
     //
-    fn setup(&mut self, _maze: &Maze<S>, lfsr: &mut LFSR) {
+    fn setup(&mut self, _maze: &Maze<S>, rng: &mut dyn Rng) {
         self.swarm_weights.clear();
 
         let mut swarm_count = 6;
@@ -88,20 +88,24 @@ where
         }
 
         for _ in 0..swarm_count {
-            let weight1 = (lfsr.big() % 101) as f32 / 100.0;
-            let weight2 = (lfsr.big() % 101) as f32 / 100.0;
-            let weight3 = (lfsr.big() % 101) as f32 / 100.0;
-            let weight4 = (lfsr.big() % 101) as f32 / 100.0;
+            let weight1 = (rng.big() % 101) as f32 / 100.0;
+            let weight2 = (rng.big() % 101) as f32 / 100.0;
+            let weight3 = (rng.big() % 101) as f32 / 100.0;
+            let weight4 = (rng.big() % 101) as f32 / 100.0;
 
             self.swarm_weights
                 .push((weight1, weight2, weight3, weight4));
         }
     }
 
-    fn step(&mut self, _maze: &mut Maze<S>, _lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, _maze: &mut Maze<S>, _rng: &mut dyn Rng) -> SolveStatus {
         SolveStatus::Solved(self.swarm_weights.len())
     }
 
+    fn supports_masks() -> bool {
+        false
+    }
+
     fn palette() -> [[u8; 3]; 6] {
         PHASE_2_PALETTE
     }
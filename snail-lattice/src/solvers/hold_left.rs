@@ -1,7 +1,10 @@
 use crate::{
     image::Image,
-    lfsr::LFSR,
-    maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    maze::{
+        Maze, CELLS_PER_IDX, SHIFTING_MODIFIER, SNAIL_MOVEMENT_TIME, TERRAIN_MODIFIER,
+        TORUS_MODIFIER,
+    },
+    rng::Rng,
     snail::{Snail, DEFAULT_PALETTE},
     solvers::Solver,
 };
@@ -19,6 +22,8 @@ where
     snail: Snail<S>,
     alt_snail: Option<Box<Inverted<S>>>,
     upgrades: u32,
+    terrain_cost: f32,
+    palette: [[u8; 3]; 6],
 }
 
 impl<const S: usize> Solver<S> for HoldLeft<S>
@@ -30,6 +35,8 @@ where
             snail: Snail::new(),
             alt_snail: None,
             upgrades: 0,
+            terrain_cost: 1.0,
+            palette: DEFAULT_PALETTE,
         }
     }
 
@@ -49,17 +56,17 @@ where
         &mut self,
         animation_cycle: bool,
         movement_timer: f32,
-        lfsr: &mut LFSR,
+        rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
     ) {
         if let Some(right_handed) = &mut self.alt_snail {
-            right_handed.draw(animation_cycle, movement_timer, lfsr, image, bx, by);
+            right_handed.draw(animation_cycle, movement_timer, rng, image, bx, by);
         }
 
         self.snail.draw(
-            DEFAULT_PALETTE,
+            self.palette,
             animation_cycle,
             movement_timer / self.movement_time(),
             image,
@@ -68,16 +75,17 @@ where
         );
     }
 
-    fn setup(&mut self, _maze: &Maze<S>, _lfsr: &mut LFSR) {
-        self.snail.reset();
+    fn setup(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
+        self.snail.place(maze.start_pos);
+        self.terrain_cost = 1.0;
         if let Some(right_handed) = &mut self.alt_snail {
-            right_handed.setup(_maze, _lfsr);
+            right_handed.setup(maze, _rng);
         }
     }
 
-    fn step(&mut self, maze: &mut Maze<S>, lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
         if let Some(right_handed) = &mut self.alt_snail {
-            match right_handed.step(maze, lfsr) {
+            match right_handed.step(maze, rng) {
                 SolveStatus::Solved(count) => return SolveStatus::Solved(count),
                 _ => {}
             }
@@ -99,6 +107,9 @@ where
         }
 
         self.snail.move_forward(maze);
+        self.terrain_cost = maze
+            .get_terrain(self.snail.pos.x, self.snail.pos.y)
+            .cost();
 
         if self.snail.pos == maze.end_pos {
             SolveStatus::Solved(1)
@@ -110,9 +121,17 @@ where
     fn movement_time(&self) -> f32 {
         // left glove
         if (self.upgrades & 0b1) != 0 {
-            SNAIL_MOVEMENT_TIME * 0.8
+            SNAIL_MOVEMENT_TIME * 0.8 * self.terrain_cost
         } else {
-            SNAIL_MOVEMENT_TIME
+            SNAIL_MOVEMENT_TIME * self.terrain_cost
         }
     }
+
+    fn set_palette(&mut self, palette: [[u8; 3]; 6]) {
+        self.palette = palette;
+    }
+
+    fn supported_modifiers() -> u32 {
+        TERRAIN_MODIFIER | SHIFTING_MODIFIER | TORUS_MODIFIER
+    }
 }
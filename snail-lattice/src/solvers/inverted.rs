@@ -1,7 +1,7 @@
 use crate::{
     image::Image,
-    lfsr::LFSR,
-    maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME, TERRAIN_MODIFIER, TORUS_MODIFIER},
+    rng::Rng,
     snail::{Snail, INVERTED_PALETTE},
     solvers::Solver,
 };
@@ -19,6 +19,7 @@ where
     snail: Snail<S>,
     alt_snail: Option<Box<HoldLeft<S>>>,
     upgrades: u32,
+    terrain_cost: f32,
 }
 
 impl<const S: usize> Solver<S> for Inverted<S>
@@ -30,6 +31,7 @@ where
             snail: Snail::new(),
             alt_snail: None,
             upgrades: 0,
+            terrain_cost: 1.0,
         }
     }
 
@@ -49,13 +51,13 @@ where
         &mut self,
         animation_cycle: bool,
         movement_timer: f32,
-        lfsr: &mut LFSR,
+        rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
     ) {
         if let Some(left_handed) = &mut self.alt_snail {
-            left_handed.draw(animation_cycle, movement_timer, lfsr, image, bx, by);
+            left_handed.draw(animation_cycle, movement_timer, rng, image, bx, by);
         }
 
         self.snail.draw(
@@ -68,16 +70,17 @@ where
         );
     }
 
-    fn setup(&mut self, _maze: &Maze<S>, _lfsr: &mut LFSR) {
-        self.snail.reset();
+    fn setup(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
+        self.snail.place(maze.start_pos);
+        self.terrain_cost = 1.0;
         if let Some(left_handed) = &mut self.alt_snail {
-            left_handed.setup(_maze, _lfsr);
+            left_handed.setup(maze, _rng);
         }
     }
 
-    fn step(&mut self, maze: &mut Maze<S>, lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
         if let Some(left_handed) = &mut self.alt_snail {
-            match left_handed.step(maze, lfsr) {
+            match left_handed.step(maze, rng) {
                 SolveStatus::Solved(count) => return SolveStatus::Solved(count),
                 _ => {}
             }
@@ -99,6 +102,9 @@ where
         }
 
         self.snail.move_forward(maze);
+        self.terrain_cost = maze
+            .get_terrain(self.snail.pos.x, self.snail.pos.y)
+            .cost();
 
         if self.snail.pos == maze.end_pos {
             SolveStatus::Solved(1)
@@ -110,12 +116,16 @@ where
     fn movement_time(&self) -> f32 {
         // right glove
         if (self.upgrades & 0b1) != 0 {
-            SNAIL_MOVEMENT_TIME * 0.8
+            SNAIL_MOVEMENT_TIME * 0.8 * self.terrain_cost
         } else {
-            SNAIL_MOVEMENT_TIME
+            SNAIL_MOVEMENT_TIME * self.terrain_cost
         }
     }
 
+    fn supported_modifiers() -> u32 {
+        TERRAIN_MODIFIER | TORUS_MODIFIER
+    }
+
     fn palette() -> [[u8; 3]; 6] {
         INVERTED_PALETTE
     }
@@ -3,23 +3,43 @@ use std::cmp::Ordering;
 use crate::{
     direction::Direction,
     image::Image,
-    lfsr::LFSR,
     maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    rng::Rng,
     snail::{Snail, DEFAULT_PALETTE},
     solvers::Solver,
+    utils::{Vec2, Vec2i},
 };
 
 use super::SolveStatus;
 
-// This does not implement a real genetic algorithm because they seem to suck for mazes and end up
-// being both way too slow and computationally intensive to be viable for this game, so we instead
-// simulate it with something aesthetically similar.
+// By default this does not implement a real genetic algorithm because they seem to suck for mazes
+// and end up being both way too slow and computationally intensive to be viable for this game, so
+// we instead simulate it with something aesthetically similar. the genetic mode runs a real one,
+// for anyone who wants to watch it struggle, see Learning::set_mode.
+
+// Solver::set_mode for the real genetic algorithm
+const GENETIC_MODE: u32 = 1;
+
+// in the genetic mode, each generation gets this many times as many moves as the solution is long
+const GENOME_LENGTH_FACTOR: usize = 4;
+// how many random snails are compared to pick each parent
+const TOURNAMENT_SIZE: usize = 3;
+// chance out of 1000 for each move of a new snail to be replaced with a random one
+const MUTATION_RATE: usize = 30;
+// a real genetic algorithm can go on forever without finding the goal, so after this many
+// generations the snails give up on evolving and just walk the solution
+const GENERATION_CAP: usize = 200;
+
+const GRAPH_HEIGHT: usize = 12;
+const GRAPH_COLOR: [u8; 3] = DEFAULT_PALETTE[2];
 
 struct LearningSnail<const S: usize>
 where
     [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
 {
     fitness: usize,
+    // how many moves it took to first get as close to the goal as it has been
+    reached_at: usize,
     counter: usize,
     moves: Vec<Direction>,
     pub snail: Snail<S>,
@@ -29,58 +49,75 @@ impl<const S: usize> LearningSnail<S>
 where
     [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
 {
-    fn new_random(lfsr: &mut LFSR, length: usize) -> Self {
+    fn new_random(rng: &mut dyn Rng, length: usize, start: Vec2) -> Self {
+        let mut snail = Snail::new();
+        snail.place(start);
+
         Self {
             fitness: usize::MAX,
+            reached_at: 0,
             counter: 0,
-            moves: Self::random_moves(length, lfsr),
-            snail: Snail::new(),
+            moves: Self::random_moves(length, rng),
+            snail,
         }
     }
 
-    fn reset(&mut self) {
-        self.snail.pos.x = 0;
-        self.snail.pos.y = 0;
+    fn reset(&mut self, start: Vec2) {
+        self.snail.pos = start;
         self.snail.active = true;
         self.fitness = usize::MAX;
+        self.reached_at = 0;
         self.counter = 0;
     }
 
-    fn random_moves(length: usize, lfsr: &mut LFSR) -> Vec<Direction> {
+    fn random_moves(length: usize, rng: &mut dyn Rng) -> Vec<Direction> {
         let mut moves = Vec::with_capacity(S * S);
 
         for _ in 0..length {
-            moves.push(Direction::from_number(lfsr.next().into()));
+            moves.push(Direction::from_number(rng.next().into()));
         }
 
         moves
     }
 
-    fn crossover(&self, lfsr: &mut LFSR, other: &LearningSnail<S>) -> Vec<Direction> {
+    fn crossover(&self, rng: &mut dyn Rng, other: &LearningSnail<S>) -> Vec<Direction> {
         let mut new_moves = self.moves.clone();
         let len = self.moves.len();
 
-        let pos1 = lfsr.big() % len;
-        let pos2 = lfsr.big() % (len - pos1) + pos1;
+        let pos1 = rng.big() % len;
+        let pos2 = rng.big() % (len - pos1) + pos1;
 
-        if lfsr.next() % 2 == 0 {
+        if rng.next() % 2 == 0 {
             new_moves[0..pos1].copy_from_slice(&other.moves[0..pos1]);
         }
 
-        if lfsr.next() % 2 == 0 {
+        if rng.next() % 2 == 0 {
             new_moves[pos1..pos2].copy_from_slice(&other.moves[pos1..pos2]);
         }
 
-        if lfsr.next() % 2 == 0 {
+        if rng.next() % 2 == 0 {
             new_moves[pos2..len].copy_from_slice(&other.moves[pos2..len]);
         }
 
         new_moves
     }
 
-    fn mutate(&mut self, solve_sequence: &Vec<Direction>, mutation_amount: usize, lfsr: &mut LFSR) {
+    fn mutate_randomly(moves: &mut [Direction], mutation_rate: usize, rng: &mut dyn Rng) {
+        for direction in moves.iter_mut() {
+            if rng.big() % 1000 < mutation_rate {
+                *direction = Direction::from_number(rng.next().into());
+            }
+        }
+    }
+
+    fn mutate(
+        &mut self,
+        solve_sequence: &Vec<Direction>,
+        mutation_amount: usize,
+        rng: &mut dyn Rng,
+    ) {
         for _ in 0..mutation_amount {
-            let i = lfsr.big() % self.moves.len();
+            let i = rng.big() % self.moves.len();
             self.moves[i] = solve_sequence[i];
         }
     }
@@ -92,7 +129,16 @@ where
         self.counter += 1;
 
         let dist = distances[self.snail.pos.y * S + self.snail.pos.x];
-        self.fitness = self.fitness.min(dist);
+        if dist < self.fitness {
+            self.fitness = dist;
+            self.reached_at = self.counter;
+        }
+    }
+
+    // lower is better. of two snails which got as close to the goal, the one which got there in
+    // fewer moves has more moves left over to make progress with
+    fn score(&self) -> (usize, usize) {
+        (self.fitness, self.reached_at)
     }
 }
 
@@ -100,15 +146,20 @@ where
 /// - Population Boom: Generate more learning snails per generation.
 /// - Uranium:         Learning Snails become more prone to beneficial mutation and faster movement, yielding more efficient solves.
 /// - Radium:          Learning Snails become more prone to beneficial mutaiton and faster movement, yielding more efficient solves.
+///
+/// In the genetic mode mutations are random rather than beneficial, so Uranium and Radium raise the mutation rate instead.
 
 pub struct Learning<const S: usize>
 where
     [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
 {
+    genetic: bool,
     population: Vec<LearningSnail<S>>,
     generation_timer: usize,
     generation_count: usize,
     fitness: usize,
+    // the best fitness of every generation since the maze was generated, for the graph
+    fitness_history: Vec<usize>,
     distances: [usize; S * S],
     upgrades: u32,
     solve_sequence: Vec<Direction>,
@@ -130,6 +181,96 @@ where
     fn mutation_amount(&self) -> usize {
         (5 + (self.upgrades & 0b10) + (self.upgrades & 0b100)) as usize
     }
+
+    fn mutation_rate(&self) -> usize {
+        MUTATION_RATE * self.mutation_amount() / 5
+    }
+
+    fn genome_length(&self) -> usize {
+        if self.genetic {
+            GENOME_LENGTH_FACTOR * self.solve_sequence.len()
+        } else {
+            self.solve_sequence.len()
+        }
+    }
+
+    // the best of a few random snails
+    fn tournament(&self, rng: &mut dyn Rng) -> &LearningSnail<S> {
+        (0..TOURNAMENT_SIZE)
+            .map(|_| &self.population[rng.big() % self.population.len()])
+            .min_by_key(|snail| snail.score())
+            .unwrap()
+    }
+
+    // breeds the next generation out of the current one, which has already been sorted by fitness
+    fn simulated_generation(&mut self, rng: &mut dyn Rng) -> Vec<Vec<Direction>> {
+        let mut moves_list = vec![];
+
+        let top_selection = self.population_count() / 5;
+
+        let mutation_amount = self.mutation_amount();
+        for snail in self.population.iter_mut() {
+            snail.mutate(&self.solve_sequence, mutation_amount, rng);
+        }
+
+        // cross
+        for _ in 0..self.population_count() {
+            let snail1 = rng.big() % top_selection;
+            let snail2 = rng.big() % top_selection;
+
+            moves_list.push(self.population[snail1].crossover(rng, &self.population[snail2]));
+        }
+
+        moves_list
+    }
+
+    fn genetic_generation(&mut self, rng: &mut dyn Rng) -> Vec<Vec<Direction>> {
+        // the best snail always makes it into the next generation unchanged
+        let mut moves_list = vec![self.population[0].moves.clone()];
+
+        let mutation_rate = self.mutation_rate();
+        while moves_list.len() < self.population_count() {
+            let parent1 = self.tournament(rng);
+            let parent2 = self.tournament(rng);
+
+            let mut moves = parent1.crossover(rng, parent2);
+            LearningSnail::<S>::mutate_randomly(&mut moves, mutation_rate, rng);
+
+            moves_list.push(moves);
+        }
+
+        moves_list
+    }
+
+    // every snail walks the solution, and does whatever it likes with the moves left over
+    fn given_up_generation(&mut self, rng: &mut dyn Rng) -> Vec<Vec<Direction>> {
+        let leftover = self.genome_length() - self.solve_sequence.len();
+
+        (0..self.population_count())
+            .map(|_| {
+                let mut moves = self.solve_sequence.clone();
+                moves.extend(LearningSnail::<S>::random_moves(leftover, rng));
+                moves
+            })
+            .collect()
+    }
+
+    fn draw_fitness_graph(&self, image: &mut Image, bx: usize, by: usize) {
+        let width = S * 10 - 3;
+        let worst = self.fitness_history.iter().copied().fold(1, usize::max);
+
+        // only the most recent generations fit
+        let history = &self.fitness_history[self.fitness_history.len().saturating_sub(width)..];
+
+        let point = |i: usize, fitness: usize| Vec2i {
+            x: (bx + 2 + i) as i32,
+            y: (by + 2 + GRAPH_HEIGHT * fitness / worst) as i32,
+        };
+
+        for (i, pair) in history.windows(2).enumerate() {
+            image.draw_line(GRAPH_COLOR, point(i, pair[0]), point(i + 1, pair[1]));
+        }
+    }
 }
 
 impl<const S: usize> Solver<S> for Learning<S>
@@ -138,9 +279,11 @@ where
 {
     fn new() -> Self {
         Learning {
+            genetic: false,
             population: Vec::new(),
             generation_count: 0,
             generation_timer: 0,
+            fitness_history: Vec::new(),
             distances: [0; S * S],
             solve_sequence: Vec::new(),
             upgrades: 0,
@@ -153,11 +296,21 @@ where
         self.upgrades = upgrades;
     }
 
+    // switching starts the evolution over, since the genomes are a different length
+    fn set_mode(&mut self, mode: u32) {
+        let genetic = mode == GENETIC_MODE;
+
+        if genetic != self.genetic {
+            self.genetic = genetic;
+            self.new_maze = true;
+        }
+    }
+
     fn draw(
         &mut self,
         animation_cycle: bool,
         movement_timer: f32,
-        _lfsr: &mut LFSR,
+        _rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
@@ -172,6 +325,10 @@ where
 
         image.draw_text(&start, bx + 2, by + 1 + S * 10 - 11);
 
+        if self.genetic {
+            self.draw_fitness_graph(image, bx, by);
+        }
+
         for snail in self.population.iter() {
             snail.snail.draw(
                 DEFAULT_PALETTE,
@@ -184,56 +341,59 @@ where
         }
     }
 
-    fn step(&mut self, maze: &mut Maze<S>, lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
         if self.new_maze {
             maze.get_distances(maze.end_pos.x, maze.end_pos.y, &mut self.distances);
-            self.solve_sequence = maze.get_solve_sequence(0, 0, maze.end_pos);
+            self.solve_sequence =
+                maze.get_solve_sequence(maze.start_pos.x, maze.start_pos.y, maze.end_pos);
 
+            let genome_length = self.genome_length();
             for snail in self.population.iter_mut() {
-                snail.reset();
-                snail.moves = LearningSnail::random_moves(self.solve_sequence.len(), lfsr);
+                snail.reset(maze.start_pos);
+                snail.moves = LearningSnail::random_moves(genome_length, rng);
             }
 
             self.new_maze = false;
+            self.generation_timer = 0;
             self.generation_count = 0;
+            self.fitness_history.clear();
         }
 
         // if empty, seed with random snails
         if self.population.len() == 0 {
             for _ in 0..self.population_count() {
-                self.population
-                    .push(LearningSnail::new_random(lfsr, self.solve_sequence.len()));
+                self.population.push(LearningSnail::new_random(
+                    rng,
+                    self.genome_length(),
+                    maze.start_pos,
+                ));
             }
         }
 
-        if self.generation_timer >= self.solve_sequence.len() {
+        if self.generation_timer >= self.genome_length() {
             // let distances = self.distances.clone();
 
-            self.population
-                .sort_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap_or(Ordering::Equal));
-
-            self.fitness = self.population[0].fitness;
-
-            let mut moves_list = vec![];
-
-            let top_selection = self.population_count() / 5;
-
-            let mutation_amount = self.mutation_amount();
-            for snail in self.population.iter_mut() {
-                snail.mutate(&self.solve_sequence, mutation_amount, lfsr);
+            if self.genetic {
+                self.population.sort_by_key(|snail| snail.score());
+            } else {
+                self.population
+                    .sort_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap_or(Ordering::Equal));
             }
 
-            // cross
-            for _ in 0..self.population_count() {
-                let snail1 = lfsr.big() % top_selection;
-                let snail2 = lfsr.big() % top_selection;
+            self.fitness = self.population[0].fitness;
+            self.fitness_history.push(self.fitness);
 
-                moves_list.push(self.population[snail1].crossover(lfsr, &self.population[snail2]));
-            }
+            let moves_list = if self.genetic && self.generation_count >= GENERATION_CAP {
+                self.given_up_generation(rng)
+            } else if self.genetic {
+                self.genetic_generation(rng)
+            } else {
+                self.simulated_generation(rng)
+            };
 
             for (snail, moves) in self.population.iter_mut().zip(moves_list) {
                 snail.moves = moves;
-                snail.reset();
+                snail.reset(maze.start_pos);
             }
 
             self.generation_timer = 0;
@@ -274,3 +434,32 @@ where
         movement_time
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Learning, GENERATION_CAP, GENETIC_MODE};
+    use crate::{
+        maze::Maze,
+        rng::Xoshiro256,
+        solvers::{SolveStatus, Solver},
+    };
+
+    #[test]
+    fn genetic_mode_always_solves() {
+        let mut rng = Xoshiro256::new(0xBEEF);
+        let mut learning = Learning::<9>::new();
+        learning.set_mode(GENETIC_MODE);
+
+        for _ in 0..3 {
+            let mut maze = Maze::<9>::new();
+            maze.generate(&mut rng);
+            learning.setup(&maze, &mut rng);
+
+            let solved = (0..1_000_000)
+                .any(|_| matches!(learning.step(&mut maze, &mut rng), SolveStatus::Solved(_)));
+            assert!(solved);
+            // the generation which walks the solution counts too
+            assert!(learning.generation_count <= GENERATION_CAP + 1);
+        }
+    }
+}
@@ -1,34 +1,53 @@
 use crate::{
     image::Image,
-    lfsr::LFSR,
     maze::{Maze, CELLS_PER_IDX},
-    snail::DEFAULT_PALETTE,
+    rng::Rng,
+    snail::{Snail, DEFAULT_PALETTE},
+    vm::{Program, VmError},
 };
 
+mod a_star;
+mod ant_colony;
 mod automaton;
+mod bidirectional;
+mod boids;
 mod clones;
+mod combinators;
+mod dead_end_filling;
 mod demolitionist;
 mod flying;
 mod hold_left;
 mod inverted;
 mod learning;
+mod pledge;
+mod q_learning;
 mod random_teleport;
 mod random_walk;
 mod rpg;
+mod scripted;
 mod telepathic;
 mod time_travel;
 mod tremaux;
 
+pub use a_star::AStar;
+pub use ant_colony::AntColony;
 pub use automaton::Automaton;
+pub use bidirectional::Bidirectional;
+pub use boids::Boids;
 pub use clones::Clones;
+pub use combinators::{FirstOf, Sequence};
+pub use dead_end_filling::DeadEndFilling;
 pub use demolitionist::Demolitionist;
 pub use flying::Flying;
 pub use hold_left::HoldLeft;
 pub use inverted::Inverted;
 pub use learning::Learning;
+pub use pledge::Pledge;
+pub use q_learning::QLearning;
 pub use random_teleport::RandomTeleport;
 pub use random_walk::RandomWalk;
 pub use rpg::Rpg;
+pub use scripted::Scripted;
 pub use telepathic::Telepathic;
 pub use time_travel::TimeTravel;
 pub use tremaux::Tremaux;
@@ -37,6 +56,8 @@ pub use tremaux::Tremaux;
 pub enum SolveStatus {
     Solved(usize),
     Rerender,
+    // a key was picked up and its door opened, so the maze needs to be rerendered
+    KeyCollected,
     None,
 }
 
@@ -45,6 +66,7 @@ impl SolveStatus {
         match self {
             SolveStatus::Solved(count) => count,
             SolveStatus::Rerender => 0,
+            SolveStatus::KeyCollected => 0,
             SolveStatus::None => 0,
         }
     }
@@ -54,13 +76,15 @@ pub trait Solver<const S: usize>
 where
     [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
 {
-    fn new() -> Self;
+    fn new() -> Self
+    where
+        Self: Sized;
 
     fn draw(
         &mut self,
         animation_cycle: bool,
         movement_timer: f32,
-        lfsr: &mut LFSR,
+        rng: &mut dyn Rng,
 
         image: &mut Image,
         bx: usize,
@@ -70,19 +94,79 @@ where
     fn set_upgrades(&mut self, upgrades: u32);
 
     // run upon maze generation
-    fn setup(&mut self, _maze: &Maze<S>, _lfsr: &mut LFSR) {}
+    fn setup(&mut self, _maze: &Maze<S>, _rng: &mut dyn Rng) {}
 
     // returns true if the step solved the maze
     // run at a fixed step rate based on movement_time
-    fn step(&mut self, maze: &mut Maze<S>, lfsr: &mut LFSR) -> SolveStatus;
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus;
+
+    // run when the walls of the maze move without a new maze being generated, so anything that
+    // was planned using the old walls can be thrown away
+    fn maze_changed(&mut self, _maze: &Maze<S>) {}
 
     fn movement_time(&self) -> f32;
 
-    fn custom_goal() -> bool {
+    // the snail predators hunt, see PREDATOR_MODIFIER. only solvers with a single snail have one
+    fn snail(&self) -> Option<&Snail<S>> {
+        None
+    }
+
+    // run when a predator catches the snail, which starts the maze over by default
+    fn caught(&mut self, maze: &Maze<S>, rng: &mut dyn Rng) {
+        self.setup(maze, rng);
+    }
+
+    // anything the solver has learned which should outlive the page, see SnailLattice::snapshot.
+    // restore is given whatever snapshot returned, possibly from an older version of the game
+    fn snapshot(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    fn restore(&mut self, _data: &[u8]) {}
+
+    // a program written by the player, see vm.rs. only Scripted runs one, and it reports what
+    // stopped the program if it crashed
+    fn set_program(&mut self, _program: &Program) {}
+
+    fn program_error(&self) -> Option<VmError> {
+        None
+    }
+
+    // switches between the ways a solver knows how to work, see e.g. Learning. 0 is the default
+    // for every solver, and modes a solver doesn't have are ignored
+    fn set_mode(&mut self, _mode: u32) {}
+
+    // draws the solver's snails in a different palette from now on, so several solvers sharing a
+    // maze can be told apart, see Race
+    fn set_palette(&mut self, _palette: [[u8; 3]; 6]) {}
+
+    fn custom_goal() -> bool
+    where
+        Self: Sized,
+    {
         false
     }
 
-    fn palette() -> [[u8; 3]; 6] {
+    // bitmask of the maze modifiers this solver can handle
+    fn supported_modifiers() -> u32
+    where
+        Self: Sized,
+    {
+        0
+    }
+
+    // whether the solver can handle mazes which don't fill the whole grid, see Maze::set_mask
+    fn supports_masks() -> bool
+    where
+        Self: Sized,
+    {
+        true
+    }
+
+    fn palette() -> [[u8; 3]; 6]
+    where
+        Self: Sized,
+    {
         DEFAULT_PALETTE
     }
 }
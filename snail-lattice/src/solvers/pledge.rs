@@ -0,0 +1,249 @@
+use crate::{
+    direction::Direction,
+    image::Image,
+    maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME, TERRAIN_MODIFIER, TORUS_MODIFIER},
+    rng::Rng,
+    snail::{Snail, DEFAULT_PALETTE},
+    solvers::Solver,
+};
+
+use super::SolveStatus;
+
+/// Pledge Snail Upgrades:
+/// - Protractor: Pledge Snail measures its turns with a protractor, allowing it to move 20% faster.
+/// - Compass:    Pledge Snail always sets off in the direction of the goal.
+/// - Sprint:     Pledge Snail moves twice as fast when it isn't holding onto a wall.
+
+pub struct Pledge<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    snail: Snail<S>,
+    upgrades: u32,
+    terrain_cost: f32,
+
+    // the direction the snail walks in whenever it isn't following a wall
+    preferred: Direction,
+
+    // left turns minus right turns since the snail started following a wall. the snail lets go
+    // of the wall once this is back to 0, which means it is facing the preferred direction again
+    // and has gone all the way around whatever it was stuck on.
+    turns: i32,
+    following: bool,
+
+    // pledge only guarantees escaping to the outside of a maze, so the snail can still go in
+    // circles around a goal in the middle, or around the whole maze when its edges wrap. after
+    // enough steps to have been through every cell twice, the snail stops letting go of the wall,
+    // since following a single wall visits every cell of a maze without loops.
+    steps: usize,
+}
+
+impl<const S: usize> Pledge<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn reset_heading(&mut self, preferred: Direction) {
+        self.preferred = preferred;
+        self.turns = 0;
+        self.following = false;
+        self.steps = 0;
+    }
+
+    fn gave_up(&self) -> bool {
+        self.steps > 2 * S * S
+    }
+
+    // the direction which closes the larger part of the distance to the goal
+    fn towards_goal(&self, maze: &Maze<S>) -> Direction {
+        let dx = maze.end_pos.x as i32 - self.snail.pos.x as i32;
+        let dy = maze.end_pos.y as i32 - self.snail.pos.y as i32;
+
+        if dx.abs() >= dy.abs() {
+            if dx >= 0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if dy >= 0 {
+            Direction::Down
+        } else {
+            Direction::Up
+        }
+    }
+
+    fn turn_text(&self) -> String {
+        // the font doesn't have a minus sign
+        if self.turns > 0 {
+            format!("left:{}", self.turns)
+        } else if self.turns < 0 {
+            format!("right:{}", -self.turns)
+        } else {
+            "turns:0".to_string()
+        }
+    }
+}
+
+impl<const S: usize> Solver<S> for Pledge<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn new() -> Self {
+        Pledge {
+            snail: Snail::new(),
+            upgrades: 0,
+            terrain_cost: 1.0,
+            preferred: Direction::Right,
+            turns: 0,
+            following: false,
+            steps: 0,
+        }
+    }
+
+    fn set_upgrades(&mut self, upgrades: u32) {
+        self.upgrades = upgrades;
+    }
+
+    fn draw(
+        &mut self,
+        animation_cycle: bool,
+        movement_timer: f32,
+        _rng: &mut dyn Rng,
+        image: &mut Image,
+        bx: usize,
+        by: usize,
+    ) {
+        image.draw_text(&self.turn_text(), bx + 2, by + 1 + S * 10 - 6);
+
+        self.snail.draw(
+            DEFAULT_PALETTE,
+            animation_cycle,
+            movement_timer / self.movement_time(),
+            image,
+            bx,
+            by,
+        );
+    }
+
+    fn setup(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
+        self.snail.place(maze.start_pos);
+        self.terrain_cost = 1.0;
+
+        // compass
+        if (self.upgrades & 0b10) != 0 {
+            self.reset_heading(self.towards_goal(maze));
+        } else {
+            self.reset_heading(Direction::Right);
+        }
+
+        self.snail.direction = self.preferred;
+    }
+
+    fn step(&mut self, maze: &mut Maze<S>, _rng: &mut dyn Rng) -> SolveStatus {
+        self.steps += 1;
+
+        let cell = maze.get_cell(self.snail.pos.x, self.snail.pos.y);
+
+        if self.following {
+            let left = self.snail.direction.rotate_counter();
+
+            // hold onto the wall with the left hand, counting every turn
+            if !cell.has_wall(left) {
+                self.snail.direction = left;
+                self.turns += 1;
+            } else {
+                while cell.has_wall(self.snail.direction) {
+                    self.snail.direction = self.snail.direction.rotate();
+                    self.turns -= 1;
+                }
+            }
+
+            if self.turns == 0 && !self.gave_up() {
+                self.following = false;
+            }
+        } else {
+            self.snail.direction = self.preferred;
+
+            // ran into a wall, turn right until there's a way forward and start following it
+            while cell.has_wall(self.snail.direction) {
+                self.snail.direction = self.snail.direction.rotate();
+                self.turns -= 1;
+                self.following = true;
+            }
+        }
+
+        self.snail.move_forward(maze);
+        self.terrain_cost = maze.get_terrain(self.snail.pos.x, self.snail.pos.y).cost();
+
+        if self.snail.pos == maze.end_pos {
+            SolveStatus::Solved(1)
+        } else {
+            SolveStatus::None
+        }
+    }
+
+    fn movement_time(&self) -> f32 {
+        let mut time = SNAIL_MOVEMENT_TIME * self.terrain_cost;
+
+        // protractor
+        if (self.upgrades & 0b1) != 0 {
+            time *= 0.8;
+        }
+
+        // sprint
+        if (self.upgrades & 0b100) != 0 && !self.following {
+            time *= 0.5;
+        }
+
+        time
+    }
+
+    // moving walls would throw off the count of turns, which is what gets the snail out from
+    // around an island, so there's no SHIFTING_MODIFIER
+    fn supported_modifiers() -> u32 {
+        TERRAIN_MODIFIER | TORUS_MODIFIER
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pledge;
+    use crate::{
+        ascii::Board,
+        rng::Xoshiro256,
+        solvers::{SolveStatus, Solver},
+    };
+
+    // the snail starts off right up against a solid block in the middle of the maze
+    const ISLAND: &str = concat!(
+        "###############\n",
+        "#            G#\n",
+        "#             #\n",
+        "#             #\n",
+        "#   #######   #\n",
+        "#   #######   #\n",
+        "#   #######   #\n",
+        "#  S#######   #\n",
+        "#   #######   #\n",
+        "#   #######   #\n",
+        "#   #######   #\n",
+        "#             #\n",
+        "#             #\n",
+        "#             #\n",
+        "###############\n",
+    );
+
+    #[test]
+    fn escapes_an_island() {
+        let mut rng = Xoshiro256::new(0xBEEF);
+        let mut maze = Board::<7>::parse(ISLAND).unwrap().maze;
+        let mut pledge = Pledge::new();
+        pledge.setup(&maze, &mut rng);
+
+        let solved =
+            (0..500).any(|_| matches!(pledge.step(&mut maze, &mut rng), SolveStatus::Solved(_)));
+        assert!(solved);
+
+        // it got there by letting go of the island, not by giving up
+        assert!(!pledge.gave_up());
+    }
+}
@@ -0,0 +1,288 @@
+use std::{cmp::Ordering, convert::TryInto};
+
+use crate::{
+    direction::Direction,
+    image::Image,
+    maze::{
+        Maze, CELLS_PER_IDX, PORTAL_MODIFIER, SHIFTING_MODIFIER, SNAIL_MOVEMENT_TIME,
+        TERRAIN_MODIFIER, TORUS_MODIFIER,
+    },
+    rng::Rng,
+    snail::{Snail, DEFAULT_PALETTE},
+    solvers::Solver,
+};
+
+use super::SolveStatus;
+
+// a state is the walls around the snail's cell, the 4 bits of its MazeCell, and whether the goal
+// is to the left of, level with or to the right of it and above, level with or below it. nothing
+// in it is specific to one maze, so whatever the snail learns carries over to the next one. moves
+// are up, down, left and right, like Direction.
+const CELL_STATES: usize = 16;
+const GOAL_STATES: usize = 9;
+const STATES: usize = CELL_STATES * GOAL_STATES;
+
+// how far each estimate moves towards what was just seen
+const LEARNING_RATE: f32 = 0.1;
+// how much less a reward is worth for every move it is away
+const DISCOUNT: f32 = 0.6;
+// how strongly the snail prefers moves it thinks are better. lots of cells look the same, so
+// always taking the best looking move would send the snail round in circles, instead a move
+// which is estimated to be worth TEMPERATURE more is e times as likely to be picked
+const TEMPERATURE: f32 = 5.0;
+
+/// Q-Learning Snail Upgrades:
+/// - Good Memory: Q-Learning Snail learns 50% more from every move.
+/// - Confidence:  Q-Learning Snail trusts what it has learned 25% more.
+/// - Track Shoes: Q-Learning Snail moves 25% faster.
+
+pub struct QLearning<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    snail: Snail<S>,
+    upgrades: u32,
+    terrain_cost: f32,
+
+    // the estimated total reward of taking each move in each state, see QLearning::step. it only
+    // depends on what the snail can see around it, so it is kept between mazes, and saved with
+    // Solver::snapshot
+    q: [[f32; 4]; STATES],
+
+    // moves taken so far in this maze, and in the last maze which was solved
+    steps: usize,
+    last_steps: usize,
+}
+
+impl<const S: usize> QLearning<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    // moves taken to solve the last maze, or 0 if none has been solved yet
+    pub fn last_steps(&self) -> usize {
+        self.last_steps
+    }
+
+    fn state(&self, maze: &Maze<S>) -> usize {
+        let pos = self.snail.pos;
+        let cell = maze.get_cell(pos.x, pos.y);
+
+        let sign = |a: usize, b: usize| match a.cmp(&b) {
+            Ordering::Less => 0,
+            Ordering::Equal => 1,
+            Ordering::Greater => 2,
+        };
+        let goal = sign(maze.end_pos.y, pos.y) * 3 + sign(maze.end_pos.x, pos.x);
+
+        cell.0 * GOAL_STATES + goal
+    }
+
+    // the moves which aren't into a wall
+    fn options(&self, maze: &Maze<S>) -> Vec<usize> {
+        let cell = maze.get_cell(self.snail.pos.x, self.snail.pos.y);
+        let behind = self.snail.direction.flip() as usize;
+
+        let options: Vec<usize> = (0..4)
+            .filter(|direction| !cell.has_wall(Direction::from_number(*direction)))
+            .collect();
+
+        // turning around is only an option at a dead end
+        if options.len() > 1 {
+            options
+                .into_iter()
+                .filter(|direction| *direction != behind)
+                .collect()
+        } else {
+            options
+        }
+    }
+
+    fn learning_rate(&self) -> f32 {
+        // good memory
+        if (self.upgrades & 0b1) != 0 {
+            1.5 * LEARNING_RATE
+        } else {
+            LEARNING_RATE
+        }
+    }
+
+    fn temperature(&self) -> f32 {
+        // confidence
+        if (self.upgrades & 0b10) != 0 {
+            TEMPERATURE / 1.25
+        } else {
+            TEMPERATURE
+        }
+    }
+
+    // how likely each of the options is to be picked, adding up to 1
+    fn chances(&self, state: usize, options: &[usize]) -> Vec<f32> {
+        let best = self.best_value(state, options);
+        let temperature = self.temperature();
+
+        let weights: Vec<f32> = options
+            .iter()
+            .map(|direction| ((self.q[state][*direction] - best) / temperature).exp())
+            .collect();
+        let total: f32 = weights.iter().sum();
+
+        weights.iter().map(|weight| weight / total).collect()
+    }
+
+    fn pick_move(&self, state: usize, options: &[usize], rng: &mut dyn Rng) -> usize {
+        let mut roll = (rng.big() % 1_000_000) as f32 / 1_000_000.0;
+
+        for (direction, chance) in options.iter().zip(self.chances(state, options)) {
+            if roll < chance {
+                return *direction;
+            }
+            roll -= chance;
+        }
+
+        options[options.len() - 1]
+    }
+
+    // what the snail expects to get from here on, given how it picks its moves
+    fn expected_value(&self, state: usize, options: &[usize]) -> f32 {
+        options
+            .iter()
+            .zip(self.chances(state, options))
+            .map(|(direction, chance)| self.q[state][*direction] * chance)
+            .sum()
+    }
+
+    fn best_value(&self, state: usize, options: &[usize]) -> f32 {
+        options
+            .iter()
+            .map(|direction| self.q[state][*direction])
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+}
+
+impl<const S: usize> Solver<S> for QLearning<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn new() -> Self {
+        QLearning {
+            snail: Snail::new(),
+            upgrades: 0,
+            terrain_cost: 1.0,
+            q: [[0.0; 4]; STATES],
+            steps: 0,
+            last_steps: 0,
+        }
+    }
+
+    fn set_upgrades(&mut self, upgrades: u32) {
+        self.upgrades = upgrades;
+    }
+
+    fn draw(
+        &mut self,
+        animation_cycle: bool,
+        movement_timer: f32,
+        _rng: &mut dyn Rng,
+        image: &mut Image,
+        bx: usize,
+        by: usize,
+    ) {
+        image.draw_text(
+            &format!("steps:{}", self.steps),
+            bx + 2,
+            by + 1 + S * 10 - 6,
+        );
+        image.draw_text(
+            &format!("last:{}", self.last_steps),
+            bx + 2,
+            by + 1 + S * 10 - 11,
+        );
+
+        self.snail.draw(
+            DEFAULT_PALETTE,
+            animation_cycle,
+            movement_timer / self.movement_time(),
+            image,
+            bx,
+            by,
+        );
+    }
+
+    fn setup(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
+        self.snail.place(maze.start_pos);
+        self.snail.direction = Direction::Right;
+        self.terrain_cost = 1.0;
+        self.steps = 0;
+    }
+
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
+        let state = self.state(maze);
+        let options = self.options(maze);
+
+        let direction = self.pick_move(state, &options, rng);
+
+        let distance = self.snail.pos.manhattan_dist(maze.end_pos) as f32;
+
+        self.snail.direction = Direction::from_number(direction);
+        self.snail.move_forward(maze);
+        self.steps += 1;
+
+        self.terrain_cost = maze.get_terrain(self.snail.pos.x, self.snail.pos.y).cost();
+
+        // every move costs however long it takes, and earns back however much closer it got to
+        // the goal as the crow flies, which gives the snail something to go on long before it
+        // first finds the goal. the estimate is updated towards the moves the snail actually
+        // makes (expected sarsa), not towards the best looking ones, since it doesn't always take
+        // those.
+        let progress = distance - self.snail.pos.manhattan_dist(maze.end_pos) as f32;
+        let reward = progress - self.terrain_cost;
+        let future = if self.snail.pos == maze.end_pos {
+            0.0
+        } else {
+            self.expected_value(self.state(maze), &self.options(maze))
+        };
+
+        let learning_rate = self.learning_rate();
+        let estimate = &mut self.q[state][direction];
+        *estimate += learning_rate * (reward + DISCOUNT * future - *estimate);
+
+        if self.snail.pos == maze.end_pos {
+            self.last_steps = self.steps;
+            SolveStatus::Solved(1)
+        } else {
+            SolveStatus::None
+        }
+    }
+
+    fn movement_time(&self) -> f32 {
+        // track shoes
+        if (self.upgrades & 0b100) != 0 {
+            SNAIL_MOVEMENT_TIME * 0.75 * self.terrain_cost
+        } else {
+            SNAIL_MOVEMENT_TIME * self.terrain_cost
+        }
+    }
+
+    fn snapshot(&self) -> Vec<u8> {
+        self.q
+            .iter()
+            .flatten()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    fn restore(&mut self, data: &[u8]) {
+        // a snapshot from a different version of the table is thrown away
+        if data.len() != STATES * 4 * 4 {
+            return;
+        }
+
+        for (value, bytes) in self.q.iter_mut().flatten().zip(data.chunks_exact(4)) {
+            *value = f32::from_le_bytes(bytes.try_into().unwrap());
+        }
+    }
+
+    fn supported_modifiers() -> u32 {
+        TERRAIN_MODIFIER | PORTAL_MODIFIER | SHIFTING_MODIFIER | TORUS_MODIFIER
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     image::Image,
-    lfsr::LFSR,
     maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    rng::Rng,
     snail::{Snail, DEFAULT_PALETTE},
     solvers::Solver,
     utils::{lerpi, Vec2},
@@ -61,7 +61,7 @@ where
         &mut self,
         animation_cycle: bool,
         movement_timer: f32,
-        _lfsr: &mut LFSR,
+        _rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
@@ -130,20 +130,20 @@ where
         }
     }
 
-    fn setup(&mut self, _maze: &Maze<S>, _lfsr: &mut LFSR) {
+    fn setup(&mut self, _maze: &Maze<S>, _rng: &mut dyn Rng) {
         self.snail.reset();
         self.teleport_bounds = Vec2 { y: S, x: S };
         self.prev_teleport_bounds = self.teleport_bounds;
     }
 
-    fn step(&mut self, maze: &mut Maze<S>, lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
         self.snail.prev_pos.x = self.snail.pos.x;
         self.snail.prev_pos.y = self.snail.pos.y;
         self.teleport_timer += SNAIL_MOVEMENT_TIME;
         if self.teleport_timer >= self.teleportation_time() {
             self.teleport_timer = 0.0;
-            self.snail.pos.x = S - (lfsr.big() % self.teleport_bounds.x) - 1;
-            self.snail.pos.y = S - (lfsr.big() % self.teleport_bounds.y) - 1;
+            self.snail.pos.x = S - (rng.big() % self.teleport_bounds.x) - 1;
+            self.snail.pos.y = S - (rng.big() % self.teleport_bounds.y) - 1;
 
             self.prev_teleport_bounds = self.teleport_bounds;
 
@@ -175,4 +175,8 @@ where
     fn movement_time(&self) -> f32 {
         SNAIL_MOVEMENT_TIME
     }
+
+    fn supports_masks() -> bool {
+        false
+    }
 }
@@ -1,8 +1,11 @@
 use crate::{
     direction::Direction,
     image::Image,
-    lfsr::LFSR,
-    maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    maze::{
+        Maze, CELLS_PER_IDX, PORTAL_MODIFIER, PREDATOR_MODIFIER, SHIFTING_MODIFIER,
+        SNAIL_MOVEMENT_TIME, TERRAIN_MODIFIER, TORUS_MODIFIER,
+    },
+    rng::Rng,
     snail::{Snail, DEFAULT_PALETTE},
     solvers::Solver,
 };
@@ -21,6 +24,8 @@ where
     snail: Snail<S>,
     directions: [Option<Direction>; S * S],
     upgrades: u32,
+    terrain_cost: f32,
+    palette: [[u8; 3]; 6],
 }
 
 impl<const S: usize> Solver<S> for RandomWalk<S>
@@ -32,6 +37,8 @@ where
             snail: Snail::new(),
             directions: [None; S * S],
             upgrades: 0,
+            terrain_cost: 1.0,
+            palette: DEFAULT_PALETTE,
         }
     }
 
@@ -43,13 +50,13 @@ where
         &mut self,
         animation_cycle: bool,
         movement_timer: f32,
-        _lfsr: &mut LFSR,
+        _rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
     ) {
         self.snail.draw(
-            DEFAULT_PALETTE,
+            self.palette,
             animation_cycle,
             movement_timer / self.movement_time(),
             image,
@@ -58,25 +65,35 @@ where
         );
     }
 
-    fn setup(&mut self, maze: &Maze<S>, _lfsr: &mut LFSR) {
-        self.snail.reset();
+    fn setup(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
+        self.snail.place(maze.start_pos);
+        self.terrain_cost = 1.0;
         self.directions = maze.get_directions(maze.end_pos);
     }
 
-    fn step(&mut self, maze: &mut Maze<S>, lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
         // chance to move in the right direction based on the upgrades provided
         let chance = (self.upgrades & 0b1)
             + (self.upgrades & 0b10)
             + ((self.upgrades & 0b100) >> 1)
             + ((self.upgrades & 0b100) >> 2);
 
-        if (lfsr.big() % 10) < chance as usize {
+        if (rng.big() % 10) < chance as usize {
             self.snail.direction =
                 self.directions[self.snail.pos.y * S + self.snail.pos.x].unwrap();
             self.snail.move_forward(maze);
         } else {
+            // only walks into a predator if every other way is blocked
+            let pos = self.snail.pos;
+            let cell = maze.get_cell(pos.x, pos.y);
+            let safe: Vec<Direction> = cell
+                .valid_directions()
+                .into_iter()
+                .filter(|dir| !maze.has_predator(maze.next_pos(pos, *dir)))
+                .collect();
+
             loop {
-                match lfsr.next() {
+                match rng.next() {
                     0 => self.snail.direction = Direction::Up,
                     1 => self.snail.direction = Direction::Down,
                     2 => self.snail.direction = Direction::Left,
@@ -84,12 +101,20 @@ where
                     _ => unreachable!(),
                 }
 
+                if !safe.is_empty() && !safe.contains(&self.snail.direction) {
+                    continue;
+                }
+
                 if self.snail.move_forward(maze) {
                     break;
                 }
             }
         }
 
+        self.terrain_cost = maze
+            .get_terrain(self.snail.pos.x, self.snail.pos.y)
+            .cost();
+
         if self.snail.pos == maze.end_pos {
             SolveStatus::Solved(1)
         } else {
@@ -97,7 +122,23 @@ where
         }
     }
 
+    fn maze_changed(&mut self, maze: &Maze<S>) {
+        self.directions = maze.get_directions(maze.end_pos);
+    }
+
     fn movement_time(&self) -> f32 {
-        SNAIL_MOVEMENT_TIME
+        SNAIL_MOVEMENT_TIME * self.terrain_cost
+    }
+
+    fn snail(&self) -> Option<&Snail<S>> {
+        Some(&self.snail)
+    }
+
+    fn set_palette(&mut self, palette: [[u8; 3]; 6]) {
+        self.palette = palette;
+    }
+
+    fn supported_modifiers() -> u32 {
+        TERRAIN_MODIFIER | PORTAL_MODIFIER | SHIFTING_MODIFIER | TORUS_MODIFIER | PREDATOR_MODIFIER
     }
 }
@@ -1,8 +1,11 @@
 use crate::{
     direction::Direction,
     image::Image,
-    lfsr::LFSR,
-    maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    maze::{
+        Maze, CELLS_PER_IDX, DOOR_MODIFIER, PORTAL_MODIFIER, SHIFTING_MODIFIER,
+        SNAIL_MOVEMENT_TIME, TORUS_MODIFIER,
+    },
+    rng::Rng,
     snail::{Snail, DEFAULT_PALETTE, GRAYSCALE_PALETTE},
     solvers::Solver,
     utils::Vec2,
@@ -25,19 +28,30 @@ where
     directions: [Option<Direction>; S * S],
 
     current_sequence: Vec<Direction>,
+
+    // whether current_sequence leads to a key rather than a lost snail or the goal
+    heading_to_key: bool,
+
+    // cells which could be reached from the start when the maze was generated, and whether the
+    // party has given up on recruitment to look for keys
+    reachable: Vec<bool>,
+    searching_for_keys: bool,
 }
 
 impl<const S: usize> Rpg<S>
 where
     [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
 {
-    fn generate_lost_snails(&mut self, lfsr: &mut LFSR) {
+    fn generate_lost_snails(&mut self, maze: &Maze<S>, rng: &mut dyn Rng) {
         for _ in 0..(S / 2) {
-            let mut x = 0;
-            let mut y = 0;
-            while (x == 0 && y == 0) || (x == S - 1 && y == S - 1) {
-                x = lfsr.big() % S;
-                y = lfsr.big() % S;
+            let mut x = maze.start_pos.x;
+            let mut y = maze.start_pos.y;
+            while (Vec2 { x, y }) == maze.start_pos
+                || (Vec2 { x, y }) == maze.end_pos
+                || !maze.is_valid(x, y)
+            {
+                x = rng.big() % S;
+                y = rng.big() % S;
             }
 
             let mut new_snail = Snail::new();
@@ -60,6 +74,9 @@ where
 
             directions: [None; S * S],
             current_sequence: vec![],
+            heading_to_key: false,
+            reachable: vec![],
+            searching_for_keys: false,
             upgrades: 0,
         }
     }
@@ -72,7 +89,7 @@ where
         &mut self,
         animation_cycle: bool,
         movement_timer: f32,
-        _lfsr: &mut LFSR,
+        _rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
@@ -100,32 +117,59 @@ where
         }
     }
 
-    fn setup(&mut self, maze: &Maze<S>, lfsr: &mut LFSR) {
+    fn setup(&mut self, maze: &Maze<S>, rng: &mut dyn Rng) {
         self.lost.clear();
         self.party.clear();
 
-        self.party.push(Snail::new());
-        self.generate_lost_snails(lfsr);
+        let mut leader = Snail::new();
+        leader.place(maze.start_pos);
+        self.party.push(leader);
+        self.generate_lost_snails(maze, rng);
+
+        self.current_sequence.clear();
+        self.heading_to_key = false;
+        self.searching_for_keys = false;
+
+        self.reachable = vec![false; S * S];
+        for pos in maze.reachable_cells(maze.start_pos) {
+            self.reachable[pos.y * S + pos.x] = true;
+        }
 
         if (self.upgrades & 0b100) != 0 {
-            self.directions = maze.get_directions(Vec2 { x: 0, y: 0 });
+            self.directions = maze.get_directions(maze.start_pos);
         }
     }
 
-    fn step(&mut self, maze: &mut Maze<S>, lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
+        // snails behind a locked door can't come on their own, so once only those are left the
+        // party goes out to find the keys
+        if (self.upgrades & 0b100) != 0
+            && !self.lost.is_empty()
+            && self
+                .lost
+                .iter()
+                .all(|snail| !self.reachable[snail.pos.y * S + snail.pos.x])
+        {
+            self.searching_for_keys = true;
+        }
+
         // recruitment
-        if (self.upgrades & 0b100) != 0 && !self.lost.is_empty() {
-            if !(self.party[0].pos.x == 0 && self.party[0].pos.y == 0) {
-                self.setup(maze, lfsr);
+        if (self.upgrades & 0b100) != 0 && !self.lost.is_empty() && !self.searching_for_keys {
+            if self.party[0].pos != maze.start_pos {
+                self.setup(maze, rng);
                 return SolveStatus::None;
             }
 
             for lost_snail in &mut self.lost {
+                if !self.reachable[lost_snail.pos.y * S + lost_snail.pos.x] {
+                    continue;
+                }
+
                 lost_snail.direction =
                     match self.directions[lost_snail.pos.y * S + lost_snail.pos.x] {
                         Some(x) => x,
                         None => {
-                            self.setup(maze, lfsr);
+                            self.setup(maze, rng);
                             return SolveStatus::None;
                         }
                     };
@@ -144,16 +188,24 @@ where
                 self.party.push(snail);
             }
         } else {
+            let mut key_collected = false;
+
             if self.current_sequence.is_empty() {
-                if let Some(last) = self.lost.last() {
-                    self.current_sequence =
-                        maze.get_solve_sequence(self.party[0].pos.x, self.party[0].pos.y, last.pos);
-                } else {
-                    self.current_sequence = maze.get_solve_sequence(
-                        self.party[0].pos.x,
-                        self.party[0].pos.y,
-                        maze.end_pos,
-                    );
+                let leader = self.party[0].pos;
+                let target = match self.lost.last() {
+                    Some(last) => last.pos,
+                    None => maze.end_pos,
+                };
+
+                self.current_sequence = maze.get_solve_sequence(leader.x, leader.y, target);
+                self.heading_to_key = false;
+
+                // the target is behind a locked door, so get a key first
+                if self.current_sequence.is_empty() && leader != target {
+                    if let Some(key) = maze.nearest_key(leader) {
+                        self.current_sequence = maze.get_solve_sequence(leader.x, leader.y, key);
+                        self.heading_to_key = true;
+                    }
                 }
 
                 self.current_sequence.reverse();
@@ -165,6 +217,7 @@ where
                     next_move = self.party[0].direction;
                     self.party[0].direction = tmp;
                     self.party[0].move_forward(maze);
+                    let leader = self.party[0].pos;
                     let mut next_pos = self.party[0].prev_pos;
 
                     for follower in self.party.iter_mut().skip(1) {
@@ -176,7 +229,7 @@ where
                         next_move = tmp;
                     }
 
-                    if self.current_sequence.is_empty() {
+                    if self.current_sequence.is_empty() && !self.heading_to_key {
                         if let Some(mut new_follower) = self.lost.pop() {
                             new_follower.pos = self.party[0].prev_pos;
                             self.party.push(new_follower);
@@ -185,6 +238,12 @@ where
                         }
                     }
 
+                    // a door has opened, so there might be a better way to the target now
+                    if maze.visit(leader.x, leader.y).is_some() {
+                        self.current_sequence.clear();
+                        key_collected = true;
+                    }
+
                     // sidequests
                     if (self.upgrades & 0b10) != 0 && !self.party.is_empty() {
                         let target_pos = self.party[0].pos;
@@ -205,11 +264,35 @@ where
             if self.party.is_empty() && self.lost.is_empty() {
                 return SolveStatus::Solved(1);
             }
+
+            if key_collected {
+                return SolveStatus::KeyCollected;
+            }
         }
 
         SolveStatus::None
     }
 
+    fn maze_changed(&mut self, maze: &Maze<S>) {
+        self.current_sequence.clear();
+        self.heading_to_key = false;
+
+        if let Some(leader) = self.party.first() {
+            self.reachable = vec![false; S * S];
+            for pos in maze.reachable_cells(leader.pos) {
+                self.reachable[pos.y * S + pos.x] = true;
+            }
+        }
+
+        if (self.upgrades & 0b100) != 0 {
+            self.directions = maze.get_directions(maze.start_pos);
+        }
+    }
+
+    fn supported_modifiers() -> u32 {
+        DOOR_MODIFIER | PORTAL_MODIFIER | SHIFTING_MODIFIER | TORUS_MODIFIER
+    }
+
     fn movement_time(&self) -> f32 {
         // Comradery
         if (self.upgrades & 0b1) != 0 {
@@ -0,0 +1,271 @@
+use crate::{
+    direction::Direction,
+    image::Image,
+    maze::{
+        Maze, CELLS_PER_IDX, PORTAL_MODIFIER, SHIFTING_MODIFIER, SNAIL_MOVEMENT_TIME,
+        TERRAIN_MODIFIER, TORUS_MODIFIER,
+    },
+    rng::Rng,
+    snail::{Snail, DEFAULT_PALETTE},
+    solvers::Solver,
+    vm::{Body, Program, Side, Vm, VmError, STACK_SIZE},
+};
+
+use super::SolveStatus;
+
+// what every snail runs until the player gives it something else, see vm.rs for the language
+pub const DEFAULT_PROGRAM: &str = "\
+# keeps its left hand on the wall
+start:
+    wall left
+    jumpif blocked
+    turn left
+    move
+    jump start
+
+blocked:
+    wall ahead
+    not
+    jumpif forward
+    turn right
+    jump start
+
+forward:
+    move
+";
+
+// [one mark, more than one mark]
+const MARK_COLORS: [[u8; 3]; 2] = [[0x00, 0xFF, 0x00], [0xFF, 0x00, 0x00]];
+
+// what the program sees and changes while it's running
+struct Surroundings<'a, const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    snail: &'a mut Snail<S>,
+    marks: &'a mut [u8; S * S],
+    maze: &'a Maze<S>,
+}
+
+impl<'a, const S: usize> Surroundings<'a, S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn direction(&self, side: Side) -> Direction {
+        match side {
+            Side::Here | Side::Ahead => self.snail.direction,
+            Side::Left => self.snail.direction.rotate_counter(),
+            Side::Right => self.snail.direction.rotate(),
+            Side::Behind => self.snail.direction.flip(),
+        }
+    }
+}
+
+impl<'a, const S: usize> Body for Surroundings<'a, S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn wall(&self, side: Side) -> bool {
+        let pos = self.snail.pos;
+        self.maze
+            .get_cell(pos.x, pos.y)
+            .has_wall(self.direction(side))
+    }
+
+    fn marks(&self, side: Side) -> usize {
+        let pos = self.snail.pos;
+
+        let pos = if side == Side::Here {
+            pos
+        } else if self.wall(side) {
+            return 0;
+        } else {
+            self.maze.next_pos(pos, self.direction(side))
+        };
+
+        self.marks[pos.y * S + pos.x] as usize
+    }
+
+    fn mark(&mut self) {
+        let pos = self.snail.pos;
+        let marks = &mut self.marks[pos.y * S + pos.x];
+        *marks = marks.saturating_add(1);
+    }
+
+    fn turn(&mut self, side: Side) {
+        self.snail.direction = self.direction(side);
+    }
+}
+
+/// Scripted Snail Upgrades:
+/// - Extra Memory: Scripted Snail's stack holds four times as many values.
+/// - Watchdog:     When Scripted Snail's program crashes, it starts over in a new maze.
+/// - Overclock:    Scripted Snail moves 25% faster.
+
+pub struct Scripted<const S: usize>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    snail: Snail<S>,
+    upgrades: u32,
+    terrain_cost: f32,
+
+    program: Program,
+    vm: Vm,
+    marks: [u8; S * S],
+
+    // the last thing that went wrong with the program, kept until it's replaced, and whether it
+    // has stopped the program in this maze
+    error: Option<VmError>,
+    crashed: bool,
+}
+
+impl<const S: usize> Scripted<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn stack_size(&self) -> usize {
+        // extra memory
+        if (self.upgrades & 0b1) != 0 {
+            4 * STACK_SIZE
+        } else {
+            STACK_SIZE
+        }
+    }
+}
+
+impl<const S: usize> Solver<S> for Scripted<S>
+where
+    [usize; (S * S) / CELLS_PER_IDX + 1]: Sized,
+{
+    fn new() -> Self {
+        Scripted {
+            snail: Snail::new(),
+            upgrades: 0,
+            terrain_cost: 1.0,
+            program: Program::compile(DEFAULT_PROGRAM).unwrap(),
+            vm: Vm::default(),
+            marks: [0; S * S],
+            error: None,
+            crashed: false,
+        }
+    }
+
+    fn set_upgrades(&mut self, upgrades: u32) {
+        self.upgrades = upgrades;
+    }
+
+    fn draw(
+        &mut self,
+        animation_cycle: bool,
+        movement_timer: f32,
+        _rng: &mut dyn Rng,
+        image: &mut Image,
+        bx: usize,
+        by: usize,
+    ) {
+        for (i, marks) in self.marks.iter().enumerate() {
+            if *marks > 0 {
+                let color = MARK_COLORS[(*marks > 1) as usize];
+                image.draw_rectangle_with(
+                    (i % S) * 10 + 2,
+                    (i / S) * 10 + 2,
+                    2,
+                    2,
+                    || color,
+                    bx,
+                    by,
+                );
+            }
+        }
+
+        if let (true, Some(error)) = (self.crashed, self.error) {
+            image.draw_text("crashed", bx + 2, by + 1 + S * 10 - 11);
+            image.draw_text(
+                &format!("line:{}", error.line()),
+                bx + 2,
+                by + 1 + S * 10 - 6,
+            );
+        }
+
+        self.snail.draw(
+            DEFAULT_PALETTE,
+            animation_cycle,
+            movement_timer / self.movement_time(),
+            image,
+            bx,
+            by,
+        );
+    }
+
+    fn setup(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
+        self.snail.place(maze.start_pos);
+        self.snail.direction = Direction::Right;
+        self.terrain_cost = 1.0;
+        self.marks = [0; S * S];
+        self.vm.reset();
+        self.crashed = false;
+    }
+
+    fn step(&mut self, maze: &mut Maze<S>, _rng: &mut dyn Rng) -> SolveStatus {
+        self.snail.place(self.snail.pos);
+
+        if self.crashed {
+            // watchdog
+            if (self.upgrades & 0b10) != 0 {
+                return SolveStatus::Solved(0);
+            }
+
+            return SolveStatus::None;
+        }
+
+        let stack_size = self.stack_size();
+        let mut surroundings = Surroundings {
+            snail: &mut self.snail,
+            marks: &mut self.marks,
+            maze,
+        };
+
+        if let Err(error) = self.vm.run(&self.program, &mut surroundings, stack_size) {
+            self.error = Some(error);
+            self.crashed = true;
+
+            return SolveStatus::None;
+        }
+
+        // moving into a wall still uses up the snail's turn
+        self.snail.move_forward(maze);
+        self.terrain_cost = maze.get_terrain(self.snail.pos.x, self.snail.pos.y).cost();
+
+        if self.snail.pos == maze.end_pos {
+            SolveStatus::Solved(1)
+        } else {
+            SolveStatus::None
+        }
+    }
+
+    fn movement_time(&self) -> f32 {
+        // overclock
+        if (self.upgrades & 0b100) != 0 {
+            SNAIL_MOVEMENT_TIME * 0.75 * self.terrain_cost
+        } else {
+            SNAIL_MOVEMENT_TIME * self.terrain_cost
+        }
+    }
+
+    // the program starts over from the top, wherever the snail is
+    fn set_program(&mut self, program: &Program) {
+        self.program = program.clone();
+        self.vm.reset();
+        self.error = None;
+        self.crashed = false;
+    }
+
+    fn program_error(&self) -> Option<VmError> {
+        self.error
+    }
+
+    fn supported_modifiers() -> u32 {
+        TERRAIN_MODIFIER | PORTAL_MODIFIER | SHIFTING_MODIFIER | TORUS_MODIFIER
+    }
+}
@@ -1,8 +1,8 @@
 use crate::{
     direction::Direction,
     image::Image,
-    lfsr::LFSR,
     maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    rng::Rng,
     snail::{Snail, DEFAULT_PALETTE, INVERTED_PALETTE, PHASE_2_PALETTE},
     solvers::Solver,
     utils::{lerpi, Vec2},
@@ -37,7 +37,7 @@ impl TelepathyBall {
         }
     }
 
-    fn draw(&self, image: &mut Image, progress: f32, lfsr: &mut LFSR, bx: usize, by: usize) {
+    fn draw(&self, image: &mut Image, progress: f32, rng: &mut dyn Rng, bx: usize, by: usize) {
         let x = lerpi(
             10 * (self.prev_pos.x as i32),
             10 * (self.pos.x as i32),
@@ -52,7 +52,7 @@ impl TelepathyBall {
             + 5;
 
         let random_color = || {
-            if lfsr.next() < 2 {
+            if rng.next() < 2 {
                 [0x55, 0xaa, 0xff]
             } else {
                 [0xff, 0xff, 0xff]
@@ -167,7 +167,7 @@ where
         &mut self,
         animation_cycle: bool,
         movement_timer: f32,
-        lfsr: &mut LFSR,
+        rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
@@ -185,13 +185,13 @@ where
         );
 
         if self.ball_sequence_index < self.ball_sequence.len() {
-            self.forward_ball.draw(image, progress, lfsr, bx, by);
+            self.forward_ball.draw(image, progress, rng, bx, by);
         }
 
         self.goal.draw(image, animation_cycle, progress, bx, by);
     }
 
-    fn setup(&mut self, _maze: &Maze<S>, lfsr: &mut LFSR) {
+    fn setup(&mut self, _maze: &Maze<S>, rng: &mut dyn Rng) {
         self.snail.reset();
         self.forward_ball.pos.x = 0;
         self.forward_ball.pos.y = 0;
@@ -212,7 +212,7 @@ where
             } else if down_moves == S - 1 {
                 self.ball_sequence.push(Direction::Right)
             } else {
-                if lfsr.next() < 2 {
+                if rng.next() < 2 {
                     self.ball_sequence.push(Direction::Right);
                     right_moves += 1;
                 } else {
@@ -223,7 +223,7 @@ where
         }
     }
 
-    fn step(&mut self, maze: &mut Maze<S>, _lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, maze: &mut Maze<S>, _rng: &mut dyn Rng) -> SolveStatus {
         let mut rerender = false;
 
         if self.ball_sequence_index < self.ball_sequence.len() {
@@ -365,6 +365,10 @@ where
         true
     }
 
+    fn supports_masks() -> bool {
+        false
+    }
+
     fn palette() -> [[u8; 3]; 6] {
         PHASE_2_PALETTE
     }
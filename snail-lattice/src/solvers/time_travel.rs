@@ -1,8 +1,8 @@
 use crate::{
     direction::Direction,
     image::Image,
-    lfsr::LFSR,
     maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    rng::Rng,
     snail::{Snail, DEFAULT_PALETTE, GRAYSCALE_PALETTE},
     solvers::Solver,
     utils::Vec2,
@@ -10,8 +10,8 @@ use crate::{
 
 use super::{SolveStatus, Tremaux};
 
-fn random_color(lfsr: &mut LFSR) -> [u8; 3] {
-    if lfsr.next() == 3 {
+fn random_color(rng: &mut dyn Rng) -> [u8; 3] {
+    if rng.next() == 3 {
         [0xFF, 0x00, 0x00]
     } else {
         [0x00, 0x00, 0x00]
@@ -24,7 +24,7 @@ struct PathTile {
 }
 
 impl PathTile {
-    fn draw(&self, lfsr: &mut LFSR, image: &mut Image, bx: usize, by: usize) {
+    fn draw(&self, rng: &mut dyn Rng, image: &mut Image, bx: usize, by: usize) {
         match self.directions {
             // up down
             [true, true, false, false] => {
@@ -33,7 +33,7 @@ impl PathTile {
                     self.pos.y * 10,
                     3,
                     10,
-                    || random_color(lfsr),
+                    || random_color(rng),
                     bx,
                     by,
                 );
@@ -45,7 +45,7 @@ impl PathTile {
                     self.pos.y * 10 + 4,
                     10,
                     3,
-                    || random_color(lfsr),
+                    || random_color(rng),
                     bx,
                     by,
                 );
@@ -58,7 +58,7 @@ impl PathTile {
                     self.pos.y * 10,
                     3,
                     7,
-                    || random_color(lfsr),
+                    || random_color(rng),
                     bx,
                     by,
                 );
@@ -68,7 +68,7 @@ impl PathTile {
                     self.pos.y * 10 + 4,
                     4,
                     3,
-                    || random_color(lfsr),
+                    || random_color(rng),
                     bx,
                     by,
                 );
@@ -81,7 +81,7 @@ impl PathTile {
                     self.pos.y * 10,
                     3,
                     7,
-                    || random_color(lfsr),
+                    || random_color(rng),
                     bx,
                     by,
                 );
@@ -91,7 +91,7 @@ impl PathTile {
                     self.pos.y * 10 + 4,
                     4,
                     3,
-                    || random_color(lfsr),
+                    || random_color(rng),
                     bx,
                     by,
                 );
@@ -104,7 +104,7 @@ impl PathTile {
                     self.pos.y * 10 + 4,
                     3,
                     7,
-                    || random_color(lfsr),
+                    || random_color(rng),
                     bx,
                     by,
                 );
@@ -114,7 +114,7 @@ impl PathTile {
                     self.pos.y * 10 + 4,
                     4,
                     3,
-                    || random_color(lfsr),
+                    || random_color(rng),
                     bx,
                     by,
                 );
@@ -127,7 +127,7 @@ impl PathTile {
                     self.pos.y * 10 + 4,
                     3,
                     7,
-                    || random_color(lfsr),
+                    || random_color(rng),
                     bx,
                     by,
                 );
@@ -137,7 +137,7 @@ impl PathTile {
                     self.pos.y * 10 + 4,
                     4,
                     3,
-                    || random_color(lfsr),
+                    || random_color(rng),
                     bx,
                     by,
                 );
@@ -204,18 +204,18 @@ where
         self.upgrades = upgrades;
     }
 
-    fn setup(&mut self, _maze: &Maze<S>, _lfsr: &mut LFSR) {
+    fn setup(&mut self, maze: &Maze<S>, rng: &mut dyn Rng) {
         self.state = TimeTravelState::TimeTraveling;
         self.time_traveler.set_movement_time(self.movement_time());
-        self.snail.reset();
+        self.snail.place(maze.start_pos);
         self.path.clear();
-        self.path_drawer.reset();
-        self.time_traveler.setup(_maze, _lfsr);
+        self.path_drawer.place(maze.start_pos);
+        self.time_traveler.setup(maze, rng);
     }
 
-    fn step(&mut self, maze: &mut Maze<S>, lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
         match self.state {
-            TimeTravelState::TimeTraveling => match self.time_traveler.step(maze, lfsr) {
+            TimeTravelState::TimeTraveling => match self.time_traveler.step(maze, rng) {
                 SolveStatus::Solved(_) => {
                     self.state = TimeTravelState::DrawingPath;
                     self.path_drawer.pos = maze.end_pos;
@@ -273,7 +273,7 @@ where
                         self.path_drawer.direction.flip(),
                     ));
 
-                    if self.path_drawer.pos.x == 0 && self.path_drawer.pos.y == 0 {
+                    if self.path_drawer.pos == maze.start_pos {
                         self.state = TimeTravelState::Normal;
                         break;
                     }
@@ -312,7 +312,7 @@ where
         &mut self,
         animation_cycle: bool,
         movement_timer: f32,
-        lfsr: &mut LFSR,
+        rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
@@ -322,11 +322,11 @@ where
                 self.snail.draw(GRAYSCALE_PALETTE, true, 0.0, image, bx, by);
 
                 self.time_traveler
-                    .draw(animation_cycle, movement_timer, lfsr, image, bx, by);
+                    .draw(animation_cycle, movement_timer, rng, image, bx, by);
             }
             TimeTravelState::DrawingPath => {
                 for tile in &self.path {
-                    tile.draw(lfsr, image, bx, by);
+                    tile.draw(rng, image, bx, by);
                 }
 
                 self.snail.draw(GRAYSCALE_PALETTE, true, 0.0, image, bx, by);
@@ -342,7 +342,7 @@ where
             }
             TimeTravelState::Normal => {
                 for tile in &self.path {
-                    tile.draw(lfsr, image, bx, by);
+                    tile.draw(rng, image, bx, by);
                 }
 
                 self.snail.draw(
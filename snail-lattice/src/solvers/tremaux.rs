@@ -3,8 +3,11 @@ use std::collections::HashMap;
 use crate::{
     direction::Direction,
     image::Image,
-    lfsr::LFSR,
-    maze::{Maze, CELLS_PER_IDX, SNAIL_MOVEMENT_TIME},
+    maze::{
+        Maze, CELLS_PER_IDX, DOOR_MODIFIER, PREDATOR_MODIFIER, SHIFTING_MODIFIER,
+        SNAIL_MOVEMENT_TIME, TERRAIN_MODIFIER, TORUS_MODIFIER,
+    },
+    rng::Rng,
     snail::{Snail, DEFAULT_PALETTE},
     solvers::Solver,
     utils::Vec2,
@@ -85,6 +88,8 @@ where
     upgrades: u32,
     directions: [Option<Direction>; S * S],
     movement_time: f32,
+    terrain_cost: f32,
+    palette: [[u8; 3]; 6],
 }
 
 impl<const S: usize> Tremaux<S>
@@ -108,6 +113,8 @@ where
             directions: [None; S * S],
             is_backtracking: false,
             movement_time: SNAIL_MOVEMENT_TIME,
+            terrain_cost: 1.0,
+            palette: DEFAULT_PALETTE,
         }
     }
 
@@ -119,7 +126,7 @@ where
         &mut self,
         animation_cycle: bool,
         movement_timer: f32,
-        _lfsr: &mut LFSR,
+        _rng: &mut dyn Rng,
         image: &mut Image,
         bx: usize,
         by: usize,
@@ -129,7 +136,7 @@ where
         }
 
         self.snail.draw(
-            DEFAULT_PALETTE,
+            self.palette,
             animation_cycle,
             movement_timer / self.movement_time(),
             image,
@@ -138,13 +145,14 @@ where
         );
     }
 
-    fn setup(&mut self, maze: &Maze<S>, _lfsr: &mut LFSR) {
-        self.snail.reset();
+    fn setup(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
+        self.snail.place(maze.start_pos);
+        self.terrain_cost = 1.0;
         self.visited.clear();
         self.directions = maze.get_directions(maze.end_pos);
     }
 
-    fn step(&mut self, maze: &mut Maze<S>, lfsr: &mut LFSR) -> SolveStatus {
+    fn step(&mut self, maze: &mut Maze<S>, rng: &mut dyn Rng) -> SolveStatus {
         let cell = maze.get_cell(self.snail.pos.x, self.snail.pos.y);
         let valid_directions = cell.valid_directions();
 
@@ -169,7 +177,7 @@ where
                     }
                 }
 
-                self.snail.direction = choices[(lfsr.next() % choices.len() as u16) as usize];
+                self.snail.direction = choices[(rng.next() % choices.len() as u16) as usize];
             }
             // 2. Go back where we came from unless it's marked twice
             else if mark.directions[back_direction as usize] < 2 {
@@ -200,12 +208,15 @@ where
                     self.is_backtracking = true;
                 }
 
+                // the compass doesn't know the way while the goal is behind a locked door
                 let odds = (self.upgrades & 0b11) << 1;
-                if odds > 0 && lfsr.big() % 12 < odds as usize {
-                    self.snail.direction =
-                        self.directions[self.snail.pos.y * S + self.snail.pos.x].unwrap();
+                let compass = self.directions[self.snail.pos.y * S + self.snail.pos.x];
+                if let (true, Some(direction)) =
+                    (odds > 0 && rng.big() % 12 < odds as usize, compass)
+                {
+                    self.snail.direction = direction;
                 } else {
-                    self.snail.direction = choices[(lfsr.next() % choices.len() as u16) as usize];
+                    self.snail.direction = choices[(rng.next() % choices.len() as u16) as usize];
                 }
             }
 
@@ -226,19 +237,51 @@ where
         }
 
         self.snail.move_forward(maze);
+        self.terrain_cost = maze.get_terrain(self.snail.pos.x, self.snail.pos.y).cost();
 
         if self.snail.pos == maze.end_pos {
             SolveStatus::Solved(1)
+        } else if maze.visit(self.snail.pos.x, self.snail.pos.y).is_some() {
+            // the marks no longer make sense now that there's a new passage, so start exploring
+            // again from here
+            self.visited.clear();
+            self.directions = maze.get_directions(maze.end_pos);
+
+            SolveStatus::KeyCollected
         } else {
             SolveStatus::None
         }
     }
 
+    fn maze_changed(&mut self, maze: &Maze<S>) {
+        self.visited.clear();
+        self.directions = maze.get_directions(maze.end_pos);
+    }
+
     fn movement_time(&self) -> f32 {
         if self.is_backtracking && (self.upgrades & 0b100) != 0 {
-            self.movement_time / 2.0
+            self.movement_time * self.terrain_cost / 2.0
         } else {
-            self.movement_time
+            self.movement_time * self.terrain_cost
         }
     }
+
+    fn snail(&self) -> Option<&Snail<S>> {
+        Some(&self.snail)
+    }
+
+    // keeps its marks, so it doesn't explore the same dead ends all over again
+    fn caught(&mut self, maze: &Maze<S>, _rng: &mut dyn Rng) {
+        self.snail.place(maze.start_pos);
+        self.terrain_cost = 1.0;
+        self.is_backtracking = false;
+    }
+
+    fn set_palette(&mut self, palette: [[u8; 3]; 6]) {
+        self.palette = palette;
+    }
+
+    fn supported_modifiers() -> u32 {
+        TERRAIN_MODIFIER | DOOR_MODIFIER | SHIFTING_MODIFIER | TORUS_MODIFIER | PREDATOR_MODIFIER
+    }
 }
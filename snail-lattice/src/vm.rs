@@ -0,0 +1,520 @@
+// A tiny stack language for players to write their own snail in, see solvers::Scripted. A program
+// has one instruction per line, and anything after a '#' is a comment:
+//
+//   wall ahead     pushes 1 if there's a wall ahead of the snail, 0 if not. also left, right and
+//                  behind
+//   marks here     pushes the number of marks on the snail's cell. also ahead, left, right and
+//                  behind, which are 0 if there's a wall in the way
+//   mark           adds a mark to the snail's cell
+//   turn left      also right and around
+//   move           moves the snail forward, which ends its turn
+//
+//   push 3         pushes a number
+//   pop            throws away the top of the stack
+//   dup            pushes another copy of the top of the stack
+//   swap           swaps the top two values of the stack
+//   add, sub       pops two values and pushes their sum or difference
+//   eq, lt         pops two values and pushes 1 if they're equal, or the first is less than the
+//                  second, and 0 if not
+//   not            pops a value and pushes 1 if it was 0, and 0 if not
+//
+//   name:          a label to jump to
+//   jump name      carries on from the label
+//   jumpif name    pops a value and jumps to the label if it isn't 0
+//
+// Once the last instruction has run the program starts again from the top. The program is
+// compiled into a list of Ops with the labels worked out ahead of time, which a Vm then runs a
+// turn at a time. A turn is cut short with an error if it runs for more than a set number of
+// instructions without moving, so a program stuck in a loop can't freeze the game.
+
+use std::{collections::HashMap, fmt};
+
+// the most values the stack can hold
+pub const STACK_SIZE: usize = 64;
+// the most instructions a program can run in a single turn
+pub const INSTRUCTION_BUDGET: usize = 1000;
+
+// where something is, as seen by the snail
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    Here,
+    Ahead,
+    Left,
+    Right,
+    Behind,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Op {
+    Wall(Side),
+    Marks(Side),
+    Mark,
+    Turn(Side),
+    Move,
+
+    Push(i32),
+    Pop,
+    Dup,
+    Swap,
+    Add,
+    Sub,
+    Eq,
+    Lt,
+    Not,
+
+    Jump(usize),
+    JumpIf(usize),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CompileError {
+    NoInstructions,
+    UnknownInstruction { line: usize, word: String },
+    MissingArgument { line: usize, instruction: String },
+    UnexpectedWord { line: usize, word: String },
+    UnknownLabel { line: usize, label: String },
+    DuplicateLabel { line: usize, label: String },
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::NoInstructions => write!(f, "the program has no instructions"),
+            CompileError::UnknownInstruction { line, word } => {
+                write!(f, "line {line}: unknown instruction {word:?}")
+            }
+            CompileError::MissingArgument { line, instruction } => {
+                write!(f, "line {line}: {instruction} needs something after it")
+            }
+            CompileError::UnexpectedWord { line, word } => {
+                write!(f, "line {line}: unexpected {word:?}")
+            }
+            CompileError::UnknownLabel { line, label } => {
+                write!(f, "line {line}: there's no label called {label:?}")
+            }
+            CompileError::DuplicateLabel { line, label } => {
+                write!(f, "line {line}: {label:?} is already a label")
+            }
+        }
+    }
+}
+
+// something which went wrong while running a program, along with the line it happened on
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VmError {
+    StackOverflow { line: usize },
+    StackUnderflow { line: usize },
+    TooManyInstructions { line: usize },
+}
+
+impl VmError {
+    pub fn line(self) -> usize {
+        match self {
+            VmError::StackOverflow { line }
+            | VmError::StackUnderflow { line }
+            | VmError::TooManyInstructions { line } => line,
+        }
+    }
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            VmError::StackOverflow { line } => write!(f, "line {line}: the stack is full"),
+            VmError::StackUnderflow { line } => write!(f, "line {line}: the stack is empty"),
+            VmError::TooManyInstructions { line } => write!(
+                f,
+                "line {line}: ran {INSTRUCTION_BUDGET} instructions without moving"
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Program {
+    ops: Vec<Op>,
+    // the line each op came from, for errors
+    lines: Vec<usize>,
+}
+
+impl Program {
+    pub fn compile(source: &str) -> Result<Program, CompileError> {
+        // labels are found first, so jumps can go forwards
+        let mut labels = HashMap::new();
+        let mut instructions = vec![];
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let code = line.split('#').next().unwrap_or("");
+            let words: Vec<String> = code.split_whitespace().map(str::to_lowercase).collect();
+
+            match words.as_slice() {
+                [] => {}
+                [label] if label.ends_with(':') => {
+                    let label = label.trim_end_matches(':').to_string();
+
+                    if labels.insert(label.clone(), instructions.len()).is_some() {
+                        return Err(CompileError::DuplicateLabel {
+                            line: line_number,
+                            label,
+                        });
+                    }
+                }
+                _ => instructions.push((line_number, words)),
+            }
+        }
+
+        if instructions.is_empty() {
+            return Err(CompileError::NoInstructions);
+        }
+
+        let mut program = Program {
+            ops: vec![],
+            lines: vec![],
+        };
+
+        for (line, words) in instructions {
+            let instruction = words[0].as_str();
+            let argument = words.get(1).map(String::as_str);
+
+            if let Some(word) = words.get(2) {
+                return Err(CompileError::UnexpectedWord {
+                    line,
+                    word: word.clone(),
+                });
+            }
+
+            let unexpected = |word: &str| CompileError::UnexpectedWord {
+                line,
+                word: word.to_string(),
+            };
+
+            let side = |sides: &[Side]| match argument {
+                Some(word) => {
+                    let side = match word {
+                        "here" => Side::Here,
+                        "ahead" => Side::Ahead,
+                        "left" => Side::Left,
+                        "right" => Side::Right,
+                        "behind" | "around" => Side::Behind,
+                        _ => return Err(unexpected(word)),
+                    };
+
+                    if sides.contains(&side) {
+                        Ok(side)
+                    } else {
+                        Err(unexpected(word))
+                    }
+                }
+                None => Err(CompileError::MissingArgument {
+                    line,
+                    instruction: instruction.to_string(),
+                }),
+            };
+
+            let label = || match argument {
+                Some(label) => {
+                    labels
+                        .get(label)
+                        .copied()
+                        .ok_or_else(|| CompileError::UnknownLabel {
+                            line,
+                            label: label.to_string(),
+                        })
+                }
+                None => Err(CompileError::MissingArgument {
+                    line,
+                    instruction: instruction.to_string(),
+                }),
+            };
+
+            let op = match instruction {
+                "wall" => Op::Wall(side(&[Side::Ahead, Side::Left, Side::Right, Side::Behind])?),
+                "marks" => Op::Marks(side(&[
+                    Side::Here,
+                    Side::Ahead,
+                    Side::Left,
+                    Side::Right,
+                    Side::Behind,
+                ])?),
+                "turn" => Op::Turn(side(&[Side::Left, Side::Right, Side::Behind])?),
+                "push" => match argument {
+                    Some(word) => Op::Push(word.parse().map_err(|_| unexpected(word))?),
+                    None => {
+                        return Err(CompileError::MissingArgument {
+                            line,
+                            instruction: instruction.to_string(),
+                        })
+                    }
+                },
+                "jump" => Op::Jump(label()?),
+                "jumpif" => Op::JumpIf(label()?),
+                _ => {
+                    let op = match instruction {
+                        "mark" => Op::Mark,
+                        "move" => Op::Move,
+                        "pop" => Op::Pop,
+                        "dup" => Op::Dup,
+                        "swap" => Op::Swap,
+                        "add" => Op::Add,
+                        "sub" => Op::Sub,
+                        "eq" => Op::Eq,
+                        "lt" => Op::Lt,
+                        "not" => Op::Not,
+                        _ => {
+                            return Err(CompileError::UnknownInstruction {
+                                line,
+                                word: instruction.to_string(),
+                            })
+                        }
+                    };
+
+                    if let Some(word) = argument {
+                        return Err(unexpected(word));
+                    }
+
+                    op
+                }
+            };
+
+            program.ops.push(op);
+            program.lines.push(line);
+        }
+
+        Ok(program)
+    }
+}
+
+// what a program can sense and do to the world, apart from moving, which Vm::run returns for
+pub trait Body {
+    fn wall(&self, side: Side) -> bool;
+    fn marks(&self, side: Side) -> usize;
+    fn mark(&mut self);
+    fn turn(&mut self, side: Side);
+}
+
+#[derive(Default)]
+pub struct Vm {
+    pc: usize,
+    stack: Vec<i32>,
+}
+
+impl Vm {
+    pub fn reset(&mut self) {
+        self.pc = 0;
+        self.stack.clear();
+    }
+
+    // runs the program from where it left off until it moves, which the caller then does.
+    // stack_size is the most values the stack can hold, which can be more than STACK_SIZE
+    pub fn run(
+        &mut self,
+        program: &Program,
+        body: &mut dyn Body,
+        stack_size: usize,
+    ) -> Result<(), VmError> {
+        for _ in 0..INSTRUCTION_BUDGET {
+            let op = program.ops[self.pc];
+            let line = program.lines[self.pc];
+
+            self.pc = (self.pc + 1) % program.ops.len();
+
+            let push = match op {
+                Op::Wall(side) => Some(body.wall(side) as i32),
+                Op::Marks(side) => Some(body.marks(side).min(i32::MAX as usize) as i32),
+                Op::Mark => {
+                    body.mark();
+                    None
+                }
+                Op::Turn(side) => {
+                    body.turn(side);
+                    None
+                }
+                Op::Move => return Ok(()),
+
+                Op::Push(value) => Some(value),
+                Op::Pop => {
+                    self.pop(line)?;
+                    None
+                }
+                Op::Dup => {
+                    let value = self.pop(line)?;
+                    self.push(value, stack_size, line)?;
+                    Some(value)
+                }
+                Op::Swap => {
+                    let (b, a) = (self.pop(line)?, self.pop(line)?);
+                    self.push(b, stack_size, line)?;
+                    Some(a)
+                }
+                Op::Add => {
+                    let (b, a) = (self.pop(line)?, self.pop(line)?);
+                    Some(a.wrapping_add(b))
+                }
+                Op::Sub => {
+                    let (b, a) = (self.pop(line)?, self.pop(line)?);
+                    Some(a.wrapping_sub(b))
+                }
+                Op::Eq => {
+                    let (b, a) = (self.pop(line)?, self.pop(line)?);
+                    Some((a == b) as i32)
+                }
+                Op::Lt => {
+                    let (b, a) = (self.pop(line)?, self.pop(line)?);
+                    Some((a < b) as i32)
+                }
+                Op::Not => Some((self.pop(line)? == 0) as i32),
+
+                Op::Jump(target) => {
+                    self.pc = target % program.ops.len();
+                    None
+                }
+                Op::JumpIf(target) => {
+                    if self.pop(line)? != 0 {
+                        self.pc = target % program.ops.len();
+                    }
+                    None
+                }
+            };
+
+            if let Some(value) = push {
+                self.push(value, stack_size, line)?;
+            }
+        }
+
+        Err(VmError::TooManyInstructions {
+            line: program.lines[self.pc],
+        })
+    }
+
+    fn pop(&mut self, line: usize) -> Result<i32, VmError> {
+        self.stack.pop().ok_or(VmError::StackUnderflow { line })
+    }
+
+    fn push(&mut self, value: i32, stack_size: usize, line: usize) -> Result<(), VmError> {
+        if self.stack.len() >= stack_size {
+            return Err(VmError::StackOverflow { line });
+        }
+
+        self.stack.push(value);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Body, CompileError, Program, Side, Vm, VmError, STACK_SIZE};
+
+    // a snail in an empty field, which counts its turns in its marks
+    struct Field {
+        turns: usize,
+    }
+
+    impl Body for Field {
+        fn wall(&self, _side: Side) -> bool {
+            false
+        }
+
+        fn marks(&self, _side: Side) -> usize {
+            self.turns
+        }
+
+        fn mark(&mut self) {}
+
+        fn turn(&mut self, _side: Side) {
+            self.turns += 1;
+        }
+    }
+
+    fn run(source: &str) -> Result<(), VmError> {
+        let program = Program::compile(source).unwrap();
+        Vm::default().run(&program, &mut Field { turns: 0 }, STACK_SIZE)
+    }
+
+    #[test]
+    fn runs_until_moving() {
+        let program = Program::compile(
+            "# turns right twice between moves\n\
+             start:\n\
+             \tturn right\n\
+             \tmarks here\n\
+             \tpush 2\n\
+             \tlt\n\
+             \tjumpif start\n\
+             \tmove",
+        )
+        .unwrap();
+
+        let mut field = Field { turns: 0 };
+        let mut vm = Vm::default();
+
+        vm.run(&program, &mut field, STACK_SIZE).unwrap();
+        assert_eq!(field.turns, 2);
+
+        // carries on from after the move, going back to the top
+        vm.run(&program, &mut field, STACK_SIZE).unwrap();
+        assert_eq!(field.turns, 3);
+    }
+
+    #[test]
+    fn compile_errors() {
+        assert_eq!(
+            Program::compile("# nothing\n\n").err(),
+            Some(CompileError::NoInstructions)
+        );
+
+        assert_eq!(
+            Program::compile("move\nfly").err(),
+            Some(CompileError::UnknownInstruction {
+                line: 2,
+                word: "fly".to_string()
+            })
+        );
+
+        assert_eq!(
+            Program::compile("turn up").err(),
+            Some(CompileError::UnexpectedWord {
+                line: 1,
+                word: "up".to_string()
+            })
+        );
+
+        assert_eq!(
+            Program::compile("move\n\nwall").err(),
+            Some(CompileError::MissingArgument {
+                line: 3,
+                instruction: "wall".to_string()
+            })
+        );
+
+        assert_eq!(
+            Program::compile("start:\nmove\njump end").err(),
+            Some(CompileError::UnknownLabel {
+                line: 3,
+                label: "end".to_string()
+            })
+        );
+
+        assert_eq!(
+            Program::compile("a:\nmove\na:").err(),
+            Some(CompileError::DuplicateLabel {
+                line: 3,
+                label: "a".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn runtime_errors() {
+        assert_eq!(run("push 1\nadd"), Err(VmError::StackUnderflow { line: 2 }));
+        assert_eq!(
+            run("push 1\nturn left"),
+            Err(VmError::StackOverflow { line: 1 })
+        );
+        assert_eq!(
+            run("a:\nturn left\njump a\nmove"),
+            Err(VmError::TooManyInstructions { line: 2 })
+        );
+    }
+}
@@ -255,9 +255,28 @@ const AutoMazeDisplay: Component<{ key: ShopKey, count: number }> = (props) => {
 
     addEventListener('fullscreenchange', togglefullscreen);
 
+    // share codes let other players watch the same maze, see share.rs. the first maze is the one
+    // that gets shared, and loading a code replaces the first maze
+    const shareCode = () => latticePostMessage(LATTICE_WORKER_STORE[props.key], { type: "share-code", index: 0 });
+    const loadCode = () => {
+        let code = prompt("Enter a maze code");
+        if (code) latticePostMessage(LATTICE_WORKER_STORE[props.key], { type: "load-code", code });
+    };
+
+    const codeListener = (msg: MessageEvent<LatticeWorkerResponse>) => {
+        if (msg.data.type == "share-code") {
+            prompt("Anyone with this code gets the first maze", msg.data.code);
+        } else if (msg.data.type == "code-error") {
+            alert(msg.data.error);
+        }
+    };
+
+    LATTICE_WORKER_STORE[props.key].addEventListener("message", codeListener);
+
     onCleanup(() => {
         clearInterval(intervalId)
         removeEventListener('fullscreenchange', togglefullscreen);
+        LATTICE_WORKER_STORE[props.key].removeEventListener("message", codeListener);
     });
 
     const averageFps = () => {
@@ -276,6 +295,13 @@ const AutoMazeDisplay: Component<{ key: ShopKey, count: number }> = (props) => {
                 </div>
 
                 <div class="text-center ml-auto flex my-auto">
+                    <button class="text-lg font-display font-bold mr-4 px-4 py-2 hover:bg-white hover:text-black transition-colors" onclick={shareCode}>
+                        Share
+                    </button>
+                    <button class="text-lg font-display font-bold mr-4 px-4 py-2 hover:bg-white hover:text-black transition-colors" onclick={loadCode}>
+                        Load
+                    </button>
+
                     <button class="text-lg font-display font-bold mr-4 px-4 py-2 hover:bg-white hover:text-black transition-colors w-20" onclick={toggleCollapsed}>
                         {collapsed() ? "Show" : "Hide"}
                     </button>
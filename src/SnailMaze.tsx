@@ -1,4 +1,4 @@
-import { Component, createEffect, createSignal, For, onMount, useContext } from "solid-js";
+import { batch, Component, createEffect, createSignal, For, onMount, useContext } from "solid-js";
 import init, { Game } from "../snail-lattice/pkg/snail_lattice";
 import { PowerupContext } from "./App";
 import { ScoreContext } from "./ScoreProvider";
@@ -74,6 +74,42 @@ const keyReleased = (e: KeyboardEvent) => {
     }
 };
 
+// the player's local date, like "2026-10-18", which picks the daily challenge's levels
+const today = () => {
+    let date = new Date();
+    let pad = (n: number) => n.toString().padStart(2, "0");
+
+    return `${date.getFullYear()}-${pad(date.getMonth() + 1)}-${pad(date.getDate())}`;
+};
+
+// best results for the daily challenge are kept per day and game, see daily.rs
+interface DailyBest {
+    time?: number;
+    score?: string;
+}
+
+const dailyKey = (date: string, gameMode: number) => `daily-best-${date}-${gameMode}`;
+
+const loadDailyBest = (date: string, gameMode: number): DailyBest =>
+    JSON.parse(localStorage.getItem(dailyKey(date, gameMode)) ?? "{}");
+
+const saveDailyBest = (date: string, gameMode: number, best: DailyBest) => {
+    // nobody can play yesterday's challenge anymore
+    for (let key of Object.keys(localStorage)) {
+        if (key.startsWith("daily-best-") && !key.startsWith(`daily-best-${date}-`)) {
+            localStorage.removeItem(key);
+        }
+    }
+
+    localStorage.setItem(dailyKey(date, gameMode), JSON.stringify(best));
+};
+
+const formatDailyBest = (best: DailyBest) => {
+    if (best.time !== undefined) return `best ${(best.time / 1000).toFixed(1)}s`;
+    if (best.score !== undefined) return `best ${formatNumber(BigInt(best.score), false)} fragments`;
+    return "no best yet";
+};
+
 const MobileControls: Component = () => {
     return (
         <div class="grid z-20 grid-cols-3 grid-rows-3 fixed md:hidden aspect-square right-4 bottom-4 text-5xl w-[196px] h-[196px] opacity-70 select-none">
@@ -163,6 +199,8 @@ const SnailMaze: Component<SnailMazeProps> = (props) => {
     const [_powerup, setPowerup] = useContext(PowerupContext);
     const [gameMode, setGameMode] = createStoredSignal("selected-game", 0);
     const [recentScores, setRecentScores] = createSignal<{ score: bigint, bonus: boolean }[]>([]);
+    const [daily, setDaily] = createSignal(false);
+    const [dailyBest, setDailyBest] = createSignal<DailyBest>({});
 
     let game: Game;
     let prevTime: number;
@@ -195,6 +233,8 @@ const SnailMaze: Component<SnailMazeProps> = (props) => {
         // @ts-ignore: this does work, but due to a wasm-bindgen we cannot make the signature take a Uint8ClampedArray
         let solve = game.render(buffer, new Uint32Array(movement), dt);
 
+        if (daily()) updateDailyBest();
+
         if (solve != 0n) {
             let newScore = solve < 0n ? -solve : solve;
             setScore(score() + newScore);
@@ -235,14 +275,71 @@ const SnailMaze: Component<SnailMazeProps> = (props) => {
         requestAnimationFrame(render);
     };
 
+    // every game has a daily challenge of its own
+    const startDaily = () => {
+        let date = today();
+        let best = loadDailyBest(date, gameMode());
+
+        game.start_daily(date, gameMode());
+        game.set_daily_best_time(best.time);
+        game.set_daily_best_score(best.score !== undefined ? BigInt(best.score) : undefined);
+        setDailyBest(best);
+    };
+
+    // saves the game's best results whenever it beats them
+    const updateDailyBest = () => {
+        let time = game.daily_best_time();
+        let score = game.daily_best_score()?.toString();
+
+        if (time !== dailyBest().time || score !== dailyBest().score) {
+            let best = { time, score };
+
+            saveDailyBest(game.daily_date(), gameMode(), best);
+            setDailyBest(best);
+        }
+    };
+
+    const toggleDaily = () => {
+        if (daily()) game.stop_daily();
+        setDaily(!daily());
+    };
+
     createEffect(() => {
         // idk why i need this here, but sure
         gameMode();
 
-        if (game)
+        if (!game) return;
+
+        if (daily())
+            startDaily();
+        // loading a share code switches the game by itself
+        else if (game.game_type() != gameMode())
             game.set_game(gameMode());
     });
 
+    // only the maze and wolfenstein have levels which can be shared, see share.rs
+    const shareCode = () => {
+        let code = game.share_code();
+        if (code) prompt("Anyone with this code gets the same level", code);
+    };
+
+    const loadCode = () => {
+        let code = prompt("Enter a level code");
+        if (!code) return;
+
+        try {
+            game.load_code(code);
+
+            // a shared level isn't part of the daily challenge
+            batch(() => {
+                setDaily(false);
+                setGameMode(game.game_type());
+            });
+        } catch (error) {
+            alert(error);
+        }
+    };
+
     onMount(() => {
         updateScale();
 
@@ -301,6 +398,13 @@ const SnailMaze: Component<SnailMazeProps> = (props) => {
                                 <button class={`p-2 hover:bg-white aspect-square text-2xl transition-colors ${UPGRADES[upgrade.key].order + 1 === gameMode() ? "bg-white" : ""}`} onClick={() => setGameMode(UPGRADES[upgrade.key].order + 1)}>{UPGRADES[upgrade.key].icon}</button>
                                 }</For>
                         </div>
+
+                        <div class="flex ml-auto pr-4">
+                            {daily() && <span class="p-2 text-white font-display my-auto">{formatDailyBest(dailyBest())}</span>}
+                            <button class={`p-2 aspect-square text-2xl transition-colors ${daily() ? "bg-white" : "hover:bg-white"}`} onClick={toggleDaily}>📅</button>
+                            <button class="p-2 hover:bg-white text-white hover:text-black font-display font-bold px-4 transition-colors" onClick={shareCode}>Share</button>
+                            <button class="p-2 hover:bg-white text-white hover:text-black font-display font-bold px-4 transition-colors" onClick={loadCode}>Load</button>
+                        </div>
                     </div>
                 </div>
                 :
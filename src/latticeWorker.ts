@@ -11,7 +11,15 @@ interface SnailLattice {
     get_dimensions: (count: number) => Uint32Array;
     get_solve_count: () => Uint32Array;
     set_upgrades: (upgrades: number) => void;
+    set_mode: (mode: number) => void;
     set_width: (width: number) => void;
+    share_code: (index: number) => string;
+}
+
+// a lattice class from lattice.rs, which can be started from a share code
+interface SnailLatticeClass<T extends SnailLattice> {
+    new(width: number, seed: number): T;
+    from_code: (width: number, code: string) => T;
 }
 
 // This class stores an array of SnailLattices, and manages the web worker
@@ -25,6 +33,7 @@ class LatticeList<T extends SnailLattice> {
     width: number;
     prevTick: number;
     tickRate: number = 1;
+    upgrades: number = 0;
     score: number;
 
     get latticeCount(): number {
@@ -56,9 +65,15 @@ class LatticeList<T extends SnailLattice> {
     }
 
     setUpgrades(upgrades: number) {
+        this.upgrades = upgrades;
         this.lattice.set_upgrades(upgrades);
     }
 
+    // see Solver::set_mode, e.g. 1 runs the learning snails on a real genetic algorithm
+    setMode(mode: number) {
+        this.lattice.set_mode(mode);
+    }
+
     // tick everything
     tick(): number {
         let now = performance.now();
@@ -119,62 +134,61 @@ export type LatticeWorkerMessage =
     | { type: "render", pages: { page: number, buffer: Uint8ClampedArray }[] }
     | { type: "reset" }
     | { type: "set-upgrades", upgrades: number }
+    | { type: "set-mode", mode: number }
+    | { type: "share-code", index: number }
+    | { type: "load-code", code: string }
     | { type: "alter", diff: number }
     | { type: "get-count" };
 
 export type LatticeWorkerResponse =
     | { type: "score", score: number, solves: Uint32Array, mazeType: ShopKey }
     | { type: "render", pages: { page: number, buffer: Uint8ClampedArray }[], mazeType: ShopKey }
-    | { type: "lattice-updated", width: number, height: number, latticeCount: number };
+    | { type: "lattice-updated", width: number, height: number, latticeCount: number }
+    | { type: "share-code", code: string, mazeType: ShopKey }
+    | { type: "code-error", error: string, mazeType: ShopKey };
 
 let LATTICE: LatticeList<SnailLattice>;
 
+// starts from the share code if there is one, which throws if the code is wrong
+function createLattice(mazeType: ShopKey, code?: string): LatticeList<SnailLattice> {
+    const build = <T extends SnailLattice>(lattice: SnailLatticeClass<T>, width: number): T =>
+        code ? lattice.from_code(width, code) : new lattice(width, randomSeed());
+
+    switch (mazeType) {
+        case "random-walk":
+            return new LatticeList("random-walk", build(RandomWalkLattice, 8), 1, 8);
+        case "random-teleport":
+            return new LatticeList("random-teleport", build(RandomTeleportLattice, 5), 1, 5);
+        case "learning":
+            return new LatticeList("learning", build(LearningLattice, 3), 1, 3);
+        case "hold-left":
+            return new LatticeList("hold-left", build(HoldLeftLattice, 4), 1, 4);
+        case "inverted":
+            return new LatticeList("inverted", build(InvertedLattice, 4), 1, 4);
+        case "tremaux":
+            return new LatticeList("tremaux", build(TremauxLattice, 3), 1, 3);
+        case "rpg":
+            return new LatticeList("rpg", build(RpgLattice, 3), 1, 3);
+        case "time-travel":
+            return new LatticeList("time-travel", build(TimeTravelLattice, 3), 1, 3);
+        case "clone":
+            return new LatticeList("clone", build(CloneLattice, 2), 1, 2);
+        case "meta":
+            return new LatticeList("meta", build(MetaLattice, 2), 1, 2);
+        case "demolitionist":
+            return new LatticeList("demolitionist", build(DemolitionistLattice, 3), 1, 3);
+        case "flying":
+            return new LatticeList("flying", build(FlyingLattice, 3), 1, 3);
+        case "telepathic":
+            return new LatticeList("telepathic", build(TelepathicLattice, 3), 1, 3);
+        case "automaton":
+            return new LatticeList("automaton", build(AutomatonLattice, 3), 1, 3);
+    }
+}
+
 function setupLattice(mazeType: ShopKey) {
     init().then(() => {
-        switch (mazeType) {
-            case "random-walk":
-                LATTICE = new LatticeList("random-walk", new RandomWalkLattice(8, randomSeed()), 1, 8);
-                break;
-            case "random-teleport":
-                LATTICE = new LatticeList("random-teleport", new RandomTeleportLattice(5, randomSeed()), 1, 5);
-                break;
-            case "learning":
-                LATTICE = new LatticeList("learning", new LearningLattice(3, randomSeed()), 1, 3);
-                break;
-            case "hold-left":
-                LATTICE = new LatticeList("hold-left", new HoldLeftLattice(4, randomSeed()), 1, 4);
-                break;
-            case "inverted":
-                LATTICE = new LatticeList("inverted", new InvertedLattice(4, randomSeed()), 1, 4);
-                break;
-            case "tremaux":
-                LATTICE = new LatticeList("tremaux", new TremauxLattice(3, randomSeed()), 1, 3);
-                break;
-            case "rpg":
-                LATTICE = new LatticeList("rpg", new RpgLattice(3, randomSeed()), 1, 3);
-                break;
-            case "time-travel":
-                LATTICE = new LatticeList("time-travel", new TimeTravelLattice(3, randomSeed()), 1, 3);
-                break;
-            case "clone":
-                LATTICE = new LatticeList("clone", new CloneLattice(2, randomSeed()), 1, 2);
-                break;
-            case "meta":
-                LATTICE = new LatticeList("meta", new MetaLattice(2, randomSeed()), 1, 2);
-                break;
-            case "demolitionist":
-                LATTICE = new LatticeList("demolitionist", new DemolitionistLattice(3, randomSeed()), 1, 3);
-                break;
-            case "flying":
-                LATTICE = new LatticeList("flying", new FlyingLattice(3, randomSeed()), 1, 3);
-                break;
-            case "telepathic":
-                LATTICE = new LatticeList("telepathic", new TelepathicLattice(3, randomSeed()), 1, 3);
-                break;
-            case "automaton":
-                LATTICE = new LatticeList("automaton", new AutomatonLattice(3, randomSeed()), 1, 3);
-                break;
-        }
+        LATTICE = createLattice(mazeType);
 
         setInterval(() => {
             let score = LATTICE.tick() + LATTICE.score;
@@ -202,6 +216,37 @@ function setupLattice(mazeType: ShopKey) {
 
 let messageQueue: LatticeWorkerMessage[] = [];
 
+// a code for a single maze, as it was when it was generated
+function shareCode(index: number) {
+    try {
+        postMessage({ type: "share-code", code: LATTICE.lattice.share_code(index), mazeType: LATTICE.mazeType });
+    } catch (error) {
+        postMessage({ type: "code-error", error: String(error), mazeType: LATTICE.mazeType });
+    }
+}
+
+// swaps in a lattice whose first maze is the one the code was made from, keeping the snails and
+// upgrades the player already has
+function loadCode(code: string) {
+    let lattice: LatticeList<SnailLattice>;
+
+    try {
+        lattice = createLattice(LATTICE.mazeType, code);
+    } catch (error) {
+        postMessage({ type: "code-error", error: String(error), mazeType: LATTICE.mazeType });
+        return;
+    }
+
+    lattice.score = LATTICE.score;
+    lattice.setTickRate(LATTICE.tickRate);
+    lattice.setUpgrades(LATTICE.upgrades);
+    lattice.setWidth(LATTICE.width);
+    lattice.alter(LATTICE.lattice.count());
+
+    LATTICE = lattice;
+    LATTICE.update();
+}
+
 function processMessage(msg: LatticeWorkerMessage) {
     switch (msg.type) {
         case "setup":
@@ -225,10 +270,22 @@ function processMessage(msg: LatticeWorkerMessage) {
             if (!LATTICE) messageQueue.push(msg);
             else LATTICE.setUpgrades(msg.upgrades);
             break;
+        case "set-mode":
+            if (!LATTICE) messageQueue.push(msg);
+            else LATTICE.setMode(msg.mode);
+            break;
         case "alter":
             if (!LATTICE) messageQueue.push(msg);
             else LATTICE.alter(msg.diff);
             break;
+        case "share-code":
+            if (!LATTICE) messageQueue.push(msg);
+            else shareCode(msg.index);
+            break;
+        case "load-code":
+            if (!LATTICE) messageQueue.push(msg);
+            else loadCode(msg.code);
+            break;
 
         // we intentionally don't add this to the message queue because if this is
         // received after the setup went through when it will get the original "add
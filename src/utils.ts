@@ -24,7 +24,7 @@ export function createStoredSignal<T>(
 }
 
 export function randomSeed(): number {
-    return self.crypto.getRandomValues(new Uint16Array(1))[0];
+    return self.crypto.getRandomValues(new Uint32Array(1))[0];
 }
 
 export function createLocalStore<T extends object>(